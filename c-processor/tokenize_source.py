@@ -0,0 +1,75 @@
+"""Tokenizes a C file without preprocessing it, writing the token file
+`fixing-rs-main --lang c` takes. Preprocessor directives are copied as they
+are, one per line, so that macros are known to the fixer and the directives
+are written back in place in its output.
+
+Usage: python tokenize_source.py INPUT.c OUTPUT.tokens
+"""
+
+import sys
+
+import pycparser
+
+from process_all import ST
+
+
+def directive_lines(code: str) -> list[tuple[bool, str]]:
+    """Splits `code` into directives, with their continuation lines joined,
+    and runs of other lines."""
+    result = []
+    lines = code.splitlines()
+    i = 0
+    while i < len(lines):
+        line = lines[i]
+        i += 1
+        if line.lstrip().startswith("#"):
+            while line.endswith("\\") and i < len(lines):
+                line = line[:-1] + " " + lines[i].strip()
+                i += 1
+            result.append((True, line.strip()))
+        else:
+            result.append((False, line + "\n"))
+    return result
+
+
+def tokenize_source(code: str) -> list[str]:
+    lexer = pycparser.c_lexer.CLexer(
+        lambda x, y, z: None, lambda: None, lambda: None, lambda x: False
+    )
+    lexer.build()
+    result = []
+
+    def lex(text: str):
+        lexer.input(text)
+        while True:
+            token = lexer.token()
+            if token is None:
+                break
+            assert "\t" not in token.value
+            if token.type in ST:
+                result.append(f"ST\t{ST[token.type]}\t{token.value}")
+            else:
+                result.append(f"LT\t{token.value}\t{token.value}")
+
+    pending = ""
+    for is_directive, line in directive_lines(code):
+        if is_directive:
+            lex(pending)
+            pending = ""
+            result.append(line)
+        else:
+            pending += line
+    lex(pending)
+    return result
+
+
+def main():
+    input_file, output_file = sys.argv[1:]
+    with open(input_file) as fin:
+        lines = tokenize_source(fin.read())
+    with open(output_file, "w") as fout:
+        fout.writelines(line + "\n" for line in lines)
+
+
+if __name__ == "__main__":
+    main()
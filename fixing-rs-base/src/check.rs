@@ -0,0 +1,281 @@
+use crate::{
+    containers::Map,
+    fixing::{collect_stats, DoFix, FixTaskError, FixTaskInfo, FixTaskResult},
+    grammar::{Grammar, GrammarRuleLength, SymbolRef},
+    naming::NamingScope,
+    props::UnionProp,
+    reachability::{
+        find, Edge, ErrorProximity, GProcessor, GReachability, GReachabilityArena,
+        GReachabilityOptions, SProcessor, SReachability, SReachabilityArena,
+    },
+    tokenizer::Token,
+    utils::HeapScope,
+};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::{self, Display},
+    time::Instant,
+};
+
+/// Whether a program is valid as it is, or where it stops being valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckResult {
+    Valid,
+    /// No program starts with the tokens up to `location`, the index of the
+    /// first token that cannot follow them, or of the end of the input when
+    /// the program is only incomplete. Syntactic props are not compared
+    /// across rules here, so a location past the actual error may be given
+    /// for grammars relying on them.
+    Syntactic {
+        location: usize,
+        /// The token at `location`, if there is one.
+        token: Option<String>,
+    },
+    /// The program parses but every derivation of it is rejected by the
+    /// semantic processor; the rejection reaching furthest into the input is
+    /// given.
+    Semantic(Rejection),
+}
+
+/// Props of the tokens from `begin` to `end` that the semantic processor
+/// found none of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    pub begin: usize,
+    pub end: usize,
+    pub reason: String,
+}
+
+impl Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckResult::Valid => write!(f, "valid"),
+            CheckResult::Syntactic {
+                location,
+                token: Some(token),
+            } => write!(
+                f,
+                "syntax error at token {}: unexpected `{}`",
+                location, token
+            ),
+            CheckResult::Syntactic {
+                location,
+                token: None,
+            } => write!(
+                f,
+                "syntax error at token {}: unexpected end of input",
+                location
+            ),
+            CheckResult::Semantic(rejection) => write!(
+                f,
+                "semantic error at tokens {}..{}: {}",
+                rejection.begin, rejection.end, rejection.reason
+            ),
+        }
+    }
+}
+
+/// The rejection of the original tokens ending furthest into the input,
+/// the innermost one among those ending at the same token.
+#[derive(Default)]
+pub(crate) struct FurthestRejection(RefCell<Option<Rejection>>);
+
+impl FurthestRejection {
+    /// Records a rejection, only making its reason if it is kept.
+    pub(crate) fn record(&self, begin: usize, end: usize, reason: impl FnOnce() -> String) {
+        let mut rejection = self.0.borrow_mut();
+        let further = match *rejection {
+            Some(ref x) => (end, begin) > (x.end, x.begin),
+            None => true,
+        };
+        if further {
+            *rejection = Some(Rejection {
+                begin,
+                end,
+                reason: reason(),
+            });
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<Rejection> {
+        self.0.borrow().clone()
+    }
+}
+
+/// The number of tokens of the longest prefix of the input some program
+/// starts with, from the edges of length 0.
+///
+/// Each symbol at each location gets the furthest location a program
+/// prefix derived from it reaches: the end of its edges, and for each rule,
+/// how far its first symbol reaches, or its second one after an edge of
+/// the first. Locations are done from the last one, so that each location
+/// only depends on itself, which is iterated to a fixed point, and on
+/// locations already done.
+fn viable_prefix<'a, 'b, PG, GProc>(
+    grammar: &'a Grammar<'a>,
+    greachability: &GReachability<'a, 'b, '_, PG, GProc>,
+    token_length: usize,
+) -> usize
+where
+    PG: UnionProp,
+    GProc: GProcessor<PG = PG>,
+{
+    let symbols = grammar.get_symbol_ref();
+    let mut reach: Vec<Map<SymbolRef<'a>, usize>> = vec![Map::default(); token_length + 1];
+    let furthest =
+        |reach: &Vec<Map<SymbolRef<'a>, usize>>, symbol: SymbolRef<'a>, begin: usize| match reach
+            [begin]
+            .get(&symbol)
+        {
+            Some(x) => *x,
+            None => greachability
+                .edges_from(symbol, begin, 0)
+                .map(|x| x.end())
+                .max()
+                .unwrap_or(begin),
+        };
+    for begin in (0..=token_length).rev() {
+        loop {
+            let mut changed = false;
+            for (_, symbol) in symbols.non_terminals.iter() {
+                let current = furthest(&reach, *symbol, begin);
+                let mut next = current;
+                for rule in symbol.rules(grammar).iter() {
+                    match rule.rule_type().length() {
+                        GrammarRuleLength::Zero => {}
+                        GrammarRuleLength::One => {
+                            next = next.max(furthest(&reach, rule.right1().unwrap(), begin));
+                        }
+                        GrammarRuleLength::Two => {
+                            let left = rule.right1().unwrap();
+                            next = next.max(furthest(&reach, left, begin));
+                            for edge in greachability.edges_from(left, begin, 0) {
+                                let right = rule.right2().unwrap();
+                                next = next.max(furthest(&reach, right, edge.end()));
+                            }
+                        }
+                    }
+                }
+                changed |= next != current;
+                reach[begin].insert(*symbol, next);
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+    furthest(&reach, symbols.start_symbol, 0)
+}
+
+fn do_check_impl<'a, GProc, SProc, PG, T, E>(
+    grammar: &'a Grammar<'a>,
+    tokens: &Vec<Token<'a, '_>>,
+    gproc: &GProc,
+    sproc: &SProc,
+    time_before_load: Instant,
+) -> Result<FixTaskResult, FixTaskError<T, E>>
+where
+    PG: UnionProp + Send + Sync,
+    GProc: GProcessor<PG = PG> + Sync,
+    SProc: SProcessor<PG = PG>,
+    T: Error,
+    E: Error,
+{
+    let time_after_load = Instant::now();
+
+    let heap = HeapScope::new();
+    let greachability_arena = GReachabilityArena::new();
+    let sreachability_arena = SReachabilityArena::new();
+    let mut syntactic_reachability = GReachability::new(
+        grammar,
+        &greachability_arena,
+        tokens,
+        gproc,
+        0,
+        GReachabilityOptions::default(),
+    );
+    syntactic_reachability.update_until(0);
+    let mut sreachability = SReachability::new(&sreachability_arena);
+    let proximity = ErrorProximity::new(Vec::new());
+    let time_after_reachability_built = Instant::now();
+
+    let start_edges = find(
+        sproc,
+        &sreachability_arena,
+        &syntactic_reachability,
+        0,
+        0,
+        &mut sreachability,
+        &proximity,
+    );
+    let time_after_find = vec![Instant::now()];
+
+    let parsed = match syntactic_reachability.get_start_edges().first() {
+        Some(x) => !x.is_empty(),
+        None => false,
+    };
+    let check = if !start_edges.is_empty() {
+        CheckResult::Valid
+    } else if parsed {
+        // Every derivation failing ends at a processor method giving no
+        // props, which records a rejection.
+        CheckResult::Semantic(sreachability_arena.rejection.get().unwrap_or(Rejection {
+            begin: 0,
+            end: tokens.len(),
+            reason: "no valid derivation".to_string(),
+        }))
+    } else {
+        let location = viable_prefix(grammar, &syntactic_reachability, tokens.len());
+        CheckResult::Syntactic {
+            location,
+            token: tokens.get(location).map(|x| x.literal.to_string()),
+        }
+    };
+
+    let stats = collect_stats(
+        &syntactic_reachability,
+        &greachability_arena,
+        &sreachability_arena,
+        heap,
+    );
+    Ok(FixTaskResult {
+        time_before_load,
+        time_after_load,
+        time_after_reachability_built,
+        time_after_find,
+        found_length: matches!(check, CheckResult::Valid).then(|| 0),
+        outputs: None,
+        alternatives: Vec::new(),
+        input: tokens.iter().map(|x| x.literal.to_string()).collect(),
+        stats,
+        check: Some(check),
+    })
+}
+
+/// Checks the input as it is instead of fixing it: only edges of length 0
+/// are built, and the result holds a [`CheckResult`].
+pub struct DoCheckImpl;
+
+impl DoFix for DoCheckImpl {
+    fn do_fix<'a, GProc, SProc, PG, T, E>(
+        self,
+        grammar: &'a Grammar<'a>,
+        _source: Option<&str>,
+        tokens: &Vec<Token<'a, '_>>,
+        gproc: &GProc,
+        sproc: &SProc,
+        _naming_scope: NamingScope,
+        _info: &FixTaskInfo,
+        time_before_load: Instant,
+    ) -> Result<FixTaskResult, FixTaskError<T, E>>
+    where
+        PG: UnionProp + Send + Sync,
+        GProc: GProcessor<PG = PG> + Sync,
+        SProc: SProcessor<PG = PG>,
+        T: Error,
+        E: Error,
+    {
+        do_check_impl(grammar, tokens, gproc, sproc, time_before_load)
+    }
+}
@@ -1,4 +1,104 @@
-use std::collections::{HashMap, HashSet};
+use crate::utils::Pointer;
+use indexmap::{IndexMap, IndexSet};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt::Debug,
+    hash::{BuildHasherDefault, Hash},
+    slice::Iter,
+};
 
-pub type Map<K, V> = HashMap<K, V>;
-pub type Set<K> = HashSet<K>;
+/// Hashed with fixed keys. Keys are often arena pointers hashed by address,
+/// so the iteration order may still change from run to run; use
+/// [`OrderedMap`] or [`PointerSet`] where it reaches the result.
+pub type Map<K, V> = HashMap<K, V, BuildHasherDefault<DefaultHasher>>;
+pub type Set<K> = HashSet<K, BuildHasherDefault<DefaultHasher>>;
+
+/// Iteration follows insertion order, so that which of several fixes of
+/// equal length gets reported does not depend on where the arenas were
+/// allocated.
+pub type OrderedMap<K, V> = IndexMap<K, V>;
+pub type OrderedSet<K> = IndexSet<K>;
+
+/// Arena pointers, told apart by the values they point to, in insertion
+/// order, such as the derivations of an edge. Most hold a few pointers and
+/// are only a vector; larger ones also get a hash index.
+pub struct PointerSet<'a, T> {
+    items: Vec<Pointer<'a, T>>,
+    index: Option<Box<Set<&'a T>>>,
+}
+
+impl<'a, T> PointerSet<'a, T>
+where
+    T: Eq + Hash,
+{
+    const INDEX_FROM: usize = 16;
+
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            index: None,
+        }
+    }
+
+    /// Adds `item` unless an equal value is in already, and returns whether
+    /// it was added.
+    pub fn insert(&mut self, item: Pointer<'a, T>) -> bool {
+        let found = match self.index {
+            Some(ref index) => index.contains(item.ptr()),
+            None => self.items.iter().any(|x| x.ptr() == item.ptr()),
+        };
+        if found {
+            return false;
+        }
+        self.items.push(item);
+        match self.index {
+            Some(ref mut index) => {
+                index.insert(item.ptr());
+            }
+            None if self.items.len() > Self::INDEX_FROM => {
+                self.index = Some(Box::new(self.items.iter().map(|x| x.ptr()).collect()));
+            }
+            None => {}
+        }
+        true
+    }
+
+    pub fn first(&self) -> Option<Pointer<'a, T>> {
+        self.items.first().copied()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Pointer<'a, T>> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.index = None;
+    }
+}
+
+impl<'a, T> Default for PointerSet<'a, T>
+where
+    T: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Debug for PointerSet<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.items.iter()).finish()
+    }
+}
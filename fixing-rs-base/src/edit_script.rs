@@ -0,0 +1,76 @@
+/// Replaces the input tokens `begin..end` by `replacement`. An empty range is
+/// an insertion before token `begin`, an empty replacement a deletion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenEdit {
+    pub begin: usize,
+    pub end: usize,
+    pub replacement: Vec<String>,
+}
+
+/// Computes a shortest token-level edit script turning `input` into `output`,
+/// counting insertions, deletions and replacements of one token each.
+/// Adjacent edits are merged, so the edits returned are disjoint and sorted.
+pub fn edit_script(input: &[impl AsRef<str>], output: &[impl AsRef<str>]) -> Vec<TokenEdit> {
+    let n = input.len();
+    let m = output.len();
+    let same = |i: usize, j: usize| input[i].as_ref() == output[j].as_ref();
+    let dist = distances(input, output);
+
+    let mut result: Vec<TokenEdit> = Vec::new();
+    let mut push = |begin: usize, end: usize, token: Option<&str>| match result.last_mut() {
+        Some(last) if last.end == begin => {
+            last.end = end;
+            last.replacement.extend(token.map(|x| x.to_string()));
+        }
+        _ => result.push(TokenEdit {
+            begin,
+            end,
+            replacement: token.map(|x| x.to_string()).into_iter().collect(),
+        }),
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && same(i, j) && dist[i][j] == dist[i + 1][j + 1] {
+            i += 1;
+            j += 1;
+        } else if i < n && j < m && dist[i][j] == 1 + dist[i + 1][j + 1] {
+            push(i, i + 1, Some(output[j].as_ref()));
+            i += 1;
+            j += 1;
+        } else if i < n && dist[i][j] == 1 + dist[i + 1][j] {
+            push(i, i + 1, None);
+            i += 1;
+        } else {
+            push(i, i, Some(output[j].as_ref()));
+            j += 1;
+        }
+    }
+    result
+}
+
+/// Number of token insertions, deletions and replacements turning `input`
+/// into `output`, the length the fixer gives a fix.
+pub fn edit_distance(input: &[impl AsRef<str>], output: &[impl AsRef<str>]) -> usize {
+    distances(input, output)[0][0]
+}
+
+/// `dist[i][j]` is the distance between `input[i..]` and `output[j..]`.
+fn distances(input: &[impl AsRef<str>], output: &[impl AsRef<str>]) -> Vec<Vec<usize>> {
+    let n = input.len();
+    let m = output.len();
+    let mut dist = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..=n).rev() {
+        for j in (0..=m).rev() {
+            dist[i][j] = if i == n {
+                m - j
+            } else if j == m {
+                n - i
+            } else if input[i].as_ref() == output[j].as_ref() {
+                dist[i + 1][j + 1]
+            } else {
+                1 + dist[i + 1][j + 1].min(dist[i + 1][j]).min(dist[i][j + 1])
+            };
+        }
+    }
+    dist
+}
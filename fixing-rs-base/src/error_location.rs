@@ -0,0 +1,141 @@
+use crate::tokenizer::Token;
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorLocation {
+    /// Index of the token reported as erroneous.
+    Token(usize),
+    /// 1-based line and column in the text handed to the tokenizer, only
+    /// for inputs that are source text rather than token files.
+    Source { line: usize, column: usize },
+}
+
+#[derive(Debug)]
+pub enum ErrorLocationParseError {
+    InvalidLocation(String),
+}
+
+impl Display for ErrorLocationParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl Error for ErrorLocationParseError {}
+
+/// Accepts either a token index (`12`) or a source position (`3:7`).
+impl FromStr for ErrorLocation {
+    type Err = ErrorLocationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(token) = s.parse() {
+            return Ok(Self::Token(token));
+        }
+        match s.split_once(':') {
+            Some((line, column)) => match (line.parse(), column.parse()) {
+                (Ok(line), Ok(column)) => Ok(Self::Source { line, column }),
+                _ => Err(ErrorLocationParseError::InvalidLocation(s.to_string())),
+            },
+            None => Err(ErrorLocationParseError::InvalidLocation(s.to_string())),
+        }
+    }
+}
+
+/// Extracts error locations from compiler output in the gcc/clang format
+/// `file:line:column: error: message`. Warnings, notes and source excerpts
+/// are skipped.
+pub fn parse_diagnostics(diagnostics: &str) -> Vec<ErrorLocation> {
+    let mut result = Vec::new();
+    for line in diagnostics.lines() {
+        let position = match line
+            .find(": error:")
+            .or_else(|| line.find(": fatal error:"))
+        {
+            Some(p) => &line[..p],
+            None => continue,
+        };
+        let mut parts = position.rsplitn(3, ':');
+        let last = parts.next().and_then(|x| x.trim().parse().ok());
+        let second = parts.next().and_then(|x| x.trim().parse().ok());
+        match (second, last) {
+            (Some(line), Some(column)) => result.push(ErrorLocation::Source { line, column }),
+            (None, Some(line)) => result.push(ErrorLocation::Source { line, column: 1 }),
+            _ => {}
+        }
+    }
+    result
+}
+
+/// The byte offset of `literal` in `input`, if it lies entirely within it.
+/// Tokens not sliced from the input, e.g. made up by a tokenizer, have none.
+fn offset_in(input: &str, literal: &str) -> Option<usize> {
+    let range = input.as_bytes().as_ptr_range();
+    let literal = literal.as_bytes().as_ptr_range();
+    if literal.start < range.start || literal.end > range.end {
+        return None;
+    }
+    let offset = literal.start as usize - range.start as usize;
+    input.is_char_boundary(offset).then_some(offset)
+}
+
+/// Maps error locations onto token indices. Source positions are resolved
+/// against `source`, which must be the text the tokens were sliced from; a
+/// position maps to the last token starting at or before it. Without a
+/// source, as for token files whose lines are not those of the program, the
+/// first source position is returned as an error.
+pub fn resolve_error_locations(
+    source: Option<&str>,
+    tokens: &[Token<'_, '_>],
+    locations: &[ErrorLocation],
+) -> Result<Vec<usize>, ErrorLocation> {
+    let input = match source {
+        Some(input) => input,
+        None => {
+            if let Some(location) = locations
+                .iter()
+                .find(|x| matches!(x, ErrorLocation::Source { .. }))
+            {
+                return Err(*location);
+            }
+            ""
+        }
+    };
+    let line_starts = std::iter::once(0)
+        .chain(input.match_indices('\n').map(|(x, _)| x + 1))
+        .collect::<Vec<_>>();
+    let mut starts = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if let Some(offset) = offset_in(input, token.literal) {
+            let line = line_starts.partition_point(|x| *x <= offset);
+            let column = offset - line_starts[line - 1] + 1;
+            starts.push(((line, column), index));
+        }
+    }
+
+    let mut result = Vec::new();
+    for location in locations {
+        match *location {
+            ErrorLocation::Token(index) => result.push(std::cmp::min(index, tokens.len())),
+            ErrorLocation::Source { line, column } => {
+                let found = starts
+                    .iter()
+                    .take_while(|(start, _)| *start <= (line, column))
+                    .last()
+                    .map(|(_, index)| *index);
+                match found {
+                    Some(index) => result.push(index),
+                    None if !tokens.is_empty() => result.push(0),
+                    None => {}
+                }
+            }
+        }
+    }
+    result.sort();
+    result.dedup();
+    Ok(result)
+}
@@ -1,12 +1,17 @@
 use crate::{
+    check::CheckResult,
+    error_location::{resolve_error_locations, ErrorLocation},
     fixing_info::FixingInfo,
     grammar::{Grammar, GrammarArena},
+    naming::{is_identifier, rename_new_ids, NamingScope, NamingStrategy, PlaceholderNaming},
     props::UnionProp,
     reachability::{
-        find, GProcessor, GReachability, GReachabilityArena, SProcessor, SReachability,
-        SReachabilityArena,
+        find, ErrorProximity, GProcessor, GReachability, GReachabilityArena, GReachabilityOptions,
+        SProcessor, SReachability, SReachabilityArena,
     },
+    stats::EngineStats,
     tokenizer::Token,
+    utils::{CountingAllocator, HeapScope, MemoryBudget},
 };
 use log::info;
 use std::{
@@ -14,9 +19,11 @@ use std::{
     fmt::{Debug, Display},
     fs,
     io::{self, Write},
+    sync::Arc,
     time::{Duration, Instant},
 };
 
+#[derive(Clone)]
 pub struct FixTaskInfo {
     pub input_name: String,
     pub env_name: String,
@@ -24,6 +31,51 @@ pub struct FixTaskInfo {
     pub max_len: usize,
     pub max_new_id: usize,
     pub verbose_gen: bool,
+    pub error_locations: Vec<ErrorLocation>,
+    pub naming: Arc<dyn NamingStrategy>,
+    /// Number of fixes of the shortest length to report; the first one is
+    /// the fix written to the output file.
+    pub max_fixes: usize,
+    pub time_limit: Option<Duration>,
+    /// Bytes the task may hold, as counted by
+    /// [`crate::utils::CountingAllocator`] or, when it is not installed, the
+    /// bytes of its reachability arenas. Past it, the task fails with
+    /// [`FixTaskError::MemoryLimit`] and its memory is freed, so the next
+    /// task starts clean.
+    pub memory_budget: Option<usize>,
+    /// Whether to drop syntactic edges that cannot be part of a fix within
+    /// `max_len`, using bounds on the tokens the grammar may yield.
+    pub prune: bool,
+    /// Number of threads building syntactic reachability; with more than
+    /// one, the result is the same as when built sequentially.
+    pub threads: usize,
+    /// File the counters of the task are appended to after each length, so
+    /// that they are kept when the task runs out of time or memory.
+    pub profile: Option<String>,
+}
+
+impl FixTaskInfo {
+    /// A task fixing `input_name` with at most `max_len` edits, introducing
+    /// as many new identifiers, and nothing else set: one fix is reported
+    /// and not written, without limits, pruning or profiling.
+    pub fn new(input_name: impl Into<String>, env_name: impl Into<String>, max_len: usize) -> Self {
+        Self {
+            input_name: input_name.into(),
+            env_name: env_name.into(),
+            output_name: None,
+            max_len,
+            max_new_id: max_len,
+            verbose_gen: false,
+            error_locations: Vec::new(),
+            naming: Arc::new(PlaceholderNaming),
+            max_fixes: 1,
+            time_limit: None,
+            memory_budget: None,
+            prune: false,
+            threads: 1,
+            profile: None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -34,6 +86,13 @@ pub struct FixTaskResult {
     pub time_after_find: Vec<Instant>,
     pub found_length: Option<usize>,
     pub outputs: Option<Vec<String>>,
+    pub alternatives: Vec<Vec<String>>,
+    /// The tokens of the input, as the fixes are given.
+    pub input: Vec<String>,
+    pub stats: EngineStats,
+    /// Whether the input is valid as it is, for tasks run by
+    /// [`crate::check::DoCheckImpl`].
+    pub check: Option<CheckResult>,
 }
 
 #[derive(Debug)]
@@ -43,6 +102,11 @@ pub enum FixTaskError<T: Error, E: Error> {
     WriteOutputError(io::Error),
     TokenizerError(T),
     EnvLoadError(E),
+    TimeLimitExceeded,
+    MemoryLimit,
+    /// A `LINE:COLUMN` error location given for an input that is not source
+    /// text, such as a token file.
+    SourceLocation(ErrorLocation),
 }
 
 impl<T: Debug + Error, E: Debug + Error> Display for FixTaskError<T, E> {
@@ -55,35 +119,84 @@ impl<T: Error, E: Error> Error for FixTaskError<T, E> {}
 
 fn do_fix_impl<'a, GProc, SProc, PG, T, E>(
     grammar: &'a Grammar<'a>,
+    source: Option<&str>,
     tokens: &Vec<Token<'a, '_>>,
     gproc: &GProc,
     sproc: &SProc,
+    mut naming_scope: NamingScope,
     info: &FixTaskInfo,
     time_before_load: Instant,
 ) -> Result<FixTaskResult, FixTaskError<T, E>>
 where
-    PG: UnionProp,
-    GProc: GProcessor<PG = PG>,
+    PG: UnionProp + Send + Sync,
+    GProc: GProcessor<PG = PG> + Sync,
     SProc: SProcessor<PG = PG>,
     T: Error,
     E: Error,
 {
     let time_after_load = Instant::now();
 
+    if info.memory_budget.is_some() || info.profile.is_some() {
+        CountingAllocator::enable();
+    }
+    let heap = HeapScope::new();
     let greachability_arena = GReachabilityArena::new();
     let sreachability_arena = SReachabilityArena::new();
-    let mut syntactic_reachability =
-        GReachability::new(&grammar, &greachability_arena, &tokens, gproc, info.max_len);
+    let mut syntactic_reachability = GReachability::new(
+        &grammar,
+        &greachability_arena,
+        &tokens,
+        gproc,
+        info.max_len,
+        GReachabilityOptions {
+            prune: info.prune,
+            profile: info.profile.is_some(),
+        },
+    );
     let mut sreachability = SReachability::new(&sreachability_arena);
+    let proximity = ErrorProximity::new(
+        resolve_error_locations(source, tokens, &info.error_locations)
+            .map_err(|e| FixTaskError::SourceLocation(e))?,
+    );
 
     let time_after_reachability_built = Instant::now();
     let mut time_after_find = Vec::new();
 
     let mut found_length = None;
     let mut outputs = None;
+    let mut alternatives = Vec::new();
+    let deadline = info.time_limit.map(|x| time_before_load + x);
+    naming_scope.reserve_keywords(
+        grammar
+            .get_symbol_ref()
+            .literal_terminals
+            .keys()
+            .filter(|x| is_identifier(x))
+            .map(|x| x.to_string()),
+    );
+    let budget = info.memory_budget.map(|x| MemoryBudget::new(x, heap));
+    syntactic_reachability.set_memory_budget(budget);
+    syntactic_reachability.set_deadline(deadline);
+    sreachability_arena.set_memory_budget(budget);
+    sreachability_arena.set_deadline(deadline);
+    sreachability_arena.set_profile(info.profile.is_some());
     for current_len in 0..=info.max_len {
+        if deadline.is_some_and(|x| Instant::now() > x) {
+            return Err(FixTaskError::TimeLimitExceeded);
+        }
         info!("Updating to length {}...", current_len);
-        syntactic_reachability.update_until(current_len);
+        if info.threads > 1 {
+            syntactic_reachability.update_until_parallel(current_len, info.threads);
+        } else {
+            syntactic_reachability.update_until(current_len);
+        }
+        if syntactic_reachability.memory_budget_exceeded() {
+            return Err(FixTaskError::MemoryLimit);
+        }
+        if syntactic_reachability.deadline_exceeded() {
+            return Err(FixTaskError::TimeLimitExceeded);
+        }
+        info!("Syntactic edges: {}", syntactic_reachability.edge_count());
         let has_syn = if let Some(ref e) = syntactic_reachability.get_start_edges().get(current_len)
         {
             e.len() != 0
@@ -91,34 +204,64 @@ where
             false
         };
         info!("Has syntactic reachability: {}", has_syn);
-        match find(
+        let start_edges = find(
             sproc,
             &sreachability_arena,
             &syntactic_reachability,
             current_len,
             current_len,
             &mut sreachability,
-        ) {
-            Some(start_edge) => {
-                time_after_find.push(Instant::now());
-                found_length = Some(current_len);
-                let result = sreachability.generate_from(start_edge.ptr(), sproc, info.verbose_gen);
-                if let Some(ref output) = info.output_name {
-                    let mut output =
-                        std::fs::File::create(output).expect("Unable to open output file.");
-                    for token in result.iter() {
-                        writeln!(output, "{}", token).unwrap();
-                    }
-                }
-                outputs = Some(result);
-                break;
-            }
-            None => {
-                time_after_find.push(Instant::now());
+            &proximity,
+        );
+        if sreachability_arena.memory_budget_exceeded() {
+            return Err(FixTaskError::MemoryLimit);
+        }
+        if sreachability_arena.deadline_exceeded() {
+            return Err(FixTaskError::TimeLimitExceeded);
+        }
+        time_after_find.push(Instant::now());
+        if let Some(ref profile) = info.profile {
+            let stats = collect_stats(
+                &syntactic_reachability,
+                &greachability_arena,
+                &sreachability_arena,
+                heap,
+            );
+            write_profile(profile, &info.input_name, current_len, &stats)
+                .map_err(FixTaskError::WriteOutputError)?;
+        }
+        if start_edges.is_empty() {
+            continue;
+        }
+        found_length = Some(current_len);
+        let mut results = Vec::new();
+        for (score, start_edge) in start_edges {
+            let result =
+                sreachability.generate_from(start_edge.ptr(), sproc, &proximity, info.verbose_gen);
+            results.push((
+                score,
+                rename_new_ids(&result, info.naming.as_ref(), &naming_scope),
+            ));
+        }
+        let mut results = rank_fixes(results, info.max_fixes.max(1));
+        let result = results.remove(0);
+        if let Some(ref output) = info.output_name {
+            let mut output = std::fs::File::create(output).expect("Unable to open output file.");
+            for token in result.iter() {
+                writeln!(output, "{}", token).unwrap();
             }
         }
+        outputs = Some(result);
+        alternatives = results;
+        break;
     }
 
+    let stats = collect_stats(
+        &syntactic_reachability,
+        &greachability_arena,
+        &sreachability_arena,
+        heap,
+    );
     Ok(FixTaskResult {
         time_before_load,
         time_after_load,
@@ -126,27 +269,93 @@ where
         time_after_find,
         found_length,
         outputs,
+        alternatives,
+        input: tokens.iter().map(|x| x.literal.to_string()).collect(),
+        stats,
+        check: None,
     })
 }
 
+/// Orders fixes of the same length, each paired with its distance to the
+/// reported errors, and keeps the first `limit` distinct ones: the nearest
+/// to the errors come first, and fixes equally near are ordered by their
+/// tokens, compared lexicographically.
+pub fn rank_fixes(mut fixes: Vec<(usize, Vec<String>)>, limit: usize) -> Vec<Vec<String>> {
+    fixes.sort();
+    let mut result: Vec<Vec<String>> = Vec::new();
+    for (_, fix) in fixes {
+        if !result.contains(&fix) {
+            result.push(fix);
+        }
+    }
+    result.truncate(limit);
+    result
+}
+
+pub(crate) fn collect_stats<'a, 'b, PG, PSI, PSS, GProc>(
+    syntactic_reachability: &GReachability<'a, 'b, '_, PG, GProc>,
+    greachability_arena: &GReachabilityArena<'a, 'b, PG>,
+    sreachability_arena: &SReachabilityArena<'a, 'b, PG, PSI, PSS>,
+    heap: HeapScope,
+) -> EngineStats
+where
+    PG: UnionProp,
+    PSI: UnionProp,
+    PSS: UnionProp,
+    GProc: GProcessor<PG = PG>,
+{
+    let mut stats = EngineStats::default();
+    syntactic_reachability.fill_stats(&mut stats);
+    sreachability_arena.fill_stats(&mut stats);
+    stats.peak_arena_bytes = stats
+        .peak_arena_bytes
+        .max(greachability_arena.bytes() + sreachability_arena.bytes());
+    stats.peak_heap_bytes = heap.peak_bytes();
+    stats
+}
+
+fn write_profile(
+    path: &str,
+    input_name: &str,
+    length: usize,
+    stats: &EngineStats,
+) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(
+        file,
+        "---PROFILE---,input_name:{},length:{}",
+        input_name, length
+    )?;
+    write!(file, "{}", stats)
+}
+
 mod do_fix_inner {
     pub trait DoFixInner {}
     impl DoFixInner for super::DoFixImpl {}
+    impl DoFixInner for crate::check::DoCheckImpl {}
 }
 
 pub trait DoFix: do_fix_inner::DoFixInner {
+    /// `source` is the text `tokens` were sliced from if it is the program
+    /// as written, and `None` for token files, whose lines say nothing of
+    /// where errors of the program are.
     fn do_fix<'a, GProc, SProc, PG, T, E>(
         self,
         grammar: &'a Grammar<'a>,
+        source: Option<&str>,
         tokens: &Vec<Token<'a, '_>>,
         gproc: &GProc,
         sproc: &SProc,
+        naming_scope: NamingScope,
         info: &FixTaskInfo,
         time_before_load: Instant,
     ) -> Result<FixTaskResult, FixTaskError<T, E>>
     where
-        PG: UnionProp,
-        GProc: GProcessor<PG = PG>,
+        PG: UnionProp + Send + Sync,
+        GProc: GProcessor<PG = PG> + Sync,
         SProc: SProcessor<PG = PG>,
         T: Error,
         E: Error;
@@ -157,20 +366,31 @@ impl DoFix for DoFixImpl {
     fn do_fix<'a, GProc, SProc, PG, T, E>(
         self,
         grammar: &'a Grammar<'a>,
+        source: Option<&str>,
         tokens: &Vec<Token<'a, '_>>,
         gproc: &GProc,
         sproc: &SProc,
+        naming_scope: NamingScope,
         info: &FixTaskInfo,
         time_before_load: Instant,
     ) -> Result<FixTaskResult, FixTaskError<T, E>>
     where
-        PG: UnionProp,
-        GProc: GProcessor<PG = PG>,
+        PG: UnionProp + Send + Sync,
+        GProc: GProcessor<PG = PG> + Sync,
         SProc: SProcessor<PG = PG>,
         T: Error,
         E: Error,
     {
-        do_fix_impl(grammar, tokens, gproc, sproc, info, time_before_load)
+        do_fix_impl(
+            grammar,
+            source,
+            tokens,
+            gproc,
+            sproc,
+            naming_scope,
+            info,
+            time_before_load,
+        )
     }
 }
 
@@ -193,13 +413,35 @@ pub trait FixingInputProcessor: FixingInputProcessorBase {
     type EnvLoadError: Error;
 }
 
-fn fix_in_loop<'a, P, T, E>(
-    processor: &P,
+/// Fixes one loaded task, as [`FixingInputProcessor::process`] does.
+pub(crate) trait ProcessFn<T: Error, E: Error>:
+    for<'a> Fn(
+    &'a Grammar<'a>,
+    &str,
+    &str,
+    &FixTaskInfo,
+    Instant,
+) -> Result<FixTaskResult, FixTaskError<T, E>>
+{
+}
+
+impl<T: Error, E: Error, F> ProcessFn<T, E> for F where
+    F: for<'a> Fn(
+        &'a Grammar<'a>,
+        &str,
+        &str,
+        &FixTaskInfo,
+        Instant,
+    ) -> Result<FixTaskResult, FixTaskError<T, E>>
+{
+}
+
+pub(crate) fn fix_in_loop<'a, T, E>(
     info: &FixTaskInfo,
     grammar: &'a Grammar<'a>,
+    process: &impl ProcessFn<T, E>,
 ) -> Result<FixTaskResult, FixTaskError<T, E>>
 where
-    P: FixingInputProcessor<TokenizerError = T, EnvLoadError = E>,
     T: Error,
     E: Error,
 {
@@ -210,13 +452,12 @@ where
     let env =
         fs::read_to_string(info.env_name.as_str()).map_err(|e| FixTaskError::ReadEnvError(e))?;
 
-    processor.process(
-        &grammar,
+    process(
+        grammar,
         input.as_str(),
         env.as_str(),
-        &info,
+        info,
         time_before_load,
-        DoFixImpl,
     )
 }
 
@@ -228,12 +469,32 @@ where
     P: FixingInputProcessor<TokenizerError = T, EnvLoadError = E>,
     T: Error,
     E: Error,
+{
+    fix_with(
+        inputs,
+        processor.info(),
+        |grammar, input, env, info, time_before_load| {
+            processor.process(grammar, input, env, info, time_before_load, DoFixImpl)
+        },
+    )
+}
+
+/// Runs `process` on each of `inputs`, printing a `---RESULT---` line for
+/// each of them.
+pub(crate) fn fix_with<T, E>(
+    inputs: impl Iterator<Item = FixTaskInfo>,
+    fixing_info: &FixingInfo,
+    process: impl ProcessFn<T, E>,
+) -> Vec<Result<FixTaskResult, FixTaskError<T, E>>>
+where
+    T: Error,
+    E: Error,
 {
     let grammar_arena = GrammarArena::new();
-    let grammar = Grammar::new(&grammar_arena, processor.info().grammar).unwrap();
+    let grammar = Grammar::new(&grammar_arena, fixing_info.grammar).unwrap();
     let mut result = Vec::new();
     for info in inputs {
-        let r = fix_in_loop(processor, &info, &grammar);
+        let r = fix_in_loop(&info, &grammar, &process);
         match r {
             Ok(ref r) => {
                 let time_load = r.time_after_load - r.time_before_load;
@@ -246,7 +507,8 @@ where
                 };
                 let time_find = time_find.as_secs_f64();
                 println!(
-                    "---RESULT---,input_name:{},length:{},time_load:{},time_build:{},time_find:{}",
+                    "---RESULT---,input_name:{},length:{},time_load:{},time_build:{},time_find:{},\
+                     syntactic_edges:{},semantic_keys:{},arena_bytes:{}",
                     info.input_name,
                     match r.found_length {
                         Some(l) => l.to_string(),
@@ -255,6 +517,9 @@ where
                     time_load,
                     time_build,
                     time_find,
+                    r.stats.syntactic_edges.iter().sum::<usize>(),
+                    r.stats.semantic_keys,
+                    r.stats.peak_arena_bytes,
                 );
             }
             Err(ref e) => {
@@ -0,0 +1,404 @@
+use crate::{
+    containers::Map,
+    grammar::{Grammar, GrammarRuleRef, GrammarRuleType, SymbolRef, SymbolType},
+    props::{IntoPropResult, PropArray, PropResult, UnionProp},
+    reachability::{GProcessor, SProcessor},
+    utils::Rng,
+};
+
+/// Programs sampled before [`Generator::generate`] gives up.
+const ATTEMPTS: usize = 1000;
+
+/// Times the left part of a rule is sampled again when no right part fits
+/// it.
+const RETRIES: usize = 3;
+
+/// Samples random programs of a grammar that the processors accept, e.g.
+/// to fuzz the fixer with mutants of them.
+///
+/// A program is sampled in two passes, as the engine processes it: the
+/// derivation tree and its syntactic props are sampled bottom-up, then
+/// inherited props are passed down the tree and synthesized props up, a
+/// random valid prop being taken wherever the processors allow several and
+/// other choices being tried where none is left. Symbolic terminals are
+/// named by [`SProcessor::process_symbolic_terminal_gen`], so new
+/// identifiers keep their placeholders.
+pub struct Generator<'a, 'p, GProc, SProc> {
+    grammar: &'a Grammar<'a>,
+    gprocessor: &'p GProc,
+    sprocessor: &'p SProc,
+    /// Height of the shortest derivation of each non-terminal.
+    heights: Map<SymbolRef<'a>, usize>,
+    max_depth: usize,
+    min_tokens: usize,
+    max_tokens: usize,
+    max_calls: usize,
+}
+
+struct Node<'a, PG: UnionProp> {
+    symbol: SymbolRef<'a>,
+    rule: Option<GrammarRuleRef<'a>>,
+    prop: PropArray<PG>,
+    subs: Vec<Node<'a, PG>>,
+}
+
+impl<'a, 'p, PG, PSI, PSS, GProc, SProc> Generator<'a, 'p, GProc, SProc>
+where
+    PG: UnionProp,
+    PSI: UnionProp,
+    PSS: UnionProp,
+    GProc: GProcessor<PG = PG>,
+    SProc: SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
+{
+    /// A generator of programs whose derivation trees are at most
+    /// `max_depth` rules of the normalized grammar deep.
+    pub fn new(
+        grammar: &'a Grammar<'a>,
+        gprocessor: &'p GProc,
+        sprocessor: &'p SProc,
+        max_depth: usize,
+    ) -> Self {
+        let mut generator = Self {
+            grammar,
+            gprocessor,
+            sprocessor,
+            heights: Map::default(),
+            max_depth,
+            min_tokens: 0,
+            max_tokens: usize::MAX,
+            max_calls: 100_000,
+        };
+        generator.compute_heights();
+        generator
+    }
+
+    /// Rejects programs shorter than `min_tokens`.
+    pub fn min_tokens(mut self, min_tokens: usize) -> Self {
+        self.min_tokens = min_tokens;
+        self
+    }
+
+    /// Rejects programs longer than `max_tokens`.
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Gives up on a derivation tree after `max_calls` calls of the semantic
+    /// processor.
+    pub fn max_calls(mut self, max_calls: usize) -> Self {
+        self.max_calls = max_calls;
+        self
+    }
+
+    fn compute_heights(&mut self) {
+        let non_terminals = self.grammar.get_symbol_ref().non_terminals;
+        loop {
+            let mut changed = false;
+            for (_, symbol) in non_terminals.iter() {
+                let height = symbol
+                    .rules(self.grammar)
+                    .iter()
+                    .flat_map(|x| self.rule_height(*x))
+                    .min();
+                if let Some(height) = height {
+                    if self.heights.get(symbol) != Some(&height) {
+                        self.heights.insert(*symbol, height);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    fn height(&self, symbol: SymbolRef<'a>) -> Option<usize> {
+        match symbol.symbol_type() {
+            SymbolType::LiteralTerminal | SymbolType::SymbolicTerminal => Some(0),
+            SymbolType::NonTerminal => self.heights.get(&symbol).copied(),
+        }
+    }
+
+    /// Height of the shortest derivation starting with `rule`.
+    fn rule_height(&self, rule: GrammarRuleRef<'a>) -> Option<usize> {
+        let mut height = 0;
+        for sub in rule.right1().into_iter().chain(rule.right2()) {
+            height = height.max(self.height(sub)?);
+        }
+        Some(height + 1)
+    }
+
+    /// Tokens of a random program, or `None` if none was sampled in
+    /// [`ATTEMPTS`] attempts.
+    pub fn generate(&self, rng: &mut Rng) -> Option<Vec<String>> {
+        let start = self.grammar.get_symbol_ref().start_symbol;
+        for _ in 0..ATTEMPTS {
+            let mut tokens = 0;
+            let tree = match self.sample_tree(start, self.max_depth, rng, &mut tokens) {
+                Some(tree) if tokens >= self.min_tokens => tree,
+                _ => continue,
+            };
+            let inh = self.sprocessor.process_root_inh();
+            let mut output = Vec::new();
+            let mut calls = 0;
+            if self
+                .sample_props(&tree, &inh, rng, &mut output, &mut calls)
+                .is_some()
+            {
+                return Some(output);
+            }
+        }
+        None
+    }
+
+    fn sample_tree(
+        &self,
+        symbol: SymbolRef<'a>,
+        depth: usize,
+        rng: &mut Rng,
+        tokens: &mut usize,
+    ) -> Option<Node<'a, PG>> {
+        let leaf = |prop| Node {
+            symbol,
+            rule: None,
+            prop: PropArray::Single(prop),
+            subs: Vec::new(),
+        };
+        match symbol.symbol_type() {
+            SymbolType::LiteralTerminal => {
+                *tokens += 1;
+                (*tokens <= self.max_tokens).then(|| leaf(PG::default()))
+            }
+            SymbolType::SymbolicTerminal => {
+                *tokens += 1;
+                if *tokens > self.max_tokens {
+                    return None;
+                }
+                let props = self.gprocessor.process_symbolic_terminal(symbol, None);
+                choose(props, rng).map(leaf)
+            }
+            SymbolType::NonTerminal => {
+                let mut rules: Vec<GrammarRuleRef<'a>> = symbol
+                    .rules(self.grammar)
+                    .iter()
+                    .copied()
+                    .filter(|x| self.rule_height(*x).is_some_and(|x| x <= depth))
+                    .collect();
+                rng.shuffle(&mut rules);
+                for rule in rules {
+                    let before = *tokens;
+                    let mut subs = Vec::new();
+                    for sub in rule.right1().into_iter().chain(rule.right2()) {
+                        match self.sample_tree(sub, depth - 1, rng, tokens) {
+                            Some(x) => subs.push(x),
+                            None => break,
+                        }
+                    }
+                    if *tokens > self.max_tokens {
+                        return None;
+                    }
+                    let arity = rule.right1().into_iter().chain(rule.right2()).count();
+                    let props = match &subs[..] {
+                        _ if subs.len() != arity => PropResult::Empty,
+                        [] => PropArray::Multiple(vec![].into()).into_prop_result(),
+                        [sub] => self.syntactic_one(rule, &sub.prop),
+                        [sub1, sub2] => self.syntactic_two(rule, &sub1.prop, &sub2.prop),
+                        _ => unreachable!(),
+                    };
+                    match choose(props, rng) {
+                        Some(prop) => {
+                            return Some(Node {
+                                symbol,
+                                rule: Some(rule),
+                                prop,
+                                subs,
+                            })
+                        }
+                        None => *tokens = before,
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn syntactic_one(
+        &self,
+        rule: GrammarRuleRef<'a>,
+        p: &PropArray<PG>,
+    ) -> PropResult<PropArray<PG>> {
+        match rule.rule_type() {
+            GrammarRuleType::Induction => self
+                .gprocessor
+                .process_non_terminal(rule.left(), rule.induction_id(), p.unwrap_multiple())
+                .into(),
+            _ => PropArray::Multiple(vec![p.unwrap_single().clone()].into()).into_prop_result(),
+        }
+    }
+
+    fn syntactic_two(
+        &self,
+        rule: GrammarRuleRef<'a>,
+        p1: &PropArray<PG>,
+        p2: &PropArray<PG>,
+    ) -> PropResult<PropArray<PG>> {
+        match rule.rule_type() {
+            GrammarRuleType::ConcatAppend => {
+                p1.append(p2.unwrap_single().clone()).into_prop_result()
+            }
+            _ => PropArray::Multiple(
+                vec![p1.unwrap_single().clone(), p2.unwrap_single().clone()].into(),
+            )
+            .into_prop_result(),
+        }
+    }
+
+    /// The synthesized prop of `node` given `inh`, pushing its tokens to
+    /// `output`, or `None` with `output` left as it was.
+    fn sample_props(
+        &self,
+        node: &Node<'a, PG>,
+        inh: &PSI,
+        rng: &mut Rng,
+        output: &mut Vec<String>,
+        calls: &mut usize,
+    ) -> Option<PropArray<PSS>> {
+        *calls += 1;
+        if *calls > self.max_calls {
+            return None;
+        }
+        let symbol = node.symbol;
+        let gprop = &node.prop;
+        let rule = match node.rule {
+            Some(rule) => rule,
+            None => {
+                return match symbol.symbol_type() {
+                    SymbolType::LiteralTerminal => {
+                        output.push(symbol.name().to_string());
+                        Some(PropArray::Single(PSS::default()))
+                    }
+                    _ => {
+                        let props = self
+                            .sprocessor
+                            .process_symbolic_terminal_syn(symbol, gprop, inh, None);
+                        let syn = choose(props, rng)?;
+                        output.push(
+                            self.sprocessor
+                                .process_symbolic_terminal_gen(symbol, gprop, inh, &syn, None),
+                        );
+                        Some(PropArray::Single(syn))
+                    }
+                };
+            }
+        };
+        let induction = rule.induction();
+        let induction_id = rule.induction_id();
+        let mark = output.len();
+        match rule.rule_type() {
+            GrammarRuleType::Induction => {
+                let sub = self.sample_props(&node.subs[0], inh, rng, output, calls)?;
+                let props = self.sprocessor.process_non_terminal_syn(
+                    induction,
+                    gprop,
+                    induction_id,
+                    inh,
+                    sub.unwrap_multiple(),
+                );
+                let result = choose(props, rng).map(PropArray::Single);
+                if result.is_none() {
+                    output.truncate(mark);
+                }
+                result
+            }
+            GrammarRuleType::ConcatZero => Some(PropArray::Multiple(vec![].into())),
+            GrammarRuleType::ConcatOne => {
+                let props = self.sprocessor.process_non_terminal_inh(
+                    induction,
+                    gprop,
+                    induction_id,
+                    0,
+                    inh,
+                    &[],
+                );
+                for left_inh in shuffled(props, rng) {
+                    if let Some(left) =
+                        self.sample_props(&node.subs[0], &left_inh, rng, output, calls)
+                    {
+                        return Some(PropArray::Multiple(
+                            vec![left.unwrap_single().clone()].into(),
+                        ));
+                    }
+                }
+                None
+            }
+            GrammarRuleType::ConcatTwo | GrammarRuleType::ConcatAppend => {
+                let left_inhs = match rule.rule_type() {
+                    GrammarRuleType::ConcatTwo => shuffled(
+                        self.sprocessor.process_non_terminal_inh(
+                            induction,
+                            gprop,
+                            induction_id,
+                            0,
+                            inh,
+                            &[],
+                        ),
+                        rng,
+                    ),
+                    _ => vec![inh.clone()],
+                };
+                for left_inh in left_inhs {
+                    for _ in 0..RETRIES {
+                        let left =
+                            match self.sample_props(&node.subs[0], &left_inh, rng, output, calls) {
+                                Some(x) => x,
+                                None => break,
+                            };
+                        let sub_types = match &left {
+                            PropArray::Single(p) => std::slice::from_ref(p),
+                            PropArray::Multiple(p) => &p[..],
+                        };
+                        let props = self.sprocessor.process_non_terminal_inh(
+                            induction,
+                            gprop,
+                            induction_id,
+                            rule.induction_location().unwrap(),
+                            inh,
+                            sub_types,
+                        );
+                        for right_inh in shuffled(props, rng) {
+                            if let Some(right) =
+                                self.sample_props(&node.subs[1], &right_inh, rng, output, calls)
+                            {
+                                let right = right.unwrap_single().clone();
+                                return Some(match left {
+                                    PropArray::Single(left) => {
+                                        PropArray::Multiple(vec![left, right].into())
+                                    }
+                                    left => left.append(right),
+                                });
+                            }
+                        }
+                        output.truncate(mark);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+fn choose<T: crate::props::Prop>(props: PropResult<T>, rng: &mut Rng) -> Option<T> {
+    let mut props: Vec<T> = props.into_iter().collect();
+    match props.len() {
+        0 => None,
+        n => Some(props.swap_remove(rng.below(n))),
+    }
+}
+
+fn shuffled<T: crate::props::Prop>(props: PropResult<T>, rng: &mut Rng) -> Vec<T> {
+    let mut props: Vec<T> = props.into_iter().collect();
+    rng.shuffle(&mut props);
+    props
+}
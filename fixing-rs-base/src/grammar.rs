@@ -1,8 +1,10 @@
+mod frozen;
 mod grammar;
 mod parseerror;
 mod rule;
 mod symbol;
 
+pub use frozen::FrozenGrammar;
 pub use grammar::{Grammar, GrammarArena, GrammarRuleRef, GrammarSymbolsRef, SymbolMap};
 pub use parseerror::{OwnedToken, ParseError};
 pub use rule::{GrammarRule, GrammarRuleLength, GrammarRuleType};
@@ -0,0 +1,37 @@
+use super::Grammar;
+use std::ops::Deref;
+
+/// A grammar that is done being built, which may be read from several
+/// threads.
+///
+/// The rules of a symbol are only written by the grammar it belongs to,
+/// through a `&mut Grammar`, which cannot exist while the grammar is borrowed
+/// for `'a`: the rules read through a `FrozenGrammar<'a>` stay as they are
+/// for as long as it lives.
+#[derive(Clone, Copy)]
+pub struct FrozenGrammar<'a> {
+    grammar: &'a Grammar<'a>,
+}
+
+impl<'a> FrozenGrammar<'a> {
+    pub fn new(grammar: &'a Grammar<'a>) -> Self {
+        Self { grammar }
+    }
+
+    pub fn grammar(&self) -> &'a Grammar<'a> {
+        self.grammar
+    }
+}
+
+impl<'a> Deref for FrozenGrammar<'a> {
+    type Target = Grammar<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.grammar
+    }
+}
+
+// SAFETY: Nothing is written to the grammar or its symbols while it is
+// borrowed for `'a`, see above.
+unsafe impl Send for FrozenGrammar<'_> {}
+unsafe impl Sync for FrozenGrammar<'_> {}
@@ -1,5 +1,5 @@
 use crate::{
-    containers::Map,
+    containers::OrderedMap,
     parsing::ast::GrammarFile,
     utils::{Pointer, RefArena},
 };
@@ -16,7 +16,7 @@ use std::{
     fmt::{Display, Formatter, Write},
 };
 
-pub type SymbolMap<'a> = Map<&'a str, SymbolRef<'a>>;
+pub type SymbolMap<'a> = OrderedMap<&'a str, SymbolRef<'a>>;
 pub type GrammarRuleRef<'a> = Pointer<'a, GrammarRule<'a>>;
 
 impl<'a> Display for GrammarRuleRef<'a> {
@@ -53,9 +53,9 @@ pub struct GrammarSymbolsRef<'a> {
 impl<'a> Grammar<'a> {
     fn construct(arena: &'a GrammarArena<'a>) -> Self {
         Self {
-            literal_terimals: Map::new(),
-            symbolic_terminals: Map::new(),
-            non_terminals: Map::new(),
+            literal_terimals: OrderedMap::new(),
+            symbolic_terminals: OrderedMap::new(),
+            non_terminals: OrderedMap::new(),
             unknown_terminal: None,
 
             next_symbol_id: 0,
@@ -576,6 +576,23 @@ impl<'a> Grammar<'a> {
             unknown_terminal: self.unknown_terminal.unwrap(),
         }
     }
+
+    /// Whether each symbol, by id, may occur in a derivation of `symbol`.
+    pub fn derivable_from(&self, symbol: SymbolRef<'a>) -> Vec<bool> {
+        let mut reached = vec![false; self.next_symbol_id];
+        let mut queue = vec![symbol];
+        while let Some(symbol) = queue.pop() {
+            if reached[symbol.symbol_id()] {
+                continue;
+            }
+            reached[symbol.symbol_id()] = true;
+            for rule in symbol.rules(self).iter() {
+                queue.extend(rule.right1());
+                queue.extend(rule.right2());
+            }
+        }
+        reached
+    }
 }
 
 impl<'a> Display for Grammar<'a> {
@@ -0,0 +1,263 @@
+use crate::{
+    check::{CheckResult, DoCheckImpl},
+    containers::Map,
+    fixing::{
+        fix_in_loop, fix_with, DoFixImpl, FixTaskError, FixTaskInfo, FixTaskResult,
+        FixingInputProcessor, FixingInputProcessorBase,
+    },
+    fixing_info::FixingInfo,
+    grammar::{Grammar, GrammarArena},
+};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    ops::Range,
+    time::Instant,
+};
+
+/// Options only some languages understand, e.g. `c_strictness`, by name.
+/// Values are given as on the command line.
+pub type LanguageOptions = Map<String, String>;
+
+/// An error of a language, reduced to its message so that languages can be
+/// used as trait objects.
+pub struct LanguageError(pub String);
+
+impl Debug for LanguageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Display for LanguageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for LanguageError {}
+
+pub type DynFixTaskError = FixTaskError<LanguageError, LanguageError>;
+
+impl<T: Error, E: Error> FixTaskError<T, E> {
+    pub fn into_dyn(self) -> DynFixTaskError {
+        match self {
+            FixTaskError::ReadInputError(e) => FixTaskError::ReadInputError(e),
+            FixTaskError::ReadEnvError(e) => FixTaskError::ReadEnvError(e),
+            FixTaskError::WriteOutputError(e) => FixTaskError::WriteOutputError(e),
+            FixTaskError::TokenizerError(e) => {
+                FixTaskError::TokenizerError(LanguageError(format!("{:?}", e)))
+            }
+            FixTaskError::EnvLoadError(e) => {
+                FixTaskError::EnvLoadError(LanguageError(format!("{:?}", e)))
+            }
+            FixTaskError::TimeLimitExceeded => FixTaskError::TimeLimitExceeded,
+            FixTaskError::MemoryLimit => FixTaskError::MemoryLimit,
+            FixTaskError::SourceLocation(e) => FixTaskError::SourceLocation(e),
+        }
+    }
+}
+
+/// Fixes tasks of one language with its options already applied. Every
+/// [`FixingInputProcessor`] is a fixer.
+pub trait Fixer {
+    fn info(&self) -> &FixingInfo;
+
+    fn process_dyn<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+    ) -> Result<FixTaskResult, DynFixTaskError>;
+
+    /// Like [`Fixer::process_dyn`], checking the input instead of fixing
+    /// it; the result holds a [`CheckResult`].
+    fn process_check_dyn<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+    ) -> Result<FixTaskResult, DynFixTaskError>;
+}
+
+impl<P: FixingInputProcessor> Fixer for P {
+    fn info(&self) -> &FixingInfo {
+        FixingInputProcessorBase::info(self)
+    }
+
+    fn process_dyn<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+    ) -> Result<FixTaskResult, DynFixTaskError> {
+        self.process(
+            grammar,
+            input_str,
+            env_str,
+            info,
+            time_before_load,
+            DoFixImpl,
+        )
+        .map_err(|e| e.into_dyn())
+    }
+
+    fn process_check_dyn<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+    ) -> Result<FixTaskResult, DynFixTaskError> {
+        self.process(
+            grammar,
+            input_str,
+            env_str,
+            info,
+            time_before_load,
+            DoCheckImpl,
+        )
+        .map_err(|e| e.into_dyn())
+    }
+}
+
+/// Like [`crate::fixing::fix`], for a fixer of a language chosen at run
+/// time.
+pub fn fix_dyn(
+    inputs: impl Iterator<Item = FixTaskInfo>,
+    fixer: &dyn Fixer,
+) -> Vec<Result<FixTaskResult, DynFixTaskError>> {
+    fix_with(
+        inputs,
+        fixer.info(),
+        |grammar, input, env, info, time_before_load| {
+            fixer.process_dyn(grammar, input, env, info, time_before_load)
+        },
+    )
+}
+
+/// Checks whether each of `inputs` is valid as it is, only reading the
+/// input and env names of the tasks.
+pub fn check_dyn(
+    inputs: impl Iterator<Item = FixTaskInfo>,
+    fixer: &dyn Fixer,
+) -> Vec<Result<CheckResult, DynFixTaskError>> {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, fixer.info().grammar).unwrap();
+    inputs
+        .map(|info| {
+            fix_in_loop(
+                &info,
+                &grammar,
+                &|grammar, input, env, info, time_before_load| {
+                    fixer.process_check_dyn(grammar, input, env, info, time_before_load)
+                },
+            )
+            .map(|x| x.check.unwrap())
+        })
+        .collect()
+}
+
+/// Fails on an option whose key is not one of `known`, so that a misspelt
+/// option is reported instead of leaving the default in place.
+pub fn check_option_keys(options: &LanguageOptions, known: &[&str]) -> Result<(), String> {
+    match options.keys().find(|x| !known.contains(&x.as_str())) {
+        Some(key) => Err(format!("Unknown option: {}", key)),
+        None => Ok(()),
+    }
+}
+
+/// A language fixes can be made in. Everything selecting a language by
+/// name, such as `fix --lang`, `gen-src` and the server, looks it up in a
+/// [`LanguageRegistry`], so adding a language only takes registering it.
+pub trait Language: Sync {
+    /// The name selecting the language, e.g. `c`.
+    fn name(&self) -> &'static str;
+
+    fn info(&self) -> &'static FixingInfo;
+
+    /// The fixer configured by `options`, or a message saying which option
+    /// is invalid or unknown, see [`check_option_keys`].
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String>;
+
+    /// The source text of the tokens of a fixed program.
+    fn detokenize(&self, tokens: &[String]) -> String {
+        tokens.join(" ")
+    }
+
+    /// How input token files of the language are laid out, if tools such
+    /// as `mutate` may edit them token by token.
+    fn token_file(&self) -> Option<TokenFile> {
+        None
+    }
+
+    /// How editor documents of the language are fixed by `lsp`, if they
+    /// can be.
+    fn document_format(&self) -> Option<&dyn DocumentFormat> {
+        None
+    }
+}
+
+/// How an editor document is cut into inputs fixed on their own, each with
+/// an env built from the rest of the document.
+pub trait DocumentFormat {
+    /// Byte ranges of the parts of `text` fixed on their own, e.g. the
+    /// bodies of methods.
+    fn inputs(&self, text: &str) -> Vec<Range<usize>>;
+
+    /// The env `text[inputs[index]]` is fixed against.
+    fn env(&self, text: &str, inputs: &[Range<usize>], index: usize) -> String;
+
+    /// Byte ranges within `input` of its tokens, as the fixer reads them,
+    /// or `None` if it cannot be tokenized.
+    fn tokens<'a>(&self, grammar: &'a Grammar<'a>, input: &str) -> Option<Vec<Range<usize>>>;
+}
+
+/// Layout of an input token file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenFile {
+    /// One token per line, identifiers being the tokens that are not
+    /// literal terminals.
+    Lines,
+    /// One `LT\t<terminal>\t<literal>` or `ST\t<terminal>\t<literal>` line
+    /// per token, identifiers being `ST\tIDENTIFIER` lines. Blank lines and
+    /// lines starting with `#` are not tokens.
+    Tagged,
+}
+
+#[derive(Default)]
+pub struct LanguageRegistry {
+    languages: Vec<Box<dyn Language>>,
+}
+
+impl LanguageRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `language`, replacing any language of the same name.
+    pub fn register(&mut self, language: impl Language + 'static) -> &mut Self {
+        self.languages.retain(|x| x.name() != language.name());
+        self.languages.push(Box::new(language));
+        self
+    }
+
+    /// The language named `name`, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&dyn Language> {
+        self.languages
+            .iter()
+            .find(|x| x.name().eq_ignore_ascii_case(name))
+            .map(|x| x.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.languages.iter().map(|x| x.name())
+    }
+}
@@ -3,13 +3,20 @@ extern crate lalrpop_util;
 #[macro_use]
 extern crate getset;
 
+pub mod check;
 pub mod containers;
+pub mod edit_script;
+pub mod error_location;
 pub mod fixing;
 pub mod fixing_info;
+pub mod generator;
 pub mod gensrc;
 pub mod grammar;
+pub mod language;
+pub mod naming;
 pub mod parsing;
 pub mod props;
 pub mod reachability;
+pub mod stats;
 pub mod tokenizer;
 pub mod utils;
@@ -0,0 +1,231 @@
+use crate::containers::{Map, Set};
+use std::fmt::Debug;
+
+/// Prefix of the placeholder names offered to the search for new
+/// declarations. They are replaced in the final output by [`rename_new_ids`].
+pub const NEW_ID_PREFIX: &str = "__new_id_";
+
+pub fn new_id_placeholder(index: usize) -> String {
+    format!("{}{}", NEW_ID_PREFIX, index)
+}
+
+pub fn is_new_id_placeholder(name: &str) -> bool {
+    match name.strip_prefix(NEW_ID_PREFIX) {
+        Some(index) => !index.is_empty() && index.bytes().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Context of the first occurrence of a placeholder in the output.
+pub struct NamingSite<'a> {
+    pub tokens: &'a [String],
+    pub position: usize,
+    pub placeholder: &'a str,
+    pub declared_type: Option<&'a str>,
+    pub scope: &'a NamingScope,
+}
+
+/// Proposes names for a new declaration, best first. Proposals do not need
+/// to be unique: taken names are skipped, and if all of them are taken the
+/// first one is made unique with a numeric suffix.
+pub trait NamingStrategy: Debug + Send + Sync {
+    fn candidates(&self, site: &NamingSite<'_>) -> Vec<String>;
+}
+
+/// Keeps the `__new_id_N` placeholders.
+#[derive(Debug)]
+pub struct PlaceholderNaming;
+
+impl NamingStrategy for PlaceholderNaming {
+    fn candidates(&self, site: &NamingSite<'_>) -> Vec<String> {
+        vec![site.placeholder.to_string()]
+    }
+}
+
+/// Derives the name from the declared type: `Foo` gives `foo`, `CLASS_1`
+/// gives `class_1`, and a lowercase type such as `int` gives `i`.
+#[derive(Debug)]
+pub struct TypeNaming;
+
+impl TypeNaming {
+    fn name_for_type(ty: &str) -> Option<String> {
+        if !is_identifier(ty) {
+            return None;
+        }
+        if !ty.chars().any(|c| c.is_ascii_lowercase()) {
+            return Some(ty.to_ascii_lowercase());
+        }
+        let mut chars = ty.chars();
+        let first = chars.next()?;
+        if first.is_ascii_uppercase() {
+            Some(first.to_ascii_lowercase().to_string() + chars.as_str())
+        } else {
+            Some(first.to_string())
+        }
+    }
+}
+
+impl NamingStrategy for TypeNaming {
+    fn candidates(&self, site: &NamingSite<'_>) -> Vec<String> {
+        site.declared_type
+            .and_then(Self::name_for_type)
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Reuses the closest identifier of the output as the base of the name, so
+/// that a new declaration next to `count` becomes `count1`.
+#[derive(Debug)]
+pub struct NearbyNaming;
+
+impl NamingStrategy for NearbyNaming {
+    fn candidates(&self, site: &NamingSite<'_>) -> Vec<String> {
+        let mut nearby: Vec<_> = site
+            .tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| is_identifier(token) && !is_new_id_placeholder(token))
+            .filter(|(_, token)| !site.scope.is_keyword(token))
+            .filter(|(_, token)| Some(token.as_str()) != site.declared_type)
+            .map(|(i, token)| (i.abs_diff(site.position), token))
+            .collect();
+        nearby.sort_by_key(|(distance, _)| *distance);
+        nearby.into_iter().map(|(_, token)| token.clone()).collect()
+    }
+}
+
+/// Takes names from a user-provided list, in order.
+#[derive(Debug)]
+pub struct VocabularyNaming {
+    names: Vec<String>,
+}
+
+impl VocabularyNaming {
+    pub fn new(names: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            names: names.into_iter().filter(|x| is_identifier(x)).collect(),
+        }
+    }
+}
+
+impl NamingStrategy for VocabularyNaming {
+    fn candidates(&self, _site: &NamingSite<'_>) -> Vec<String> {
+        self.names.clone()
+    }
+}
+
+/// Names a fix must not introduce, together with the language-specific way
+/// of finding the type a placeholder is declared with. Keywords are kept
+/// apart so that strategies do not build names upon them.
+pub struct NamingScope {
+    keywords: Set<String>,
+    reserved: Set<String>,
+    declared_type: fn(&[String], usize) -> Option<String>,
+}
+
+impl NamingScope {
+    pub fn new(
+        reserved: impl IntoIterator<Item = String>,
+        declared_type: fn(&[String], usize) -> Option<String>,
+    ) -> Self {
+        Self {
+            keywords: Set::default(),
+            reserved: reserved
+                .into_iter()
+                .filter(|x| !is_new_id_placeholder(x))
+                .collect(),
+            declared_type,
+        }
+    }
+
+    pub fn reserve(&mut self, names: impl IntoIterator<Item = String>) {
+        self.reserved
+            .extend(names.into_iter().filter(|x| !is_new_id_placeholder(x)));
+    }
+
+    pub fn reserve_keywords(&mut self, keywords: impl IntoIterator<Item = String>) {
+        self.keywords.extend(keywords);
+    }
+
+    pub fn is_keyword(&self, name: &str) -> bool {
+        self.keywords.contains(name)
+    }
+
+    pub fn is_reserved(&self, name: &str) -> bool {
+        self.is_keyword(name) || self.reserved.contains(name)
+    }
+}
+
+const FALLBACK_NAME: &str = "var";
+
+/// Replaces every placeholder in `tokens` by a fresh name. All occurrences of
+/// a placeholder get the same name, and no name collides with the reserved
+/// names of `scope`, with the other tokens or with another new name.
+pub fn rename_new_ids(
+    tokens: &[String],
+    strategy: &dyn NamingStrategy,
+    scope: &NamingScope,
+) -> Vec<String> {
+    let taken: Set<&str> = tokens
+        .iter()
+        .filter(|x| !is_new_id_placeholder(x))
+        .map(|x| x.as_str())
+        .collect();
+    let mut names: Map<&str, String> = Map::default();
+    for (position, token) in tokens.iter().enumerate() {
+        if !is_new_id_placeholder(token) || names.contains_key(token.as_str()) {
+            continue;
+        }
+        let declared_type = (scope.declared_type)(tokens, position);
+        let site = NamingSite {
+            tokens,
+            position,
+            placeholder: token,
+            declared_type: declared_type.as_deref(),
+            scope,
+        };
+        let candidates = strategy.candidates(&site);
+        let is_free = |name: &str| {
+            is_identifier(name)
+                && !is_new_id_placeholder(name)
+                && !scope.is_reserved(name)
+                && !taken.contains(name)
+                && !names.values().any(|x| x == name)
+        };
+        let name = match candidates.iter().find(|x| *x == token || is_free(x)) {
+            Some(name) => name.clone(),
+            None => {
+                let base = candidates
+                    .iter()
+                    .find(|x| is_identifier(x) && !is_new_id_placeholder(x) && !scope.is_keyword(x))
+                    .map(|x| x.as_str())
+                    .unwrap_or(FALLBACK_NAME);
+                let separator = if base.ends_with(|c: char| c.is_ascii_digit()) {
+                    "_"
+                } else {
+                    ""
+                };
+                (1..)
+                    .map(|i| format!("{}{}{}", base, separator, i))
+                    .find(|x| is_free(x))
+                    .unwrap()
+            }
+        };
+        names.insert(token, name);
+    }
+    tokens
+        .iter()
+        .map(|x| names.get(x.as_str()).unwrap_or(x).clone())
+        .collect()
+}
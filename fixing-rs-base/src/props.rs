@@ -1,13 +1,17 @@
 mod assembly_prop_result;
 mod prop;
 mod prop_array;
+mod prop_interner;
 mod prop_pair;
 mod prop_result;
+mod prop_store;
 mod prop_union;
 
 pub use assembly_prop_result::AssemblyPropResult;
 pub use prop::{Prop, PropEmpty};
 pub use prop_array::PropArray;
+pub use prop_interner::PropInterner;
 pub use prop_pair::{AssembleWith, PropPair};
 pub use prop_result::{IntoPropResult, PropResult, PropResultIter};
+pub use prop_store::{Interned, PropStorage, PropStore, StoredProp, Unshared};
 pub use prop_union::{IntoSingleProp, IntoUnionProp, UnionProp};
@@ -1,13 +1,21 @@
 use std::{fmt::Debug, hash::Hash};
 
-use super::UnionProp;
+use super::{UnionProp, Unshared};
 
-pub trait Prop: Debug + Clone + PartialEq + Eq + Hash {}
+pub trait Prop: Debug + Clone + PartialEq + Eq + Hash {
+    /// Props of different kinds are never compared with each other; a
+    /// store holding props of several kinds tells them apart with it.
+    fn kind(&self) -> usize {
+        0
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PropEmpty;
 impl Prop for PropEmpty {}
-impl UnionProp for PropEmpty {}
+impl UnionProp for PropEmpty {
+    type Storage = Unshared;
+}
 impl Default for PropEmpty {
     fn default() -> Self {
         Self
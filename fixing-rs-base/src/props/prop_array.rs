@@ -9,7 +9,17 @@ where
     Multiple(Box<[P]>),
 }
 
-impl<P> Prop for PropArray<P> where P: Prop {}
+impl<P> Prop for PropArray<P>
+where
+    P: Prop,
+{
+    fn kind(&self) -> usize {
+        match self {
+            Self::Single(_) => 0,
+            Self::Multiple(_) => 1,
+        }
+    }
+}
 
 impl<P> PropArray<P>
 where
@@ -0,0 +1,69 @@
+use super::Prop;
+use crate::{
+    containers::Map,
+    utils::{Pointer, RefArena},
+};
+use std::cell::RefCell;
+
+/// Stores each distinct prop once. Props interned by the same interner are
+/// equal exactly when their handles are, so the handles compare and hash by
+/// address instead of by content. Props are kept in one table per
+/// [`Prop::kind`], so props of different kinds are never compared.
+pub struct PropInterner<'t, P>
+where
+    P: Prop,
+{
+    arena: RefArena<P>,
+    props: RefCell<Map<usize, Map<&'t P, Pointer<'t, P>>>>,
+}
+
+impl<'t, P> PropInterner<'t, P>
+where
+    P: Prop,
+{
+    pub fn new() -> Self {
+        Self {
+            arena: RefArena::new(),
+            props: RefCell::new(Map::default()),
+        }
+    }
+
+    pub fn intern(&'t self, prop: P) -> Pointer<'t, P> {
+        let kind = prop.kind();
+        if let Some(props) = self.props.borrow().get(&kind) {
+            if let Some(interned) = props.get(&prop) {
+                return *interned;
+            }
+        }
+        let interned = self.arena.alloc(prop);
+        self.props
+            .borrow_mut()
+            .entry(kind)
+            .or_default()
+            .insert(interned.ptr(), interned);
+        interned
+    }
+
+    /// Number of distinct props interned.
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+
+    /// Bytes taken by the props interned, not counting memory they own.
+    pub fn bytes(&self) -> usize {
+        self.arena.bytes()
+    }
+}
+
+impl<P> Default for PropInterner<'_, P>
+where
+    P: Prop,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -27,6 +27,18 @@ where
         }
     }
 
+    pub fn len(&self) -> usize {
+        match self {
+            PropResult::Empty => 0,
+            PropResult::One(_) => 1,
+            PropResult::Many(ps) => ps.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn consume(self, mut f: impl FnMut(T)) {
         match self {
             PropResult::Empty => {}
@@ -0,0 +1,212 @@
+use super::{Prop, PropInterner, UnionProp};
+use crate::utils::RefArena;
+use std::{
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::Deref,
+};
+
+/// How a [`PropStore`] keeps props, and so how the props it hands out
+/// compare and hash. Chosen by the props of a processor with
+/// [`UnionProp::Storage`].
+pub trait PropStorage {
+    fn store<'t, P>(interner: &'t PropInterner<'t, P>, arena: &'t RefArena<P>, prop: P) -> &'t P
+    where
+        P: Prop;
+
+    fn eq<P>(left: &P, right: &P) -> bool
+    where
+        P: Prop;
+
+    fn hash<P, H>(prop: &P, state: &mut H)
+    where
+        P: Prop,
+        H: Hasher;
+}
+
+/// Props stored once each in a [`PropInterner`], compared and hashed by
+/// address. Pays off when equal props are built over and over, and are
+/// costly to compare or hash, as symbol tables are.
+pub struct Interned;
+
+impl PropStorage for Interned {
+    fn store<'t, P>(interner: &'t PropInterner<'t, P>, _: &'t RefArena<P>, prop: P) -> &'t P
+    where
+        P: Prop,
+    {
+        interner.intern(prop).ptr()
+    }
+
+    fn eq<P>(left: &P, right: &P) -> bool
+    where
+        P: Prop,
+    {
+        std::ptr::eq(left, right)
+    }
+
+    fn hash<P, H>(prop: &P, state: &mut H)
+    where
+        P: Prop,
+        H: Hasher,
+    {
+        std::ptr::hash(prop, state)
+    }
+}
+
+/// Props stored as they come, without looking for an equal one, and
+/// compared and hashed by value. Suits props that are cheap to compare and
+/// hash.
+pub struct Unshared;
+
+impl PropStorage for Unshared {
+    fn store<'t, P>(_: &'t PropInterner<'t, P>, arena: &'t RefArena<P>, prop: P) -> &'t P
+    where
+        P: Prop,
+    {
+        arena.alloc(prop).ptr()
+    }
+
+    fn eq<P>(left: &P, right: &P) -> bool
+    where
+        P: Prop,
+    {
+        left == right
+    }
+
+    fn hash<P, H>(prop: &P, state: &mut H)
+    where
+        P: Prop,
+        H: Hasher,
+    {
+        prop.hash(state)
+    }
+}
+
+/// Holds the props of type `P` of the keys of reachability, stored as the
+/// union prop `S` chooses, for as long as the reachability lives.
+pub struct PropStore<'t, S, P>
+where
+    P: Prop,
+{
+    interner: PropInterner<'t, P>,
+    arena: RefArena<P>,
+    storage: PhantomData<S>,
+}
+
+impl<'t, S, P> PropStore<'t, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+    pub fn new() -> Self {
+        Self {
+            interner: PropInterner::new(),
+            arena: RefArena::new(),
+            storage: PhantomData,
+        }
+    }
+
+    pub fn store(&'t self, prop: P) -> StoredProp<'t, S, P> {
+        StoredProp {
+            prop: S::Storage::store(&self.interner, &self.arena, prop),
+            storage: PhantomData,
+        }
+    }
+
+    /// Number of props stored.
+    pub fn len(&self) -> usize {
+        self.interner.len() + self.arena.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interner.is_empty() && self.arena.is_empty()
+    }
+
+    /// Bytes taken by the props stored, not counting memory they own.
+    pub fn bytes(&self) -> usize {
+        self.interner.bytes() + self.arena.bytes()
+    }
+}
+
+impl<S, P> Default for PropStore<'_, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A prop of a [`PropStore`], compared and hashed as the union prop `S`
+/// chooses.
+pub struct StoredProp<'t, S, P> {
+    prop: &'t P,
+    storage: PhantomData<S>,
+}
+
+impl<'t, S, P> StoredProp<'t, S, P> {
+    pub fn get(&self) -> &'t P {
+        self.prop
+    }
+}
+
+impl<S, P> Clone for StoredProp<'_, S, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, P> Copy for StoredProp<'_, S, P> {}
+
+impl<S, P> PartialEq for StoredProp<'_, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+    fn eq(&self, other: &Self) -> bool {
+        S::Storage::eq(self.prop, other.prop)
+    }
+}
+
+impl<S, P> Eq for StoredProp<'_, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+}
+
+impl<S, P> Hash for StoredProp<'_, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        S::Storage::hash(self.prop, state)
+    }
+}
+
+impl<S, P> Debug for StoredProp<'_, S, P>
+where
+    P: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.prop.fmt(f)
+    }
+}
+
+impl<S, P> Deref for StoredProp<'_, S, P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        self.prop
+    }
+}
+
+impl<S, P> Prop for StoredProp<'_, S, P>
+where
+    S: UnionProp,
+    P: Prop,
+{
+}
@@ -1,4 +1,4 @@
-use super::Prop;
+use super::{Prop, PropStorage};
 
 pub trait IntoUnionProp<T> {
     fn into_union_prop(self) -> T;
@@ -38,11 +38,19 @@ where
     }
 }
 
-pub trait UnionProp: Prop + Default {}
+pub trait UnionProp: Prop + Default {
+    /// How reachability stores these props, and arrays of them.
+    type Storage: PropStorage;
+}
 
+/// Declares a union prop `$name` of the props `$type`, defaulting to the
+/// default of `$def`. Its props are [`crate::props::Interned`] unless
+/// another [`PropStorage`] is given last.
 #[macro_export]
 macro_rules! union_prop {
-    {$name:ident, $def:ident, { $( $item:ident($type:ident) ),* }} => {
+    (@storage) => { $crate::props::Interned };
+    (@storage $storage:ty) => { $storage };
+    {$name:ident, $def:ident, { $( $item:ident($type:ident) ),* } $(, $storage:ty)?} => {
         #[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
         pub enum $name {
             $( $item ( $type ) ),*
@@ -67,7 +75,9 @@ macro_rules! union_prop {
                 Self :: $def ( Default::default() )
             }
         }
-        impl $crate::props::UnionProp for $name {}
+        impl $crate::props::UnionProp for $name {
+            type Storage = $crate::union_prop!(@storage $($storage)?);
+        }
         impl $name {
             pub fn prop_name(&self) -> &'static str {
                 match self {
@@ -76,7 +86,7 @@ macro_rules! union_prop {
             }
         }
     };
-    {$name:ident < $l:lifetime >, $def:ident, { $( $item:ident($type:ty) ),* }} => {
+    {$name:ident < $l:lifetime >, $def:ident, { $( $item:ident($type:ty) ),* } $(, $storage:ty)?} => {
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
         pub enum $name < $l >{
             $( $item ( $type ) ),*
@@ -102,7 +112,9 @@ macro_rules! union_prop {
             }
         }
         impl< $l > $crate::props::Prop for $name< $l > {}
-        impl< $l > $crate::props::UnionProp for $name< $l > {}
+        impl< $l > $crate::props::UnionProp for $name< $l > {
+            type Storage = $crate::union_prop!(@storage $($storage)?);
+        }
         impl $name <'_> {
             pub fn prop_name(&self) -> &'static str {
                 match self {
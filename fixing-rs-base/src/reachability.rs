@@ -1,7 +1,9 @@
 mod arena;
+mod proximity;
 mod semantic;
 mod syntactic;
 
 pub use arena::*;
+pub use proximity::*;
 pub use semantic::*;
 pub use syntactic::*;
@@ -2,15 +2,22 @@ use super::{
     syntactic::{GKey, GRule},
     FKey, FRule, SKey, SReachabilityCacheEntity,
 };
-use crate::{props::UnionProp, utils::RefArena};
+use crate::{
+    check::FurthestRejection,
+    props::{PropArray, PropStore, UnionProp},
+    stats::{EngineStats, SCounters},
+    utils::{MemoryBudget, RefArena},
+};
+use std::{cell::Cell, time::Instant};
 
 pub struct GReachabilityArena<'a, 'b, PG>
 where
     PG: UnionProp,
 {
-    pub(super) gedges: RefArena<GKey<'a, PG>>,
+    pub(super) gedges: RefArena<GKey<'a, 'b, PG>>,
     pub(super) grules: RefArena<GRule<'a, 'b, PG>>,
     pub(super) tokens: RefArena<String>,
+    pub(super) props: PropStore<'b, PG, PropArray<PG>>,
 }
 
 impl<'a, 'b, PG> GReachabilityArena<'a, 'b, PG>
@@ -22,8 +29,14 @@ where
             gedges: RefArena::new(),
             grules: RefArena::new(),
             tokens: RefArena::new(),
+            props: PropStore::new(),
         }
     }
+
+    /// Bytes taken by the values allocated so far.
+    pub fn bytes(&self) -> usize {
+        self.gedges.bytes() + self.grules.bytes() + self.tokens.bytes() + self.props.bytes()
+    }
 }
 
 pub struct SReachabilityArena<'a, 'b, PG, PSI, PSS>
@@ -33,9 +46,17 @@ where
     PSS: UnionProp,
 {
     pub(super) sedges: RefArena<SKey<'a, 'b, PG, PSI>>,
-    pub(super) fkey: RefArena<FKey<'a, PG, PSI, PSS>>,
+    pub(super) fkey: RefArena<FKey<'a, 'b, PG, PSI, PSS>>,
     pub(super) frule: RefArena<FRule<'a, 'b, PG, PSI, PSS>>,
     pub(super) cache_entity: RefArena<SReachabilityCacheEntity<'a, 'b, PG, PSI, PSS>>,
+    pub(super) inh_props: PropStore<'b, PSI, PSI>,
+    pub(super) syn_props: PropStore<'b, PSS, PropArray<PSS>>,
+    pub(crate) counters: SCounters,
+    pub(crate) rejection: FurthestRejection,
+    memory_budget: Cell<Option<MemoryBudget>>,
+    memory_budget_exceeded: Cell<bool>,
+    deadline: Cell<Option<Instant>>,
+    deadline_exceeded: Cell<bool>,
 }
 
 impl<'a, 'b, PG, PSI, PSS> SReachabilityArena<'a, 'b, PG, PSI, PSS>
@@ -50,6 +71,74 @@ where
             fkey: RefArena::new(),
             frule: RefArena::new(),
             cache_entity: RefArena::new(),
+            inh_props: PropStore::new(),
+            syn_props: PropStore::new(),
+            counters: SCounters::default(),
+            rejection: FurthestRejection::default(),
+            memory_budget: Cell::new(None),
+            memory_budget_exceeded: Cell::new(false),
+            deadline: Cell::new(None),
+            deadline_exceeded: Cell::new(false),
         }
     }
+
+    /// Bytes taken by the values allocated so far.
+    pub fn bytes(&self) -> usize {
+        self.sedges.bytes()
+            + self.fkey.bytes()
+            + self.frule.bytes()
+            + self.cache_entity.bytes()
+            + self.inh_props.bytes()
+            + self.syn_props.bytes()
+    }
+
+    /// Stops searches, which then find nothing, once the task holds more
+    /// than `budget`.
+    pub fn set_memory_budget(&self, budget: Option<MemoryBudget>) {
+        self.memory_budget.set(budget);
+    }
+
+    /// Whether a search stopped because of the memory budget.
+    pub fn memory_budget_exceeded(&self) -> bool {
+        self.memory_budget_exceeded.get()
+    }
+
+    /// Stops searches, which then find nothing, once `deadline` has passed.
+    pub fn set_deadline(&self, deadline: Option<Instant>) {
+        self.deadline.set(deadline);
+    }
+
+    /// Whether a search stopped because of the deadline.
+    pub fn deadline_exceeded(&self) -> bool {
+        self.deadline_exceeded.get()
+    }
+
+    /// Counts the cache lookups and the props of each method of the
+    /// semantic processor when set, for [`Self::fill_stats`].
+    pub fn set_profile(&self, profile: bool) {
+        self.counters.set_enabled(profile);
+    }
+
+    /// Whether the search should stop, either limit being exceeded with
+    /// `current_bytes` held by the arena of the current search.
+    pub(crate) fn check_limits(&self, current_bytes: usize) -> bool {
+        if let Some(budget) = self.memory_budget.get() {
+            if budget.exceeded(self.bytes() + current_bytes) {
+                self.memory_budget_exceeded.set(true);
+            }
+        }
+        if let Some(deadline) = self.deadline.get() {
+            if Instant::now() > deadline {
+                self.deadline_exceeded.set(true);
+            }
+        }
+        self.memory_budget_exceeded.get() || self.deadline_exceeded.get()
+    }
+
+    /// Records the counters of semantic reachability in `stats`; the cache
+    /// lookups and the props are only counted when profiling.
+    pub fn fill_stats(&self, stats: &mut EngineStats) {
+        stats.semantic_keys = self.sedges.len();
+        self.counters.fill(stats);
+    }
 }
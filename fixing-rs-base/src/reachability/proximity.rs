@@ -0,0 +1,226 @@
+use super::{Edge, GKeyRef, GProcessor, GReachability, GRuleRef};
+use crate::{containers::Map, grammar::SymbolType, props::UnionProp};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::min,
+    hash::Hash,
+    iter::Copied,
+    slice::Iter,
+    vec::IntoIter,
+};
+
+/// Soft preference for fixes whose edits are close to reported error
+/// locations. The score of a derivation is the sum, over its edits, of the
+/// token distance to the nearest error location. The search only uses scores
+/// to order candidates of the same length, so an empty location list leaves
+/// the search unchanged.
+pub struct ErrorProximity<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    locations: Vec<usize>,
+    scores: ScoreMemo<GKeyRef<'a, 'b, PG>>,
+}
+
+impl<'a, 'b, PG> ErrorProximity<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    pub fn new(mut locations: Vec<usize>) -> Self {
+        locations.sort();
+        locations.dedup();
+        Self {
+            locations,
+            scores: ScoreMemo::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    fn distance(&self, loc: usize) -> usize {
+        let index = self.locations.partition_point(|x| *x < loc);
+        let after = self.locations.get(index).map(|x| x - loc);
+        let before = index.checked_sub(1).map(|i| loc - self.locations[i]);
+        min(after.unwrap_or(usize::MAX), before.unwrap_or(usize::MAX))
+    }
+
+    /// Score of a terminal edit spanning `begin..end`: a replacement of token
+    /// `begin` when the span is one token, an insertion before it otherwise.
+    pub fn terminal_score(&self, begin: usize, end: usize) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let score = self.distance(begin);
+        if end == begin && begin > 0 {
+            min(score, self.distance(begin - 1))
+        } else {
+            score
+        }
+    }
+
+    /// Score of deleting the tokens in `begin..end`.
+    pub fn deletion_score(&self, begin: usize, end: usize) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        (begin..end).fold(0, |score, loc| score.saturating_add(self.distance(loc)))
+    }
+
+    /// Score of a concatenation spanning `begin..end` whose children are given
+    /// as `(begin, end, score)`. Tokens not covered by any child are deleted.
+    pub fn concat_score(
+        &self,
+        begin: usize,
+        end: usize,
+        subs: impl IntoIterator<Item = (usize, usize, usize)>,
+    ) -> usize {
+        let mut score = 0usize;
+        let mut current = begin;
+        for (sub_begin, sub_end, sub_score) in subs {
+            score = score.saturating_add(self.deletion_score(current, sub_begin));
+            score = score.saturating_add(sub_score);
+            current = sub_end;
+        }
+        score.saturating_add(self.deletion_score(current, end))
+    }
+
+    /// Best score among all derivations of a syntactic edge. Only edges whose
+    /// lengths are already settled may be scored, as the result is memoized.
+    pub fn edge_score<GProc>(
+        &self,
+        greachability: &GReachability<'a, 'b, '_, PG, GProc>,
+        edge: GKeyRef<'a, 'b, PG>,
+    ) -> usize
+    where
+        GProc: GProcessor<PG = PG>,
+    {
+        if self.is_empty() || edge.length() == 0 {
+            return 0;
+        }
+        self.scores
+            .get_or_compute(edge, || match edge.symbol().symbol_type() {
+                SymbolType::NonTerminal => greachability
+                    .get_sub_edges(edge)
+                    .iter()
+                    .map(|rule| self.rule_score(greachability, edge, *rule))
+                    .min()
+                    .unwrap_or(usize::MAX),
+                SymbolType::LiteralTerminal | SymbolType::SymbolicTerminal => {
+                    self.terminal_score(edge.begin(), edge.end())
+                }
+            })
+    }
+
+    pub fn rule_score<GProc>(
+        &self,
+        greachability: &GReachability<'a, 'b, '_, PG, GProc>,
+        edge: GKeyRef<'a, 'b, PG>,
+        rule: GRuleRef<'a, 'b, PG>,
+    ) -> usize
+    where
+        GProc: GProcessor<PG = PG>,
+    {
+        let subs = [rule.sub1(), rule.sub2()];
+        let subs = subs
+            .iter()
+            .flatten()
+            .map(|sub| (sub.begin(), sub.end(), self.edge_score(greachability, *sub)));
+        self.concat_score(edge.begin(), edge.end(), subs)
+    }
+
+    /// Orders the derivations of `edge` so that the ones closest to the
+    /// errors are explored first. Without error locations, they are
+    /// iterated where they are stored.
+    pub fn order_rules<'s, GProc>(
+        &self,
+        greachability: &'s GReachability<'a, 'b, '_, PG, GProc>,
+        edge: GKeyRef<'a, 'b, PG>,
+    ) -> OrderedRules<'s, 'a, 'b, PG>
+    where
+        GProc: GProcessor<PG = PG>,
+    {
+        let rules = greachability.get_sub_edges(edge).iter().copied();
+        if self.is_empty() {
+            return OrderedRules::Derived(rules);
+        }
+        let mut rules: Vec<_> = rules.collect();
+        rules.sort_by_cached_key(|rule| self.rule_score(greachability, edge, *rule));
+        OrderedRules::Sorted(rules.into_iter())
+    }
+}
+
+/// The derivations of an edge in the order given by
+/// [`ErrorProximity::order_rules`].
+pub enum OrderedRules<'s, 'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    Derived(Copied<Iter<'s, GRuleRef<'a, 'b, PG>>>),
+    Sorted(IntoIter<GRuleRef<'a, 'b, PG>>),
+}
+
+impl<'s, 'a, 'b, PG> Iterator for OrderedRules<'s, 'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    type Item = GRuleRef<'a, 'b, PG>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            OrderedRules::Derived(it) => it.next(),
+            OrderedRules::Sorted(it) => it.next(),
+        }
+    }
+}
+
+/// Memoized scores of edges whose derivations may form cycles of unit
+/// rules. An edge met again while it is being scored counts as
+/// `usize::MAX`, which a cycle cannot improve on, so the scores computed
+/// from it are only kept once that edge is done.
+pub struct ScoreMemo<K> {
+    scores: RefCell<Map<K, usize>>,
+    /// The edges being scored, by their depth in the recursion.
+    open: RefCell<Map<K, usize>>,
+    /// The lowest depth of the open edges met since the current edge was
+    /// opened.
+    low: Cell<usize>,
+}
+
+impl<K> ScoreMemo<K>
+where
+    K: Eq + Hash + Copy,
+{
+    pub fn get_or_compute(&self, key: K, compute: impl FnOnce() -> usize) -> usize {
+        if let Some(score) = self.scores.borrow().get(&key) {
+            return *score;
+        }
+        if let Some(depth) = self.open.borrow().get(&key) {
+            self.low.set(min(self.low.get(), *depth));
+            return usize::MAX;
+        }
+        let depth = self.open.borrow().len();
+        self.open.borrow_mut().insert(key, depth);
+        let outer_low = self.low.replace(usize::MAX);
+        let score = compute();
+        self.open.borrow_mut().remove(&key);
+        if self.low.get() >= depth {
+            self.scores.borrow_mut().insert(key, score);
+            self.low.set(outer_low);
+        } else {
+            self.low.set(min(self.low.get(), outer_low));
+        }
+        score
+    }
+}
+
+impl<K> Default for ScoreMemo<K> {
+    fn default() -> Self {
+        Self {
+            scores: RefCell::new(Map::default()),
+            open: RefCell::new(Map::default()),
+            low: Cell::new(usize::MAX),
+        }
+    }
+}
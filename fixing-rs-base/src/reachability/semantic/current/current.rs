@@ -1,10 +1,10 @@
 use super::{iter_or_cache::SIterOrCache, SCurrentArena, SIter, SIterWrap, SIterWrapRef};
 use crate::{
     containers::Map,
-    props::UnionProp,
+    props::{StoredProp, UnionProp},
     reachability::{
-        semantic::reachability::SReachabilityEdges, GKeyRef, GProcessor, GReachability, SKey,
-        SKeyRef, SProcessor, SReachability, SReachabilityArena,
+        semantic::reachability::SReachabilityEdges, ErrorProximity, GKeyRef, GProcessor,
+        GReachability, SKey, SKeyRef, SProcessor, SReachability, SReachabilityArena,
     },
     utils::RefCellFrom,
 };
@@ -38,6 +38,8 @@ where
     arena: &'c SCurrentArena<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>,
     #[getset(get_copy = "pub")]
     greachability: &'c GReachability<'a, 'b, 'p, PG, GProc>,
+    #[getset(get_copy = "pub")]
+    proximity: &'c ErrorProximity<'a, 'b, PG>,
 }
 
 impl<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>
@@ -54,12 +56,14 @@ where
         base_arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>,
         arena: &'c SCurrentArena<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>,
         greachability: &'c GReachability<'a, 'b, 'p, PG, GProc>,
+        proximity: &'c ErrorProximity<'a, 'b, PG>,
     ) -> Self {
         Self {
-            generators: RefCellFrom::new(Map::new()),
+            generators: RefCellFrom::new(Map::default()),
             processor,
             base_arena,
             greachability,
+            proximity,
             arena,
         }
     }
@@ -67,27 +71,27 @@ where
     pub(super) fn query_edge(
         &'c self,
         syntactic_edge: GKeyRef<'a, 'b, PG>,
-        inh_prop: PSI,
+        inh_prop: StoredProp<'b, PSI, PSI>,
         reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>,
     ) -> SIterOrCache<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc> {
         let (_, edges, cache) = reachability.split();
         let key = SKey::new(syntactic_edge, inh_prop);
         {
-            match cache.query_edge(&key) {
-                Some(v) => return SIterOrCache::Cache(v),
-                None => {}
+            let cached = cache.query_edge(&key);
+            self.base_arena.counters.cache_lookup(cached.is_some());
+            if let Some(v) = cached {
+                return SIterOrCache::Cache(v);
             }
         }
         {
             let b = self.generators.borrow(edges);
-            let result = b.get(&key);
-            match result {
-                Some((_, it)) => {
-                    return SIterOrCache::Iter(it.iter());
-                }
-                _ => {}
+            if let Some((_, it)) = b.get(&key) {
+                return SIterOrCache::Iter(it.iter());
             }
         }
+        if self.base_arena.check_limits(self.arena.sgen.bytes()) {
+            return SIterOrCache::Cache([].iter());
+        }
         let keyref = self.base_arena.sedges.alloc(key);
         let itwrap = self
             .arena
@@ -100,12 +104,12 @@ where
     }
 
     pub(super) fn cache(&self, reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>) {
-        let mut gens = Map::new();
+        let mut gens = Map::default();
         let (_, edges, cache) = reachability.split();
         let gens_b = self.generators.borrow_mut(edges);
         mem::swap(&mut gens, gens_b);
         for (_, (kref, itwarp)) in gens.into_iter() {
-            cache.add_cache(kref, itwarp.take_props());
+            cache.add_cache(kref, itwarp.take_edges());
         }
     }
 }
@@ -1,11 +1,21 @@
 use super::{SCurrentArena, SReachabilityCurrent};
 use crate::{
-    props::UnionProp,
+    props::{StoredProp, UnionProp},
     reachability::{
-        FKeyRef, GProcessor, GReachability, SProcessor, SReachability, SReachabilityArena,
+        ErrorProximity, FKeyRef, GProcessor, GReachability, SProcessor, SReachability,
+        SReachabilityArena,
     },
+    stats::SMethod,
 };
 
+/// Finds shortest semantically valid start edges with a length in
+/// `length_from..=length_to`, one from each syntactic start edge of that
+/// length that has any, paired with the distance of the syntactic edge to the
+/// reported errors given by `proximity`.
+///
+/// The fix taken from a syntactic start edge is the first one reached, its
+/// derivations being explored nearest to the errors first. Callers choose
+/// among the start edges found, see [`crate::fixing::rank_fixes`].
 pub fn find<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>(
     processor: &'q SProc,
     base_arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>,
@@ -13,7 +23,8 @@ pub fn find<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>(
     length_from: usize,
     length_to: usize,
     reachability: &'c mut SReachability<'a, 'b, PG, PSI, PSS>,
-) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>>
+    proximity: &'c ErrorProximity<'a, 'b, PG>,
+) -> Vec<(usize, FKeyRef<'a, 'b, PG, PSI, PSS>)>
 where
     PG: UnionProp,
     PSI: UnionProp,
@@ -21,10 +32,12 @@ where
     GProc: GProcessor<PG = PG>,
     SProc: SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
 {
-    let root_prop = processor.process_root_inh();
+    let root_prop = base_arena.inh_props.store(processor.process_root_inh());
+    base_arena.counters.props(SMethod::RootInh, 1);
 
     let arena = SCurrentArena::new();
-    let current = SReachabilityCurrent::new(processor, base_arena, &arena, greachability);
+    let current =
+        SReachabilityCurrent::new(processor, base_arena, &arena, greachability, proximity);
     let result = find_inner(
         greachability,
         length_from,
@@ -34,6 +47,9 @@ where
         &current,
     );
     current.cache(reachability);
+    base_arena
+        .counters
+        .arena_bytes(greachability.arena_bytes() + base_arena.bytes() + arena.sgen.bytes());
     result
 }
 
@@ -41,10 +57,10 @@ pub fn find_inner<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>(
     greachability: &'c GReachability<'a, 'b, 'p, PG, GProc>,
     length_from: usize,
     length_to: usize,
-    root_prop: PSI,
+    root_prop: StoredProp<'b, PSI, PSI>,
     reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>,
     current: &'c SReachabilityCurrent<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>,
-) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>>
+) -> Vec<(usize, FKeyRef<'a, 'b, PG, PSI, PSS>)>
 where
     PG: UnionProp,
     PSI: UnionProp,
@@ -52,19 +68,20 @@ where
     GProc: GProcessor<PG = PG>,
     SProc: SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
 {
+    let mut result = Vec::new();
     for cur_len in length_from..(length_to + 1) {
-        match greachability.get_start_edges().get(cur_len) {
-            Some(e) => {
-                for gkey in e.iter() {
-                    let mut it = current.query_edge(gkey.clone(), root_prop.clone(), reachability);
-                    match it.next(reachability) {
-                        Some((_, fkey)) => return Some(fkey),
-                        None => {}
-                    }
+        if let Some(e) = greachability.get_start_edges().get(cur_len) {
+            for gkey in e.iter() {
+                let mut it = current.query_edge(*gkey, root_prop, reachability);
+                if let Some(fkey) = it.next(reachability) {
+                    let score = current.proximity().edge_score(greachability, *gkey);
+                    result.push((score, fkey));
                 }
             }
-            None => {}
+        }
+        if !result.is_empty() {
+            break;
         }
     }
-    None
+    result
 }
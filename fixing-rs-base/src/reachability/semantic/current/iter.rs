@@ -1,13 +1,13 @@
 use super::{iter_or_cache::SIterOrCache, SReachabilityCurrent};
 use crate::{
     grammar::{GrammarRuleLength, GrammarRuleType, SymbolType},
-    props::{IntoPropResult, PropArray, PropResult, PropResultIter, UnionProp},
+    props::{IntoPropResult, Prop, PropArray, PropResult, PropResultIter, StoredProp, UnionProp},
     reachability::{
-        Edge, FKeyRef, GProcessor, GReachability, GRule, GRuleRef, SKeyRef, SProcessor,
-        SReachability,
+        Edge, FKeyRef, GKeyRef, GProcessor, GReachability, GRuleRef, OrderedRules, SKeyRef,
+        SProcessor, SReachability,
     },
+    stats::SMethod,
 };
-use std::collections::hash_map::Values;
 
 pub struct SIter<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>
 where
@@ -23,16 +23,16 @@ where
     processor: &'q SProc,
     reachability: &'c SReachabilityCurrent<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>,
 
-    rule_it: Option<Values<'c, &'b GRule<'a, 'b, PG>, GRuleRef<'a, 'b, PG>>>,
+    rule_it: Option<OrderedRules<'c, 'a, 'b, PG>>,
     rule: Option<GRuleRef<'a, 'b, PG>>,
     left_inh_it: Option<PropResultIter<PSI>>,
-    left_inh: Option<PSI>,
+    left_inh: Option<StoredProp<'b, PSI, PSI>>,
     left_syn_it: Option<SIterOrCache<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>>,
-    left_syn: Option<PropArray<PSS>>,
+    left_syn: Option<StoredProp<'b, PSS, PropArray<PSS>>>,
     right_inh_it: Option<PropResultIter<PSI>>,
-    right_inh: Option<PSI>,
+    right_inh: Option<StoredProp<'b, PSI, PSI>>,
     right_syn_it: Option<SIterOrCache<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>>,
-    right_syn: Option<PropArray<PSS>>,
+    right_syn: Option<StoredProp<'b, PSS, PropArray<PSS>>>,
     syn_it: Option<PropResultIter<PropArray<PSS>>>,
     syn: Option<PropArray<PSS>>,
     literal: Option<&'b str>,
@@ -89,23 +89,54 @@ where
             literal: None,
         }
     }
+    fn store_inh(&self, inh: PSI) -> StoredProp<'b, PSI, PSI> {
+        self.reachability.base_arena().inh_props.store(inh)
+    }
+    /// Counts the props `method` gave for `edge`, recording a rejection if
+    /// there are none and `edge` covers original tokens only.
+    fn counted<P: Prop>(
+        &self,
+        method: SMethod,
+        edge: GKeyRef<'a, 'b, PG>,
+        props: PropResult<P>,
+    ) -> PropResult<P> {
+        let base_arena = self.reachability.base_arena();
+        base_arena.counters.props(method, props.len());
+        if props.len() == 0 && edge.length() == 0 {
+            base_arena.rejection.record(edge.begin(), edge.end(), || {
+                let symbol = edge.symbol();
+                let symbol = symbol.name();
+                match method {
+                    SMethod::SymbolicTerminalSyn => format!(
+                        "`{}` is not a valid {}",
+                        self.greachability.literals()[edge.begin()],
+                        symbol
+                    ),
+                    SMethod::NonTerminalInh => format!("{} is not allowed here", symbol),
+                    _ => format!("invalid {}", symbol),
+                }
+            });
+        }
+        props
+    }
     fn process_left_inh(&self) -> PropResult<PSI> {
         let rule = self.rule.unwrap();
         let inh_prop = self.key.inh_prop();
         match rule.rule().rule_type() {
-            GrammarRuleType::Induction => inh_prop.clone().into_prop_result(),
+            GrammarRuleType::Induction => inh_prop.get().clone().into_prop_result(),
             GrammarRuleType::ConcatOne | GrammarRuleType::ConcatTwo => {
                 let grule = rule.rule();
-                self.processor.process_non_terminal_inh(
+                let props = self.processor.process_non_terminal_inh(
                     grule.induction(),
                     self.key.syntactic_edge().prop(),
                     grule.induction_id(),
                     0,
                     &inh_prop,
                     &[][..],
-                )
+                );
+                self.counted(SMethod::NonTerminalInh, rule.sub1().unwrap(), props)
             }
-            GrammarRuleType::ConcatAppend => inh_prop.clone().into_prop_result(),
+            GrammarRuleType::ConcatAppend => inh_prop.get().clone().into_prop_result(),
             _ => unreachable!(),
         }
     }
@@ -115,7 +146,7 @@ where
     ) -> SIterOrCache<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc> {
         let sub1 = self.rule.unwrap().sub1().unwrap();
         self.reachability
-            .query_edge(sub1, self.left_inh.clone().unwrap(), reachability)
+            .query_edge(sub1, self.left_inh.unwrap(), reachability)
     }
     fn process_right_inh(&self) -> PropResult<PSI> {
         let rule = self.rule.unwrap();
@@ -124,25 +155,27 @@ where
         match rule.rule().rule_type() {
             GrammarRuleType::ConcatAppend => {
                 let grule = rule.rule();
-                self.processor.process_non_terminal_inh(
+                let props = self.processor.process_non_terminal_inh(
                     grule.induction(),
                     self.key.syntactic_edge().prop(),
                     grule.induction_id(),
                     grule.induction_location().unwrap(),
                     &inh_prop,
                     left_syn.unwrap_multiple(),
-                )
+                );
+                self.counted(SMethod::NonTerminalInh, rule.sub2().unwrap(), props)
             }
             GrammarRuleType::ConcatTwo => {
                 let grule = rule.rule();
-                self.processor.process_non_terminal_inh(
+                let props = self.processor.process_non_terminal_inh(
                     grule.induction(),
                     self.key.syntactic_edge().prop(),
                     grule.induction_id(),
                     grule.induction_location().unwrap(),
                     &inh_prop,
                     &[left_syn.unwrap_single().clone()],
-                )
+                );
+                self.counted(SMethod::NonTerminalInh, rule.sub2().unwrap(), props)
             }
             _ => unreachable!(),
         }
@@ -153,7 +186,7 @@ where
     ) -> SIterOrCache<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc> {
         self.reachability.query_edge(
             self.rule.unwrap().sub2().unwrap(),
-            self.right_inh.clone().unwrap(),
+            self.right_inh.unwrap(),
             reachability,
         )
     }
@@ -165,35 +198,36 @@ where
             SymbolType::LiteralTerminal => {
                 PropArray::Single(<PSS as Default>::default()).into_prop_result()
             }
-            SymbolType::SymbolicTerminal => (self.processor.process_symbolic_terminal_syn(
-                symbol,
-                gprop,
-                &self.key.inh_prop(),
+            SymbolType::SymbolicTerminal => {
                 if gedge.length() == 0 {
                     self.literal =
                         Some(self.greachability.literals()[self.key.syntactic_edge().begin()]);
-                    self.literal
-                } else {
-                    None
-                },
-            ))
-            .into(),
+                }
+                let props = self.processor.process_symbolic_terminal_syn(
+                    symbol,
+                    gprop,
+                    &self.key.inh_prop(),
+                    self.literal,
+                );
+                self.counted(SMethod::SymbolicTerminalSyn, gedge, props)
+                    .into()
+            }
             SymbolType::NonTerminal => {
                 let rule = self.rule.unwrap();
                 let grule = rule.rule();
                 let symbol = grule.induction();
                 let induction_id = grule.induction_id();
                 match rule.rule().rule_type() {
-                    GrammarRuleType::Induction => self
-                        .processor
-                        .process_non_terminal_syn(
+                    GrammarRuleType::Induction => {
+                        let props = self.processor.process_non_terminal_syn(
                             symbol,
                             gprop,
                             induction_id,
                             &self.key.inh_prop(),
                             self.left_syn.as_ref().unwrap().unwrap_multiple(),
-                        )
-                        .into(),
+                        );
+                        self.counted(SMethod::NonTerminalSyn, gedge, props).into()
+                    }
                     GrammarRuleType::ConcatZero => {
                         PropArray::Multiple(vec![].into()).into_prop_result()
                     }
@@ -229,7 +263,7 @@ where
     pub(super) fn next(
         &mut self,
         reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>,
-    ) -> Option<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)> {
+    ) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         loop {
             self.state = match self.state {
                 ItState::Initial => match self.key.syntactic_edge().symbol().symbol_type() {
@@ -237,10 +271,11 @@ where
                         ItState::ReadyIterSyn
                     }
                     SymbolType::NonTerminal => {
+                        let gedge = self.key.syntactic_edge();
                         self.rule_it = Some(
-                            self.greachability
-                                .get_sub_edges(self.key.syntactic_edge())
-                                .values(),
+                            self.reachability
+                                .proximity()
+                                .order_rules(self.greachability, gedge),
                         );
                         ItState::IterRule
                     }
@@ -263,7 +298,7 @@ where
                 },
                 ItState::IterLeftInh => match self.left_inh_it.as_mut().unwrap().next() {
                     Some(left_inh) => {
-                        self.left_inh = Some(left_inh);
+                        self.left_inh = Some(self.store_inh(left_inh));
                         self.left_syn_it = Some(self.process_left_syn(reachability));
                         ItState::IterLeftSyn
                     }
@@ -275,7 +310,7 @@ where
                 ItState::IterLeftSyn => {
                     match self.left_syn_it.as_mut().unwrap().next(reachability) {
                         Some(left_syn) => {
-                            self.left_syn = Some(left_syn.0);
+                            self.left_syn = Some(left_syn.syn_prop());
                             match self.rule.unwrap().rule().rule_type().length() {
                                 GrammarRuleLength::One => ItState::ReadyIterSyn,
                                 GrammarRuleLength::Two => {
@@ -293,7 +328,7 @@ where
                 }
                 ItState::IterRightInh => match self.right_inh_it.as_mut().unwrap().next() {
                     Some(right_inh) => {
-                        self.right_inh = Some(right_inh);
+                        self.right_inh = Some(self.store_inh(right_inh));
                         self.right_syn_it = Some(self.process_right_syn(reachability));
                         ItState::IterRightSyn
                     }
@@ -305,7 +340,7 @@ where
                 ItState::IterRightSyn => {
                     match self.right_syn_it.as_mut().unwrap().next(reachability) {
                         Some(right_syn) => {
-                            self.right_syn = Some(right_syn.0);
+                            self.right_syn = Some(right_syn.syn_prop());
                             ItState::ReadyIterSyn
                         }
                         None => {
@@ -342,10 +377,11 @@ where
                 },
                 ItState::AssemblyResult => {
                     self.state = ItState::IterSyn;
-                    let (_, edges, _) = reachability.split();
+                    let (arena, edges, _) = reachability.split();
+                    let syn = arena.syn_props.store(self.syn.take().unwrap());
                     match edges.assembly_result(
                         self.key,
-                        self.syn.as_mut().unwrap(),
+                        syn,
                         &self.rule,
                         &self.left_inh,
                         &self.left_syn,
@@ -353,9 +389,7 @@ where
                         &self.right_syn,
                         self.literal,
                     ) {
-                        Some(fkey) => {
-                            break Some((self.syn.take().unwrap(), fkey));
-                        }
+                        Some(fkey) => break Some(fkey),
                         None => continue,
                     }
                 }
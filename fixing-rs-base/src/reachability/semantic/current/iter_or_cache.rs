@@ -2,7 +2,7 @@
 
 use super::SIterWrapIter;
 use crate::{
-    props::{PropArray, UnionProp},
+    props::UnionProp,
     reachability::{FKeyRef, GProcessor, SProcessor, SReachability},
 };
 use std::slice::Iter;
@@ -16,7 +16,7 @@ where
     SProc: SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
 {
     Iter(SIterWrapIter<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>),
-    Cache(Iter<'b, (PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)>),
+    Cache(Iter<'b, FKeyRef<'a, 'b, PG, PSI, PSS>>),
 }
 
 impl<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>
@@ -31,10 +31,10 @@ where
     pub(super) fn next(
         &mut self,
         reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>,
-    ) -> Option<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)> {
+    ) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         match self {
             SIterOrCache::Iter(ref mut iter) => iter.next(reachability),
-            SIterOrCache::Cache(ref mut iter) => iter.next().cloned(),
+            SIterOrCache::Cache(ref mut iter) => iter.next().copied(),
         }
     }
 }
@@ -1,6 +1,6 @@
 use super::{SIter, SIterWrapIter};
 use crate::{
-    props::{PropArray, UnionProp},
+    props::UnionProp,
     reachability::{FKeyRef, GProcessor, SProcessor, SReachability},
     utils::Pointer,
 };
@@ -33,7 +33,7 @@ where
 {
     content: RefCell<(
         SIter<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>,
-        Vec<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)>,
+        Vec<FKeyRef<'a, 'b, PG, PSI, PSS>>,
     )>,
 }
 
@@ -55,16 +55,13 @@ where
         }
     }
 
-    pub(super) fn get_from_vec(
-        &self,
-        index: usize,
-    ) -> Option<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)> {
+    pub(super) fn get_from_vec(&self, index: usize) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         self.content
             .try_borrow()
             .expect("SIterWrap reentrance")
             .1
             .get(index)
-            .cloned()
+            .copied()
     }
 
     pub(super) fn new(sit: SIter<'a, 'b, 'c, 'p, 'q, PG, PSI, PSS, GProc, SProc>) -> Self {
@@ -73,7 +70,7 @@ where
         }
     }
 
-    pub(super) fn take_props(&self) -> Vec<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)> {
+    pub(super) fn take_edges(&self) -> Vec<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         let mut result = Vec::new();
         mem::swap(&mut result, &mut self.content.borrow_mut().1);
         result
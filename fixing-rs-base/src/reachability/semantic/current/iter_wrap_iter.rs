@@ -1,5 +1,5 @@
 use crate::{
-    props::{PropArray, UnionProp},
+    props::UnionProp,
     reachability::{FKeyRef, GProcessor, SProcessor, SReachability},
 };
 
@@ -37,7 +37,7 @@ where
     pub(super) fn next(
         &mut self,
         reachability: &mut SReachability<'a, 'b, PG, PSI, PSS>,
-    ) -> Option<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)> {
+    ) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         let mut res = self.content.get_from_vec(self.cur_loc);
         if res.is_none() {
             self.content.next(reachability);
@@ -1,12 +1,12 @@
 use crate::{
-    containers::Map,
+    containers::PointerSet,
     grammar::{GrammarRuleRef, SymbolRef},
-    props::{PropArray, UnionProp},
+    props::{PropArray, StoredProp, UnionProp},
     utils::Pointer,
 };
 
 #[derive(Debug, PartialEq, Eq, Hash, Getters, CopyGetters)]
-pub struct FKey<'a, PG, PSI, PSS>
+pub struct FKey<'a, 'b, PG, PSI, PSS>
 where
     PG: UnionProp,
     PSI: UnionProp,
@@ -20,13 +20,13 @@ where
     symbol: SymbolRef<'a>,
     #[getset(get = "pub")]
     gprop: PropArray<PG>,
-    #[getset(get = "pub")]
-    inh_prop: PSI,
-    #[getset(get = "pub")]
-    syn_prop: PropArray<PSS>,
+    #[getset(get_copy = "pub")]
+    inh_prop: StoredProp<'b, PSI, PSI>,
+    #[getset(get_copy = "pub")]
+    syn_prop: StoredProp<'b, PSS, PropArray<PSS>>,
 }
 
-impl<'a, PG, PSI, PSS> FKey<'a, PG, PSI, PSS>
+impl<'a, 'b, PG, PSI, PSS> FKey<'a, 'b, PG, PSI, PSS>
 where
     PG: UnionProp,
     PSI: UnionProp,
@@ -37,8 +37,8 @@ where
         end: usize,
         symbol: SymbolRef<'a>,
         gprop: PropArray<PG>,
-        inh_prop: PSI,
-        syn_prop: PropArray<PSS>,
+        inh_prop: StoredProp<'b, PSI, PSI>,
+        syn_prop: StoredProp<'b, PSS, PropArray<PSS>>,
     ) -> Self {
         Self {
             begin,
@@ -97,7 +97,7 @@ where
     #[getset(get_copy = "pub")]
     length: usize,
     #[getset(get = "pub")]
-    rules: Map<&'b FRule<'a, 'b, PG, PSI, PSS>, FRuleRef<'a, 'b, PG, PSI, PSS>>,
+    rules: PointerSet<'b, FRule<'a, 'b, PG, PSI, PSS>>,
     #[getset(get = "pub")]
     literal: Option<String>,
 }
@@ -116,7 +116,7 @@ where
         Self {
             key,
             length,
-            rules: Map::new(),
+            rules: PointerSet::new(),
             literal: literal.map(|s| s.to_string()),
         }
     }
@@ -124,13 +124,13 @@ where
         self.length = length;
     }
     pub(super) fn insert_rule(&mut self, rule: FRuleRef<'a, 'b, PG, PSI, PSS>) {
-        self.rules.insert(rule.ptr(), rule);
+        self.rules.insert(rule);
     }
     pub(super) fn clear_rules(&mut self) {
         self.rules.clear();
     }
 }
 
-pub type FKeyRef<'a, 'b, PG, PSI, PSS> = Pointer<'b, FKey<'a, PG, PSI, PSS>>;
+pub type FKeyRef<'a, 'b, PG, PSI, PSS> = Pointer<'b, FKey<'a, 'b, PG, PSI, PSS>>;
 pub type FRuleRef<'a, 'b, PG, PSI, PSS> = Pointer<'b, FRule<'a, 'b, PG, PSI, PSS>>;
 pub type FEntityRef<'a, 'b, PG, PSI, PSS> = Pointer<'b, FEntity<'a, 'b, PG, PSI, PSS>>;
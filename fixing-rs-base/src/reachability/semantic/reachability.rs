@@ -1,5 +1,9 @@
-use super::{FKey, SProcessor};
-use crate::{grammar::SymbolType, props::UnionProp, reachability::SReachabilityArena};
+use super::{FKey, FKeyRef, FRuleRef, SProcessor};
+use crate::{
+    grammar::SymbolType,
+    props::UnionProp,
+    reachability::{ErrorProximity, SReachabilityArena, ScoreMemo},
+};
 use log::info;
 
 mod cache;
@@ -44,19 +48,67 @@ where
 
     pub fn generate_from(
         &self,
-        start: &FKey<'a, PG, PSI, PSS>,
+        start: &FKey<'a, 'b, PG, PSI, PSS>,
         proc: &impl SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
+        proximity: &ErrorProximity<'a, 'b, PG>,
         verbose: bool,
     ) -> Vec<String> {
         let mut result = Vec::new();
-        self.append(start, proc, &mut result, verbose);
+        let scores = ScoreMemo::default();
+        self.append(start, proc, proximity, &scores, &mut result, verbose);
         result
     }
 
+    fn edit_score(
+        &self,
+        key: FKeyRef<'a, 'b, PG, PSI, PSS>,
+        proximity: &ErrorProximity<'a, 'b, PG>,
+        scores: &ScoreMemo<FKeyRef<'a, 'b, PG, PSI, PSS>>,
+    ) -> usize {
+        let entity = self.edges.get_entity(key.ptr()).unwrap();
+        if entity.length() == 0 {
+            return 0;
+        }
+        scores.get_or_compute(key, || match key.symbol().symbol_type() {
+            SymbolType::NonTerminal => entity
+                .rules()
+                .iter()
+                .map(|rule| self.rule_score(key, *rule, proximity, scores))
+                .min()
+                .unwrap_or(usize::MAX),
+            SymbolType::LiteralTerminal | SymbolType::SymbolicTerminal => {
+                proximity.terminal_score(key.begin(), key.end())
+            }
+        })
+    }
+
+    fn rule_score(
+        &self,
+        key: FKeyRef<'a, 'b, PG, PSI, PSS>,
+        rule: FRuleRef<'a, 'b, PG, PSI, PSS>,
+        proximity: &ErrorProximity<'a, 'b, PG>,
+        scores: &ScoreMemo<FKeyRef<'a, 'b, PG, PSI, PSS>>,
+    ) -> usize {
+        let subs: Vec<_> = [rule.right1(), rule.right2()]
+            .into_iter()
+            .flatten()
+            .map(|sub| {
+                (
+                    sub.begin(),
+                    sub.end(),
+                    self.edit_score(sub, proximity, scores),
+                )
+            })
+            .collect();
+        proximity.concat_score(key.begin(), key.end(), subs)
+    }
+
     fn append(
         &self,
-        current: &FKey<'a, PG, PSI, PSS>,
+        current: &FKey<'a, 'b, PG, PSI, PSS>,
         proc: &impl SProcessor<PG = PG, PSI = PSI, PSS = PSS>,
+        proximity: &ErrorProximity<'a, 'b, PG>,
+        scores: &ScoreMemo<FKeyRef<'a, 'b, PG, PSI, PSS>>,
         result: &mut Vec<String>,
         verbose: bool,
     ) {
@@ -76,12 +128,22 @@ where
         }
         match current.symbol().symbol_type() {
             SymbolType::NonTerminal => {
-                let entity = entity.rules().values().next().unwrap();
+                // The first derivation is the one the search reached first.
+                let key = entity.key();
+                let entity = if proximity.is_empty() {
+                    entity.rules().iter().next().unwrap()
+                } else {
+                    entity
+                        .rules()
+                        .iter()
+                        .min_by_key(|rule| self.rule_score(key, **rule, proximity, scores))
+                        .unwrap()
+                };
                 if let Some(key) = entity.right1() {
-                    self.append(key.ptr(), proc, result, verbose);
+                    self.append(key.ptr(), proc, proximity, scores, result, verbose);
                 }
                 if let Some(key) = entity.right2() {
-                    self.append(key.ptr(), proc, result, verbose);
+                    self.append(key.ptr(), proc, proximity, scores, result, verbose);
                 }
             }
             SymbolType::LiteralTerminal => {
@@ -3,13 +3,12 @@ use std::slice::Iter;
 use super::super::{FKeyRef, SKeyRef};
 use crate::{
     containers::Map,
-    props::{PropArray, UnionProp},
+    props::UnionProp,
     reachability::{SKey, SReachabilityArena},
     utils::Pointer,
 };
 
-pub type SReachabilityCacheEntity<'a, 'b, PG, PSI, PSS> =
-    Vec<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)>;
+pub type SReachabilityCacheEntity<'a, 'b, PG, PSI, PSS> = Vec<FKeyRef<'a, 'b, PG, PSI, PSS>>;
 pub type SReachabilityCacheEntityRef<'a, 'b, PG, PSI, PSS> =
     Pointer<'b, SReachabilityCacheEntity<'a, 'b, PG, PSI, PSS>>;
 
@@ -31,7 +30,7 @@ where
 {
     pub(super) fn new(arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>) -> Self {
         Self {
-            cache: Map::new(),
+            cache: Map::default(),
             arena,
         }
     }
@@ -39,7 +38,7 @@ where
     pub(in super::super) fn add_cache(
         &mut self,
         key: SKeyRef<'a, 'b, PG, PSI>,
-        edges: Vec<(PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)>,
+        edges: Vec<FKeyRef<'a, 'b, PG, PSI, PSS>>,
     ) {
         let edges = self.arena.cache_entity.alloc(edges);
         self.cache.insert(key.ptr(), edges);
@@ -48,7 +47,7 @@ where
     pub(in super::super) fn query_edge(
         &self,
         key: &SKey<'a, 'b, PG, PSI>,
-    ) -> Option<Iter<'b, (PropArray<PSS>, FKeyRef<'a, 'b, PG, PSI, PSS>)>> {
+    ) -> Option<Iter<'b, FKeyRef<'a, 'b, PG, PSI, PSS>>> {
         match self.cache.get(key) {
             Some(v) => Some(v.ptr().iter()),
             None => None,
@@ -1,7 +1,7 @@
 use crate::{
     containers::Map,
     grammar::{GrammarRuleLength, SymbolType},
-    props::{PropArray, UnionProp},
+    props::{PropArray, StoredProp, UnionProp},
     reachability::{
         Edge, FEntity, FKey, FKeyRef, FRule, GKeyRef, GRuleRef, SKeyRef, SReachabilityArena,
     },
@@ -14,7 +14,7 @@ where
     PSS: UnionProp,
 {
     arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>,
-    edges: Map<&'b FKey<'a, PG, PSI, PSS>, FEntity<'a, 'b, PG, PSI, PSS>>,
+    edges: Map<&'b FKey<'a, 'b, PG, PSI, PSS>, FEntity<'a, 'b, PG, PSI, PSS>>,
 }
 
 impl<'a, 'b, PG, PSI, PSS> SReachabilityEdges<'a, 'b, PG, PSI, PSS>
@@ -26,23 +26,23 @@ where
     pub(super) fn new(arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>) -> Self {
         Self {
             arena,
-            edges: Map::new(),
+            edges: Map::default(),
         }
     }
 
     pub fn get_entity(
         &self,
-        key: &FKey<'a, PG, PSI, PSS>,
+        key: &FKey<'a, 'b, PG, PSI, PSS>,
     ) -> Option<&FEntity<'a, 'b, PG, PSI, PSS>> {
         self.edges.get(key)
     }
 
     fn find_or_add_edge<'t>(
         arena: &'b SReachabilityArena<'a, 'b, PG, PSI, PSS>,
-        edges: &'t mut Map<&'b FKey<'a, PG, PSI, PSS>, FEntity<'a, 'b, PG, PSI, PSS>>,
+        edges: &'t mut Map<&'b FKey<'a, 'b, PG, PSI, PSS>, FEntity<'a, 'b, PG, PSI, PSS>>,
         gedge: GKeyRef<'a, 'b, PG>,
-        inh: &PSI,
-        syn: &PropArray<PSS>,
+        inh: StoredProp<'b, PSI, PSI>,
+        syn: StoredProp<'b, PSS, PropArray<PSS>>,
         literal: Option<&str>,
     ) -> &'t mut FEntity<'a, 'b, PG, PSI, PSS> {
         let edge_key = FKey::new(
@@ -50,8 +50,8 @@ where
             gedge.end(),
             gedge.symbol(),
             gedge.prop().clone(),
-            inh.clone(),
-            syn.clone(),
+            inh,
+            syn,
         );
         if !edges.contains_key(&edge_key) {
             let edge_key = arena.fkey.alloc(edge_key);
@@ -66,12 +66,12 @@ where
     pub(in super::super) fn assembly_result(
         &mut self,
         key: SKeyRef<'a, 'b, PG, PSI>,
-        syn: &PropArray<PSS>,
+        syn: StoredProp<'b, PSS, PropArray<PSS>>,
         rule: &Option<GRuleRef<'a, 'b, PG>>,
-        left_inh: &Option<PSI>,
-        left_syn: &Option<PropArray<PSS>>,
-        right_inh: &Option<PSI>,
-        right_syn: &Option<PropArray<PSS>>,
+        left_inh: &Option<StoredProp<'b, PSI, PSI>>,
+        left_syn: &Option<StoredProp<'b, PSS, PropArray<PSS>>>,
+        right_inh: &Option<StoredProp<'b, PSI, PSI>>,
+        right_syn: &Option<StoredProp<'b, PSS, PropArray<PSS>>>,
         literal: Option<&str>,
     ) -> Option<FKeyRef<'a, 'b, PG, PSI, PSS>> {
         let gedge = key.syntactic_edge();
@@ -87,8 +87,8 @@ where
                             self.arena,
                             &mut self.edges,
                             rule.sub1().unwrap(),
-                            left_inh.as_ref().unwrap(),
-                            left_syn.as_ref().unwrap(),
+                            left_inh.unwrap(),
+                            left_syn.unwrap(),
                             None,
                         )
                         .key(),
@@ -102,8 +102,8 @@ where
                             self.arena,
                             &mut self.edges,
                             rule.sub2().unwrap(),
-                            right_inh.as_ref().unwrap(),
-                            right_syn.as_ref().unwrap(),
+                            right_inh.unwrap(),
+                            right_syn.unwrap(),
                             None,
                         )
                         .key(),
@@ -1,6 +1,10 @@
 use std::fmt::Debug;
 
-use crate::{props::UnionProp, reachability::{GKeyRef, Edge}, utils::Pointer};
+use crate::{
+    props::{StoredProp, UnionProp},
+    reachability::{Edge, GKeyRef},
+    utils::Pointer,
+};
 
 #[derive(PartialEq, Eq, Hash, Getters, CopyGetters)]
 pub struct SKey<'a, 'b, PG, PSI>
@@ -10,8 +14,8 @@ where
 {
     #[getset(get_copy = "pub")]
     syntactic_edge: GKeyRef<'a, 'b, PG>,
-    #[getset(get = "pub")]
-    inh_prop: PSI,
+    #[getset(get_copy = "pub")]
+    inh_prop: StoredProp<'b, PSI, PSI>,
 }
 
 impl<'a, 'b, PG, PSI> SKey<'a, 'b, PG, PSI>
@@ -19,7 +23,10 @@ where
     PG: UnionProp,
     PSI: UnionProp,
 {
-    pub(super) fn new(syntactic_edge: GKeyRef<'a, 'b, PG>, inh_prop: PSI) -> Self {
+    pub(super) fn new(
+        syntactic_edge: GKeyRef<'a, 'b, PG>,
+        inh_prop: StoredProp<'b, PSI, PSI>,
+    ) -> Self {
         Self {
             syntactic_edge,
             inh_prop,
@@ -1,9 +1,11 @@
+mod bounds;
 mod edge;
 mod edgemap;
 mod processor;
 mod reachability;
 
+pub use bounds::GBounds;
 pub use edge::{GKey, GKeyRef, GRule, GRuleRef};
 pub use edgemap::{Edge, EdgeMap};
 pub use processor::GProcessor;
-pub use reachability::GReachability;
+pub use reachability::{GReachability, GReachabilityOptions};
@@ -0,0 +1,170 @@
+use crate::grammar::{Grammar, GrammarRuleLength, GrammarSymbolsRef, SymbolRef, SymbolType};
+
+const UNBOUNDED: usize = usize::MAX;
+
+/// Lower bounds on the edits a fix needs outside of an edge, from the numbers
+/// of tokens the symbols of the grammar may yield.
+///
+/// A symbol at `begin..end` sits in a sentential form `α X β` of the start
+/// symbol, where `α` covers the first `begin` tokens and `β` the last ones.
+/// Covering `begin` tokens by `α` takes at least `|yield(α) - begin|`
+/// insertions or deletions, so the bound only needs the fewest and most
+/// tokens `α` and `β` may yield over all such forms.
+pub struct GBounds {
+    token_length: usize,
+    min_left: Vec<usize>,
+    max_left: Vec<usize>,
+    min_right: Vec<usize>,
+    max_right: Vec<usize>,
+}
+
+impl GBounds {
+    pub fn new<'a>(grammar: &'a Grammar<'a>, token_length: usize) -> Self {
+        let GrammarSymbolsRef {
+            literal_terminals,
+            symbolic_terminals,
+            non_terminals,
+            start_symbol,
+            unknown_terminal,
+            ..
+        } = grammar.get_symbol_ref();
+        let symbols: Vec<SymbolRef<'a>> = literal_terminals
+            .values()
+            .chain(symbolic_terminals.values())
+            .chain(non_terminals.values())
+            .chain(std::iter::once(&unknown_terminal))
+            .copied()
+            .collect();
+        let count = symbols.iter().map(|x| x.symbol_id() + 1).max().unwrap_or(0);
+        let rules: Vec<_> = non_terminals
+            .values()
+            .flat_map(|x| x.rules(grammar).iter().copied())
+            .collect();
+
+        // Yields beyond the length of the input bound nothing more, so the
+        // most tokens are capped there to keep the fixpoint finite.
+        let cap = token_length;
+        let terminal = |x: &SymbolRef| x.symbol_type() != SymbolType::NonTerminal;
+        let mut min_yield = vec![UNBOUNDED; count];
+        let mut max_yield = vec![0; count];
+        for symbol in symbols.iter().filter(|x| terminal(x)) {
+            min_yield[symbol.symbol_id()] = 1;
+            max_yield[symbol.symbol_id()] = 1.min(cap);
+        }
+        let sum = |y: &Vec<usize>, first: &SymbolRef, second: Option<SymbolRef>| {
+            let y1 = y[first.symbol_id()];
+            match second {
+                Some(x) => y1.saturating_add(y[x.symbol_id()]),
+                None => y1,
+            }
+        };
+        Self::fixpoint(|| {
+            let mut changed = false;
+            for rule in rules.iter() {
+                let left = rule.left().symbol_id();
+                let (min, max) = match rule.rule_type().length() {
+                    GrammarRuleLength::Zero => (0, 0),
+                    _ => {
+                        let right1 = rule.right1().unwrap();
+                        (
+                            sum(&min_yield, &right1, rule.right2()),
+                            sum(&max_yield, &right1, rule.right2()).min(cap),
+                        )
+                    }
+                };
+                changed |= Self::lower(&mut min_yield[left], min);
+                changed |= Self::raise(&mut max_yield[left], max);
+            }
+            changed
+        });
+
+        let mut result = Self {
+            token_length,
+            min_left: vec![UNBOUNDED; count],
+            max_left: vec![0; count],
+            min_right: vec![UNBOUNDED; count],
+            max_right: vec![0; count],
+        };
+        result.min_left[start_symbol.symbol_id()] = 0;
+        result.min_right[start_symbol.symbol_id()] = 0;
+        Self::fixpoint(|| {
+            let mut changed = false;
+            for rule in rules.iter() {
+                let left = rule.left().symbol_id();
+                let (min_left, max_left) = (result.min_left[left], result.max_left[left]);
+                let (min_right, max_right) = (result.min_right[left], result.max_right[left]);
+                let (right1, right2) = match rule.rule_type().length() {
+                    GrammarRuleLength::Zero => continue,
+                    GrammarRuleLength::One => (rule.right1().unwrap().symbol_id(), None),
+                    GrammarRuleLength::Two => (
+                        rule.right1().unwrap().symbol_id(),
+                        Some(rule.right2().unwrap().symbol_id()),
+                    ),
+                };
+                let (min_sibling, max_sibling) = match right2 {
+                    Some(right2) => (min_yield[right2], max_yield[right2]),
+                    None => (0, 0),
+                };
+                changed |= Self::lower(&mut result.min_left[right1], min_left);
+                changed |= Self::raise(&mut result.max_left[right1], max_left);
+                changed |= Self::lower(
+                    &mut result.min_right[right1],
+                    min_right.saturating_add(min_sibling),
+                );
+                changed |= Self::raise(
+                    &mut result.max_right[right1],
+                    (max_right + max_sibling).min(cap),
+                );
+                if let Some(right2) = right2 {
+                    let (min_sibling, max_sibling) = (min_yield[right1], max_yield[right1]);
+                    changed |= Self::lower(
+                        &mut result.min_left[right2],
+                        min_left.saturating_add(min_sibling),
+                    );
+                    changed |= Self::raise(
+                        &mut result.max_left[right2],
+                        (max_left + max_sibling).min(cap),
+                    );
+                    changed |= Self::lower(&mut result.min_right[right2], min_right);
+                    changed |= Self::raise(&mut result.max_right[right2], max_right);
+                }
+            }
+            changed
+        });
+        result
+    }
+
+    fn fixpoint(mut step: impl FnMut() -> bool) {
+        while step() {}
+    }
+
+    fn lower(value: &mut usize, new: usize) -> bool {
+        let changed = new < *value;
+        if changed {
+            *value = new;
+        }
+        changed
+    }
+
+    fn raise(value: &mut usize, new: usize) -> bool {
+        let changed = new > *value;
+        if changed {
+            *value = new;
+        }
+        changed
+    }
+
+    /// The fewest edits a fix needs outside of `begin..end` when `symbol`
+    /// derives that span.
+    pub fn outside(&self, symbol: SymbolRef<'_>, begin: usize, end: usize) -> usize {
+        let id = symbol.symbol_id();
+        let cost = |min: usize, max: usize, tokens: usize| {
+            min.saturating_sub(tokens).max(tokens.saturating_sub(max))
+        };
+        cost(self.min_left[id], self.max_left[id], begin).saturating_add(cost(
+            self.min_right[id],
+            self.max_right[id],
+            self.token_length - end,
+        ))
+    }
+}
@@ -2,14 +2,14 @@ use std::fmt::Display;
 
 use crate::{
     grammar::{GrammarRuleRef, SymbolRef},
-    props::{PropArray, UnionProp},
+    props::{PropArray, StoredProp, UnionProp},
     utils::Pointer,
 };
 
 use super::Edge;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub struct GKey<'a, PG>
+pub struct GKey<'a, 'b, PG>
 where
     PG: UnionProp,
 {
@@ -17,14 +17,14 @@ where
     end: usize,
     symbol: SymbolRef<'a>,
     length: usize,
-    prop: PropArray<PG>,
+    prop: StoredProp<'b, PG, PropArray<PG>>,
 }
 
-impl<'a, PG> Edge<'a> for GKey<'a, PG>
+impl<'a, 'b, PG> Edge<'a> for GKey<'a, 'b, PG>
 where
     PG: UnionProp,
 {
-    type OtherType = PropArray<PG>;
+    type OtherType = StoredProp<'b, PG, PropArray<PG>>;
 
     fn begin(&self) -> usize {
         self.begin
@@ -47,7 +47,7 @@ where
     }
 }
 
-impl<'a, PG> GKey<'a, PG>
+impl<'a, 'b, PG> GKey<'a, 'b, PG>
 where
     PG: UnionProp,
 {
@@ -56,7 +56,7 @@ where
         end: usize,
         symbol: SymbolRef<'a>,
         length: usize,
-        prop: PropArray<PG>,
+        prop: StoredProp<'b, PG, PropArray<PG>>,
     ) -> Self {
         Self {
             begin,
@@ -67,12 +67,12 @@ where
         }
     }
 
-    pub fn prop(&self) -> &PropArray<PG> {
-        &self.prop
+    pub fn prop(&self) -> &'b PropArray<PG> {
+        self.prop.get()
     }
 }
 
-impl<'a, PG> Display for GKey<'a, PG>
+impl<'a, PG> Display for GKey<'a, '_, PG>
 where
     PG: UnionProp,
 {
@@ -134,5 +134,5 @@ where
     }
 }
 
-pub type GKeyRef<'a, 'b, PG> = Pointer<'b, GKey<'a, PG>>;
+pub type GKeyRef<'a, 'b, PG> = Pointer<'b, GKey<'a, 'b, PG>>;
 pub type GRuleRef<'a, 'b, PG> = Pointer<'b, GRule<'a, 'b, PG>>;
@@ -16,6 +16,12 @@ pub trait GProcessor {
         symbol: SymbolRef<'a>,
         literal: Option<&str>,
     ) -> PropResult<Self::PG>;
+    /// Whether the rules of the nonterminal `symbol` are applied at all, so
+    /// that symbols no derivation of the input may use take no edges, not
+    /// even of the parts their rules are split into.
+    fn derives<'a>(&self, _symbol: SymbolRef<'a>) -> bool {
+        true
+    }
 }
 
 pub struct SyntacticProcessorEmpty;
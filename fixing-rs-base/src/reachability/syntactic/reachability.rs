@@ -1,19 +1,25 @@
-use super::{super::GReachabilityArena, Edge, EdgeMap, GKey, GKeyRef, GProcessor, GRule, GRuleRef};
+use super::{super::GReachabilityArena, Edge, EdgeMap, GBounds, GKey, GKeyRef, GProcessor, GRule};
 use crate::{
-    containers::{Map, Set},
-    grammar::{Grammar, GrammarRuleRef, GrammarRuleType, GrammarSymbolsRef, SymbolRef, SymbolType},
+    containers::{Map, OrderedMap, PointerSet},
+    grammar::{
+        FrozenGrammar, Grammar, GrammarRuleRef, GrammarRuleType, GrammarSymbolsRef, SymbolRef,
+        SymbolType,
+    },
     props::{IntoPropResult, PropArray, PropResult, UnionProp},
+    stats::EngineStats,
     tokenizer::Token,
-    utils::Queue,
+    utils::{MemoryBudget, Queue},
 };
-use std::fmt::Display;
+use std::{fmt::Display, time::Instant};
+
+mod parallel;
 
 struct SymbolQuickRef<'a, 'b, PG>
 where
     PG: UnionProp,
 {
-    ref_right: Map<(SymbolRef<'a>, usize, usize), Set<GKeyRef<'a, 'b, PG>>>,
-    ref_left: Map<(SymbolRef<'a>, usize, usize), Set<GKeyRef<'a, 'b, PG>>>,
+    ref_right: Map<(SymbolRef<'a>, usize, usize), Vec<GKeyRef<'a, 'b, PG>>>,
+    ref_left: Map<(SymbolRef<'a>, usize, usize), Vec<GKeyRef<'a, 'b, PG>>>,
 }
 
 impl<'a, 'b, PG> SymbolQuickRef<'a, 'b, PG>
@@ -22,13 +28,13 @@ where
 {
     fn new() -> Self {
         Self {
-            ref_right: Map::new(),
-            ref_left: Map::new(),
+            ref_right: Map::default(),
+            ref_left: Map::default(),
         }
     }
     fn get_ref<const RIGHT: bool>(
         &self,
-    ) -> &Map<(SymbolRef<'a>, usize, usize), Set<GKeyRef<'a, 'b, PG>>> {
+    ) -> &Map<(SymbolRef<'a>, usize, usize), Vec<GKeyRef<'a, 'b, PG>>> {
         if RIGHT {
             &self.ref_right
         } else {
@@ -37,7 +43,7 @@ where
     }
     fn get_ref_mut<const RIGHT: bool>(
         &mut self,
-    ) -> &mut Map<(SymbolRef<'a>, usize, usize), Set<GKeyRef<'a, 'b, PG>>> {
+    ) -> &mut Map<(SymbolRef<'a>, usize, usize), Vec<GKeyRef<'a, 'b, PG>>> {
         if RIGHT {
             &mut self.ref_right
         } else {
@@ -50,23 +56,35 @@ where
             if RIGHT { edge.begin() } else { edge.end() },
             edge.length(),
         );
-        let r = self.get_ref_mut::<RIGHT>();
-        if !r.contains_key(&key) {
-            r.insert(key, Set::new());
-        }
-        r.get_mut(&key).unwrap().insert(edge);
+        // Edges are added once, when built, so the lists hold no duplicates.
+        self.get_ref_mut::<RIGHT>()
+            .entry(key)
+            .or_default()
+            .push(edge);
     }
 }
 
+/// How a [`GReachability`] builds its edges; by default, all of them,
+/// without counting.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct GReachabilityOptions {
+    /// Drop edges that cannot be part of a fix within the maximum length,
+    /// using bounds on the tokens the grammar may yield.
+    pub prune: bool,
+    /// Count the rules applied and the lookups, for
+    /// [`GReachability::fill_stats`].
+    pub profile: bool,
+}
+
 pub struct GReachability<'a, 'b, 'p, PG, GProc>
 where
     PG: UnionProp,
     GProc: GProcessor<PG = PG>,
 {
     grammar_ref: GrammarSymbolsRef<'a>,
-    grammar: &'a Grammar<'a>,
+    grammar: FrozenGrammar<'a>,
     arena: &'b GReachabilityArena<'a, 'b, PG>,
-    edges: EdgeMap<'a, 'b, GKey<'a, PG>, Map<&'b GRule<'a, 'b, PG>, GRuleRef<'a, 'b, PG>>>,
+    edges: EdgeMap<'a, 'b, GKey<'a, 'b, PG>, PointerSet<'b, GRule<'a, 'b, PG>>>,
     literals: Vec<&'b str>,
     to_update: Queue<GKeyRef<'a, 'b, PG>>,
     max_length: usize,
@@ -75,6 +93,20 @@ where
     quick_ref: SymbolQuickRef<'a, 'b, PG>,
     start_edge: Vec<Vec<GKeyRef<'a, 'b, PG>>>,
     next_updated_length: usize,
+    /// When set, edges that cannot be part of a fix within `max_length` are
+    /// not added.
+    bounds: Option<GBounds>,
+    edge_count: usize,
+    /// Number of edges of each length.
+    length_counts: Vec<usize>,
+    /// Whether the rules applied and the lookups are counted.
+    profile: bool,
+    rules_applied: OrderedMap<GrammarRuleRef<'a>, usize>,
+    quick_ref_lookups: usize,
+    memory_budget: Option<MemoryBudget>,
+    memory_budget_exceeded: bool,
+    deadline: Option<Instant>,
+    deadline_exceeded: bool,
 }
 
 impl<'a, 'b, 'p, PG, GProc> GReachability<'a, 'b, 'p, PG, GProc>
@@ -88,9 +120,10 @@ where
         tokens: &Vec<Token<'a, '_>>,
         processor: &'p GProc,
         max_length: usize,
+        options: GReachabilityOptions,
     ) -> Self {
         let mut result = Self {
-            grammar,
+            grammar: FrozenGrammar::new(grammar),
             grammar_ref: grammar.get_symbol_ref(),
             arena,
             edges: EdgeMap::new(tokens.len(), max_length),
@@ -102,6 +135,16 @@ where
             quick_ref: SymbolQuickRef::new(),
             start_edge: Vec::new(),
             next_updated_length: 0,
+            bounds: options.prune.then(|| GBounds::new(grammar, tokens.len())),
+            edge_count: 0,
+            length_counts: vec![0; max_length + 1],
+            profile: options.profile,
+            rules_applied: OrderedMap::new(),
+            quick_ref_lookups: 0,
+            memory_budget: None,
+            memory_budget_exceeded: false,
+            deadline: None,
+            deadline_exceeded: false,
         };
         result.add_originals(tokens, processor);
         if max_length > 0 {
@@ -168,10 +211,19 @@ where
         symbol: SymbolRef<'a>,
         length: usize,
         prop: PropArray<PG>,
-    ) -> GKeyRef<'a, 'b, PG> {
+    ) -> Option<GKeyRef<'a, 'b, PG>> {
+        if self.stopped() {
+            return None;
+        }
+        if let Some(ref bounds) = self.bounds {
+            if length.saturating_add(bounds.outside(symbol, begin, end)) > self.max_length {
+                return None;
+            }
+        }
+        let prop = self.arena.props.store(prop);
         let edge_key = GKey::new(begin, end, symbol, length, prop);
         if let Some((key, _)) = self.edges.get(&edge_key) {
-            return key;
+            return Some(key);
         }
         if symbol.name() == "argumentList^0" {
             if let PropArray::Single(_) = edge_key.prop() {
@@ -184,7 +236,28 @@ where
         self.quick_ref.add::<true>(key);
         self.quick_ref.add::<false>(key);
         self.try_put_into_start_edge(key);
-        key
+        self.edge_count += 1;
+        self.length_counts[length] += 1;
+        self.check_limits();
+        Some(key)
+    }
+
+    fn check_limits(&mut self) {
+        if let Some(budget) = self.memory_budget {
+            if budget.exceeded(self.arena.bytes()) {
+                self.memory_budget_exceeded = true;
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() > deadline {
+                self.deadline_exceeded = true;
+            }
+        }
+    }
+
+    /// Whether updates stopped because a limit was exceeded.
+    fn stopped(&self) -> bool {
+        self.memory_budget_exceeded || self.deadline_exceeded
     }
 
     fn try_put_into_start_edge(&mut self, key: GKeyRef<'a, 'b, PG>) {
@@ -212,7 +285,7 @@ where
         let (_, generations) = self.edges.get_mut(&*edge).unwrap();
         let entity = GRule::new(sub1, sub2, rule);
         let entity = self.arena.grules.alloc(entity);
-        generations.insert(entity.ptr(), entity);
+        generations.insert(entity);
     }
 
     fn update0(&mut self) {
@@ -222,57 +295,236 @@ where
 
         for rule in zero_productions {
             let symbol = rule.left();
-            let prop = self.process_zero(*rule);
+            if !self.processor.derives(rule.induction()) {
+                continue;
+            }
+            let prop = self.expander().process_zero(*rule);
             for i in 0..=self.token_length {
                 prop.clone().consume(|p| {
-                    let edge = self.add_edge(i, i, symbol, 0, p);
-                    self.add_generation(edge, None, None, *rule);
+                    if self.profile {
+                        *self.rules_applied.entry(*rule).or_insert(0) += 1;
+                    }
+                    if let Some(edge) = self.add_edge(i, i, symbol, 0, p) {
+                        self.add_generation(edge, None, None, *rule);
+                    }
                 });
             }
         }
     }
     fn update1(&mut self, edge: GKeyRef<'a, 'b, PG>) {
-        let GrammarSymbolsRef { start_symbol, .. } = self.grammar_ref;
+        self.expander().one(edge, |g| self.apply(g));
+    }
+    fn update2<const RIGHT: bool>(
+        &mut self,
+        edge: GKeyRef<'a, 'b, PG>,
+        from_length: usize,
+        to_length: usize,
+    ) {
+        let expander = self.expander();
+        expander.lookups::<RIGHT>(edge, from_length, to_length, |rule, i, key| {
+            if self.stopped() {
+                return;
+            }
+            if self.profile {
+                self.quick_ref_lookups += 1;
+            }
+            if let Some(edges) = self.quick_ref.get_ref::<RIGHT>().get(&key) {
+                for other_edge in edges.clone() {
+                    expander.two::<RIGHT>(rule, i, edge, other_edge, |g| self.apply(g));
+                }
+            }
+        });
+    }
+    fn update_edge(
+        &mut self,
+        edge: GKeyRef<'a, 'b, PG>,
+        one: bool,
+        from_length: usize,
+        to_length: usize,
+    ) {
+        if one {
+            self.update1(edge);
+        }
+        self.update2::<true>(edge, from_length, to_length);
+        self.update2::<false>(edge, from_length, to_length);
+    }
+
+    fn apply(&mut self, generation: Generation<'a, 'b, PG>) {
+        let Generation {
+            begin,
+            end,
+            symbol,
+            length,
+            prop,
+            sub1,
+            sub2,
+            rule,
+        } = generation;
+        if self.profile {
+            *self.rules_applied.entry(rule).or_insert(0) += 1;
+        }
+        if let Some(edge) = self.add_edge(begin, end, symbol, length, prop) {
+            self.add_generation(edge, sub1, sub2, rule);
+        }
+    }
+
+    fn expander(&self) -> Expander<'a, 'p, GProc> {
+        Expander {
+            grammar: self.grammar,
+            processor: self.processor,
+            start_symbol: self.grammar_ref.start_symbol,
+            token_length: self.token_length,
+            max_length: self.max_length,
+        }
+    }
+
+    /// Runs the updates from `next_updated_length` to `max_length`, handing
+    /// the edges to `process` in batches, in the order they were added. The
+    /// flag tells whether the edges are new, rather than combined again with
+    /// the new lengths.
+    fn update_until_with(
+        &mut self,
+        max_length: usize,
+        mut process: impl FnMut(&mut Self, &[GKeyRef<'a, 'b, PG>], bool, usize, usize),
+    ) {
+        let max_length = std::cmp::min(max_length, self.max_length);
+        if max_length < self.next_updated_length {
+            return;
+        }
+        let from_length = self.next_updated_length;
+        let mut batch = Vec::new();
+        for current_length in 0..from_length {
+            let mut idx = self.to_update.index_from_begin(current_length);
+            loop {
+                batch.clear();
+                while batch.len() < BATCH_SIZE {
+                    match self.to_update.get_next(&mut idx, current_length) {
+                        Some(edge) => batch.push(edge),
+                        None => break,
+                    }
+                }
+                if batch.is_empty() {
+                    break;
+                }
+                process(self, &batch, false, from_length, max_length);
+                if self.stopped() {
+                    return;
+                }
+            }
+        }
+        for current_length in from_length..=max_length {
+            loop {
+                batch.clear();
+                while batch.len() < BATCH_SIZE {
+                    match self.to_update.queue_next(current_length) {
+                        Some(edge) => batch.push(edge),
+                        None => break,
+                    }
+                }
+                if batch.is_empty() {
+                    break;
+                }
+                process(self, &batch, true, from_length, max_length);
+                if self.stopped() {
+                    return;
+                }
+            }
+        }
+        self.next_updated_length = max_length + 1;
+    }
+}
+
+/// Maximum number of edges handed to one update step.
+const BATCH_SIZE: usize = 1 << 14;
+
+/// An edge to add, with how it is derived.
+struct Generation<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    begin: usize,
+    end: usize,
+    symbol: SymbolRef<'a>,
+    length: usize,
+    prop: PropArray<PG>,
+    sub1: Option<GKeyRef<'a, 'b, PG>>,
+    sub2: Option<GKeyRef<'a, 'b, PG>>,
+    rule: GrammarRuleRef<'a>,
+}
+
+/// Derives new edges from an edge, without reading the edges built so far.
+struct Expander<'a, 'p, GProc> {
+    grammar: FrozenGrammar<'a>,
+    processor: &'p GProc,
+    start_symbol: SymbolRef<'a>,
+    token_length: usize,
+    max_length: usize,
+}
+
+impl<'a, 'p, GProc> Clone for Expander<'a, 'p, GProc> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, 'p, GProc> Copy for Expander<'a, 'p, GProc> {}
+
+impl<'a, 'p, PG, GProc> Expander<'a, 'p, GProc>
+where
+    PG: UnionProp,
+    GProc: GProcessor<PG = PG>,
+{
+    fn one<'b>(&self, edge: GKeyRef<'a, 'b, PG>, mut f: impl FnMut(Generation<'a, 'b, PG>)) {
         let symbol = edge.symbol();
-        let ref_one = symbol.ref_one(&self.grammar);
-        for rule in ref_one.iter() {
-            if rule.left() == start_symbol {
+        let ref_one = symbol.ref_one(self.grammar.grammar());
+        for rule in ref_one.iter().filter(|x| self.processor.derives(x.induction())) {
+            let (begin, end, length) = if rule.left() == self.start_symbol {
                 let total_len = edge.length()
                     + edge.begin()
                     + (self.token_length.checked_sub(edge.end()).unwrap());
-                if total_len <= self.max_length {
-                    let prop = self.processor_one(*rule, &edge.prop());
-                    prop.consume(|p| {
-                        let genedge =
-                            self.add_edge(0, self.token_length, start_symbol, total_len, p);
-                        self.add_generation(genedge, Some(edge), None, *rule);
-                    });
+                if total_len > self.max_length {
+                    continue;
                 }
+                (0, self.token_length, total_len)
             } else {
-                let prop = self.processor_one(*rule, edge.prop());
-                prop.consume(|p| {
-                    let genedge =
-                        self.add_edge(edge.begin(), edge.end(), rule.left(), edge.length(), p);
-                    self.add_generation(genedge, Some(edge), None, *rule);
-                });
-            }
+                (edge.begin(), edge.end(), edge.length())
+            };
+            let prop = self.processor_one(*rule, edge.prop());
+            prop.consume(|p| {
+                f(Generation {
+                    begin,
+                    end,
+                    symbol: rule.left(),
+                    length,
+                    prop: p,
+                    sub1: Some(edge),
+                    sub2: None,
+                    rule: *rule,
+                })
+            });
         }
     }
-    fn update2<const RIGHT: bool>(
-        &mut self,
+
+    /// Calls `f` with each rule, gap and key of the edges `edge` combines
+    /// with, from its right when `RIGHT` is set, into edges of lengths from
+    /// `from_length` to `to_length`.
+    fn lookups<'b, const RIGHT: bool>(
+        &self,
         edge: GKeyRef<'a, 'b, PG>,
         from_length: usize,
         to_length: usize,
+        mut f: impl FnMut(GrammarRuleRef<'a>, usize, (SymbolRef<'a>, usize, usize)),
     ) {
         let symbol = edge.symbol();
         for rule in {
             if RIGHT {
-                symbol.ref_two_left(&self.grammar)
+                symbol.ref_two_left(self.grammar.grammar())
             } else {
-                symbol.ref_two_right(&self.grammar)
+                symbol.ref_two_right(self.grammar.grammar())
             }
         }
         .iter()
+        .filter(|x| self.processor.derives(x.induction()))
         {
             let loc_max = if RIGHT {
                 self.token_length.checked_sub(edge.end()).unwrap()
@@ -302,29 +554,37 @@ where
                 };
                 let length_to = to_length.checked_sub(edge.length() + i).unwrap();
                 for l in length_from..=length_to {
-                    let key = (other_symbol, cur_loc, l);
-                    if let Some(edges) = self.quick_ref.get_ref::<RIGHT>().get(&key) {
-                        for other_edge in edges.clone() {
-                            let right1 = if RIGHT { &edge } else { &other_edge };
-                            let right2 = if RIGHT { &other_edge } else { &edge };
-                            let prop = self.process_two(*rule, right1.prop(), right2.prop());
-                            prop.consume(|p| {
-                                let genedge = self.add_edge(
-                                    right1.begin(),
-                                    right2.end(),
-                                    rule.left(),
-                                    i + right1.length() + right2.length(),
-                                    p,
-                                );
-                                self.add_generation(genedge, Some(*right1), Some(*right2), *rule);
-                            });
-                        }
-                    }
+                    f(*rule, i, (other_symbol, cur_loc, l));
                 }
             }
         }
     }
 
+    /// Combines `edge` with `other_edge`, found by [`Self::lookups`].
+    fn two<'b, const RIGHT: bool>(
+        &self,
+        rule: GrammarRuleRef<'a>,
+        i: usize,
+        edge: GKeyRef<'a, 'b, PG>,
+        other_edge: GKeyRef<'a, 'b, PG>,
+        mut f: impl FnMut(Generation<'a, 'b, PG>),
+    ) {
+        let right1 = if RIGHT { edge } else { other_edge };
+        let right2 = if RIGHT { other_edge } else { edge };
+        let prop = self.process_two(rule, right1.prop(), right2.prop());
+        prop.consume(|p| {
+            f(Generation {
+                begin: right1.begin(),
+                end: right2.end(),
+                symbol: rule.left(),
+                length: i + right1.length() + right2.length(),
+                prop: p,
+                sub1: Some(right1),
+                sub2: Some(right2),
+                rule,
+            })
+        });
+    }
     fn process_zero(&self, rule: GrammarRuleRef<'a>) -> PropResult<PropArray<PG>> {
         match rule.rule_type() {
             GrammarRuleType::ConcatZero => PropArray::Multiple(vec![].into()).into_prop_result(),
@@ -375,36 +635,74 @@ where
         &self.start_edge
     }
 
-    pub fn get_sub_edges(
-        &self,
-        edge: GKeyRef<'a, 'b, PG>,
-    ) -> &Map<&'b GRule<'a, 'b, PG>, GRuleRef<'a, 'b, PG>> {
+    pub fn get_sub_edges(&self, edge: GKeyRef<'a, 'b, PG>) -> &PointerSet<'b, GRule<'a, 'b, PG>> {
         &self.edges.get(edge.ptr()).unwrap().1
     }
+    /// The edges of `symbol` of `length` beginning at `begin`.
+    pub fn edges_from(
+        &self,
+        symbol: SymbolRef<'a>,
+        begin: usize,
+        length: usize,
+    ) -> impl Iterator<Item = GKeyRef<'a, 'b, PG>> + '_ {
+        self.quick_ref
+            .ref_right
+            .get(&(symbol, begin, length))
+            .into_iter()
+            .flatten()
+            .copied()
+    }
     pub fn update_until(&mut self, max_length: usize) {
-        let max_length = std::cmp::min(max_length, self.max_length);
-        if max_length < self.next_updated_length {
-            return;
-        }
-        for current_length in 0..self.next_updated_length {
-            let mut idx = self.to_update.index_from_begin(current_length);
-            while let Some(edge) = self.to_update.get_next(&mut idx, current_length) {
-                self.update2::<true>(edge, self.next_updated_length, max_length);
-                self.update2::<false>(edge, self.next_updated_length, max_length);
-            }
-        }
-        for current_length in self.next_updated_length..=max_length {
-            while let Some(edge) = self.to_update.queue_next(current_length) {
-                self.update1(edge);
-                self.update2::<true>(edge, self.next_updated_length, max_length);
-                self.update2::<false>(edge, self.next_updated_length, max_length);
+        self.update_until_with(max_length, |this, edges, one, from_length, to_length| {
+            for edge in edges {
+                if this.stopped() {
+                    break;
+                }
+                this.update_edge(*edge, one, from_length, to_length);
             }
-        }
-        self.next_updated_length = max_length + 1;
+        });
     }
     pub fn literals(&self) -> &Vec<&'b str> {
         &self.literals
     }
+    /// Stops updates, after the edge going over it, once the task holds
+    /// more than `budget`. The edges are then incomplete and the
+    /// reachability should be dropped.
+    pub fn set_memory_budget(&mut self, budget: Option<MemoryBudget>) {
+        self.memory_budget = budget;
+    }
+    pub fn memory_budget_exceeded(&self) -> bool {
+        self.memory_budget_exceeded
+    }
+    /// Stops updates, after the edge going past it, once `deadline` has
+    /// passed. The edges are then incomplete and the reachability should be
+    /// dropped.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
+    }
+    pub fn deadline_exceeded(&self) -> bool {
+        self.deadline_exceeded
+    }
+    /// The number of edges built so far.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+    /// Bytes taken by the edges, rules and props built so far.
+    pub fn arena_bytes(&self) -> usize {
+        self.arena.bytes()
+    }
+    /// Records the counters of the edges built so far in `stats`; the rules
+    /// applied and the lookups are only counted when profiling.
+    pub fn fill_stats(&self, stats: &mut EngineStats) {
+        stats.syntactic_edges = self.length_counts.clone();
+        stats.profiled = self.profile;
+        stats.rules_applied = self
+            .rules_applied
+            .iter()
+            .map(|(rule, count)| (rule.ptr().to_string(), *count))
+            .collect();
+        stats.quick_ref_lookups = self.quick_ref_lookups;
+    }
 }
 
 impl<'a, 'b, 'p, PG, GProc> Display for GReachability<'a, 'b, 'p, PG, GProc>
@@ -426,7 +724,7 @@ where
         for edge in edges {
             let rules = self.edges.get(edge.ptr()).unwrap().1;
             writeln!(f, "Edge: {}", edge.ptr())?;
-            for rule_ref in rules.values() {
+            for rule_ref in rules.iter() {
                 writeln!(f, " {}", rule_ref.ptr())?;
             }
             writeln!(f)?;
@@ -0,0 +1,247 @@
+use super::{
+    super::{Edge, GKeyRef, GProcessor},
+    Expander, GReachability, Generation, SymbolQuickRef,
+};
+use crate::{grammar::SymbolRef, props::UnionProp, utils::HeapScope};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Batches smaller than this are not worth handing to threads.
+const MIN_PARALLEL_BATCH: usize = 256;
+
+/// Number of edges a worker takes at a time.
+const CHUNK_SIZE: usize = 64;
+
+/// What the workers read to expand the edges of a batch.
+struct WorkerInput<'s, 'a, 'b, 'p, PG, GProc>
+where
+    PG: UnionProp,
+{
+    expander: Expander<'a, 'p, GProc>,
+    quick_ref: &'s SymbolQuickRef<'a, 'b, PG>,
+    edges: &'s [GKeyRef<'a, 'b, PG>],
+}
+
+// SAFETY: Symbols, and thus edges, are not `Sync`, as their rules are filled
+// in through a `RefCellFrom` while the grammar is built. The expander reads
+// the rules through a `FrozenGrammar<'a>`, so nothing is written to them for
+// `'a`, and the edges and `quick_ref` are borrowed for `'s`, so nothing is
+// added to them while the workers run.
+unsafe impl<PG, GProc> Sync for WorkerInput<'_, '_, '_, '_, PG, GProc>
+where
+    PG: UnionProp + Sync,
+    GProc: Sync,
+{
+}
+
+/// The expansions of the chunks a worker took, by the index of their first
+/// edge, with the bytes the worker allocated for them.
+struct WorkerOutput<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    chunks: Vec<(usize, Vec<Expansion<'a, 'b, PG>>)>,
+    bytes: isize,
+}
+
+// SAFETY: Expansions refer to symbols and edges, which are not `Send` for the
+// reason given for `WorkerInput`. The grammar is frozen for `'a` and the
+// edges are only read, by either thread, while they live in the arena for
+// `'b`. The props they hold are owned.
+unsafe impl<PG> Send for WorkerOutput<'_, '_, PG> where PG: UnionProp + Send {}
+
+/// The edges derived from an edge against the edges built before its batch.
+struct Expansion<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    generations: Vec<Generation<'a, 'b, PG>>,
+    /// The keys looked up, with the number of edges found for each.
+    lookups: Vec<(bool, (SymbolRef<'a>, usize, usize), usize)>,
+    /// Whether one of the derived edges may have been found by a later
+    /// lookup of the same edge.
+    self_dependent: bool,
+}
+
+impl<'a, 'b, PG> Expansion<'a, 'b, PG>
+where
+    PG: UnionProp,
+{
+    fn new<GProc>(
+        expander: &Expander<'a, '_, GProc>,
+        quick_ref: &SymbolQuickRef<'a, 'b, PG>,
+        edge: GKeyRef<'a, 'b, PG>,
+        one: bool,
+        from_length: usize,
+        to_length: usize,
+    ) -> Self
+    where
+        GProc: GProcessor<PG = PG>,
+    {
+        let mut result = Self {
+            generations: Vec::new(),
+            lookups: Vec::new(),
+            self_dependent: false,
+        };
+        if one {
+            expander.one(edge, |g| result.generations.push(g));
+        }
+        result.add_two::<true, _>(expander, quick_ref, edge, from_length, to_length);
+        result.add_two::<false, _>(expander, quick_ref, edge, from_length, to_length);
+        result.self_dependent = result.generations.iter().any(|g| {
+            g.length + edge.length() <= to_length
+                && result
+                    .lookups
+                    .iter()
+                    .any(|(right, (symbol, loc, length), _)| {
+                        *symbol == g.symbol
+                            && *length == g.length
+                            && *loc == if *right { g.begin } else { g.end }
+                    })
+        });
+        result
+    }
+
+    fn add_two<const RIGHT: bool, GProc>(
+        &mut self,
+        expander: &Expander<'a, '_, GProc>,
+        quick_ref: &SymbolQuickRef<'a, 'b, PG>,
+        edge: GKeyRef<'a, 'b, PG>,
+        from_length: usize,
+        to_length: usize,
+    ) where
+        GProc: GProcessor<PG = PG>,
+    {
+        expander.lookups::<RIGHT>(edge, from_length, to_length, |rule, i, key| {
+            let edges = quick_ref.get_ref::<RIGHT>().get(&key);
+            self.lookups
+                .push((RIGHT, key, edges.map_or(0, |x| x.len())));
+            for other_edge in edges.into_iter().flatten() {
+                expander.two::<RIGHT>(rule, i, edge, *other_edge, |g| self.generations.push(g));
+            }
+        });
+    }
+
+    /// Whether the sequential update of the edge, run now, would derive the
+    /// same edges. Edges are only ever appended to the lists looked up, so
+    /// the lists are unchanged when their sizes are.
+    fn is_current(&self, quick_ref: &SymbolQuickRef<'a, 'b, PG>) -> bool {
+        !self.self_dependent
+            && self.lookups.iter().all(|(right, key, count)| {
+                let edges = match right {
+                    true => quick_ref.get_ref::<true>().get(key),
+                    false => quick_ref.get_ref::<false>().get(key),
+                };
+                edges.map_or(0, |x| x.len()) == *count
+            })
+    }
+}
+
+impl<'a, 'b, 'p, PG, GProc> GReachability<'a, 'b, 'p, PG, GProc>
+where
+    PG: UnionProp + Send + Sync,
+    GProc: GProcessor<PG = PG> + Sync,
+{
+    /// Same as [`Self::update_until`], with the edges of each length expanded
+    /// on `threads` threads. The edges derived are added in the order the
+    /// sequential update adds them; an edge whose expansion depends on edges
+    /// added earlier in its batch is expanded again, so the result is the
+    /// same, down to the order of the edges and of their derivations.
+    pub fn update_until_parallel(&mut self, max_length: usize, threads: usize) {
+        self.update_until_with(max_length, |this, edges, one, from_length, to_length| {
+            if threads <= 1 || edges.len() < MIN_PARALLEL_BATCH {
+                for edge in edges {
+                    if this.stopped() {
+                        break;
+                    }
+                    this.update_edge(*edge, one, from_length, to_length);
+                }
+                return;
+            }
+            let expansions = this.expand(edges, one, from_length, to_length, threads);
+            for (edge, expansion) in edges.iter().zip(expansions) {
+                if this.stopped() {
+                    break;
+                }
+                if expansion.is_current(&this.quick_ref) {
+                    if this.profile {
+                        this.quick_ref_lookups += expansion.lookups.len();
+                    }
+                    for generation in expansion.generations {
+                        this.apply(generation);
+                    }
+                } else {
+                    this.update_edge(*edge, one, from_length, to_length);
+                }
+            }
+        });
+    }
+
+    fn expand(
+        &self,
+        edges: &[GKeyRef<'a, 'b, PG>],
+        one: bool,
+        from_length: usize,
+        to_length: usize,
+        threads: usize,
+    ) -> Vec<Expansion<'a, 'b, PG>> {
+        let input = WorkerInput {
+            expander: self.expander(),
+            quick_ref: &self.quick_ref,
+            edges,
+        };
+        let next_chunk = AtomicUsize::new(0);
+        let mut chunks = thread::scope(|s| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    let input = &input;
+                    let next_chunk = &next_chunk;
+                    s.spawn(move || {
+                        let edges = input.edges;
+                        let heap = HeapScope::new();
+                        let mut result = Vec::new();
+                        loop {
+                            let begin = next_chunk.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                            if begin >= edges.len() {
+                                break;
+                            }
+                            let end = std::cmp::min(begin + CHUNK_SIZE, edges.len());
+                            let chunk: Vec<_> = edges[begin..end]
+                                .iter()
+                                .map(|edge| {
+                                    Expansion::new(
+                                        &input.expander,
+                                        input.quick_ref,
+                                        *edge,
+                                        one,
+                                        from_length,
+                                        to_length,
+                                    )
+                                })
+                                .collect();
+                            result.push((begin, chunk));
+                        }
+                        WorkerOutput {
+                            chunks: result,
+                            bytes: heap.bytes_moved(),
+                        }
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|x| {
+                    // The expansions are freed, or kept in the edges, by the
+                    // calling thread, which thus counts them.
+                    let output = x.join().unwrap();
+                    HeapScope::adopt(output.bytes);
+                    output.chunks
+                })
+                .collect::<Vec<_>>()
+        });
+        chunks.sort_by_key(|(begin, _)| *begin);
+        chunks.into_iter().flat_map(|(_, x)| x).collect()
+    }
+}
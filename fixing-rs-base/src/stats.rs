@@ -0,0 +1,138 @@
+use std::{
+    cell::Cell,
+    fmt::{self, Display},
+};
+
+/// Counters of the work done for one fix task, to tell where its time and
+/// memory went.
+#[derive(Debug, Clone, Default)]
+pub struct EngineStats {
+    /// Number of syntactic edges of each length.
+    pub syntactic_edges: Vec<usize>,
+    /// Whether the task was profiled. Only then are the rules applied, the
+    /// lookups, the cache lookups and the props produced counted, counting
+    /// them slowing down the search.
+    pub profiled: bool,
+    /// Number of edges each grammar rule derived, duplicates included, in
+    /// the order the rules were first applied.
+    pub rules_applied: Vec<(String, usize)>,
+    /// Number of lookups of edges to combine with.
+    pub quick_ref_lookups: usize,
+    /// Number of semantic keys, i.e. syntactic edges with an inherited prop.
+    pub semantic_keys: usize,
+    /// Number of semantic keys found in the cache of earlier lengths.
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+    /// Number of props returned by each method of the semantic processor.
+    pub props_produced: Vec<(&'static str, usize)>,
+    /// Largest number of bytes held by the arenas of the task at once, as
+    /// seen at the end of each search, not counting memory the values
+    /// allocated in them own.
+    pub peak_arena_bytes: usize,
+    /// Largest number of bytes the task held at once, as counted by
+    /// [`crate::utils::CountingAllocator`]; 0 when it is not installed or
+    /// not counting.
+    pub peak_heap_bytes: usize,
+}
+
+/// One counter per line, as its name and value separated by a tab.
+impl Display for EngineStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (length, count) in self.syntactic_edges.iter().enumerate() {
+            writeln!(f, "syntactic_edges[{}]\t{}", length, count)?;
+        }
+        for (rule, count) in self.rules_applied.iter() {
+            writeln!(f, "rules_applied[{}]\t{}", rule, count)?;
+        }
+        writeln!(f, "quick_ref_lookups\t{}", self.quick_ref_lookups)?;
+        writeln!(f, "semantic_keys\t{}", self.semantic_keys)?;
+        writeln!(f, "cache_hits\t{}", self.cache_hits)?;
+        writeln!(f, "cache_misses\t{}", self.cache_misses)?;
+        for (method, count) in self.props_produced.iter() {
+            writeln!(f, "props_produced[{}]\t{}", method, count)?;
+        }
+        writeln!(f, "peak_arena_bytes\t{}", self.peak_arena_bytes)?;
+        writeln!(f, "peak_heap_bytes\t{}", self.peak_heap_bytes)
+    }
+}
+
+/// Methods of [`crate::reachability::SProcessor`] whose props are counted.
+#[derive(Clone, Copy)]
+pub(crate) enum SMethod {
+    RootInh,
+    NonTerminalInh,
+    NonTerminalSyn,
+    SymbolicTerminalSyn,
+}
+
+impl SMethod {
+    const ALL: [SMethod; 4] = [
+        SMethod::RootInh,
+        SMethod::NonTerminalInh,
+        SMethod::NonTerminalSyn,
+        SMethod::SymbolicTerminalSyn,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SMethod::RootInh => "process_root_inh",
+            SMethod::NonTerminalInh => "process_non_terminal_inh",
+            SMethod::NonTerminalSyn => "process_non_terminal_syn",
+            SMethod::SymbolicTerminalSyn => "process_symbolic_terminal_syn",
+        }
+    }
+}
+
+/// Counters of semantic reachability, kept for the whole task.
+#[derive(Default)]
+pub(crate) struct SCounters {
+    /// Whether the cache lookups and the props are counted.
+    enabled: Cell<bool>,
+    cache_hits: Cell<usize>,
+    cache_misses: Cell<usize>,
+    props: [Cell<usize>; 4],
+    peak_arena_bytes: Cell<usize>,
+}
+
+impl SCounters {
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    pub(crate) fn cache_lookup(&self, hit: bool) {
+        if !self.enabled.get() {
+            return;
+        }
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.set(counter.get() + 1);
+    }
+
+    pub(crate) fn props(&self, method: SMethod, count: usize) {
+        if !self.enabled.get() {
+            return;
+        }
+        let counter = &self.props[method as usize];
+        counter.set(counter.get() + count);
+    }
+
+    /// Records the bytes held by the arenas of the task at the end of a
+    /// search, counting the arena of the search, which is then dropped.
+    pub(crate) fn arena_bytes(&self, bytes: usize) {
+        let peak = &self.peak_arena_bytes;
+        peak.set(peak.get().max(bytes));
+    }
+
+    pub(crate) fn fill(&self, stats: &mut EngineStats) {
+        stats.cache_hits = self.cache_hits.get();
+        stats.cache_misses = self.cache_misses.get();
+        stats.props_produced = SMethod::ALL
+            .iter()
+            .map(|method| (method.name(), self.props[*method as usize].get()))
+            .collect();
+        stats.peak_arena_bytes = stats.peak_arena_bytes.max(self.peak_arena_bytes.get());
+    }
+}
@@ -1,19 +1,25 @@
+mod alloc;
 mod arena;
+mod persistent;
 mod pointer;
 mod provide_arena;
 mod queue;
 mod refcell;
+mod rng;
 mod strpool;
 mod symtab;
 mod value_enum;
 
+pub use alloc::{CountingAllocator, HeapScope, MemoryBudget};
 pub use arena::RefArena;
+pub use persistent::PersistentMap;
 pub use pointer::Pointer;
 pub use provide_arena::ProvideArena;
 pub use queue::{Queue, QueueItem, QueueItemIndex};
 pub use refcell::RefCellFrom;
+pub use rng::Rng;
 pub use strpool::{StringPool, StringRef};
-pub use symtab::{SymTab, SymTabContent};
+pub use symtab::SymTab;
 pub use value_enum::ValueEnum;
 
 pub fn slice_check_len<T>(slice: &[T], len: usize, msg: &str) {
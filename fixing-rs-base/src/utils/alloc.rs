@@ -0,0 +1,147 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Whether [`CountingAllocator`] counts, which it does once a task needs it.
+static COUNTING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Bytes allocated by the thread and not freed, less the bytes it freed
+    /// that other threads allocated.
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    /// Largest value of `ALLOCATED` since the last [`HeapScope`] was made.
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn count(bytes: isize) {
+    if !COUNTING.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = ALLOCATED.try_with(|allocated| {
+        let current = allocated.get().wrapping_add(bytes);
+        allocated.set(current);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(current)));
+    });
+}
+
+/// The system allocator, counting the bytes each thread allocates so that
+/// the memory a fix task holds is known. Install it with
+/// `#[global_allocator]`; without it, [`HeapScope`] sees nothing allocated.
+///
+/// Counting starts with [`CountingAllocator::enable`], which fixing calls
+/// for tasks with a memory budget or profiled; until then, allocating costs
+/// one more load of a flag.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Counts allocations from now on, in every thread. Memory allocated
+    /// before is not counted, though freeing it is, as for memory allocated
+    /// before a [`HeapScope`] was made.
+    pub fn enable() {
+        COUNTING.store(true, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            count(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            count(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        count(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            count(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+fn allocated() -> isize {
+    ALLOCATED.try_with(|x| x.get()).unwrap_or(0)
+}
+
+/// The bytes the current thread allocated, and did not free, since the scope
+/// was made, as counted by [`CountingAllocator`].
+#[derive(Clone, Copy, Debug)]
+pub struct HeapScope {
+    start: isize,
+}
+
+impl HeapScope {
+    /// Starts counting, also restarting the peak of the thread.
+    pub fn new() -> Self {
+        let start = allocated();
+        let _ = PEAK.try_with(|x| x.set(start));
+        Self { start }
+    }
+
+    /// Bytes held now.
+    pub fn bytes(&self) -> usize {
+        allocated().saturating_sub(self.start).max(0) as usize
+    }
+
+    /// Largest number of bytes held at once.
+    pub fn peak_bytes(&self) -> usize {
+        let peak = PEAK.try_with(|x| x.get()).unwrap_or(0);
+        peak.saturating_sub(self.start).max(0) as usize
+    }
+
+    /// Counts as allocated by the current thread the `bytes` another thread
+    /// allocated for it, as given by [`Self::bytes_moved`] on that thread,
+    /// and which the current thread will free.
+    pub fn adopt(bytes: isize) {
+        count(bytes);
+    }
+
+    /// The bytes the current thread allocated, less those it freed, since
+    /// the scope was made, to be handed to [`Self::adopt`] by the thread
+    /// the values allocated are moved to.
+    pub fn bytes_moved(&self) -> isize {
+        allocated().wrapping_sub(self.start)
+    }
+}
+
+impl Default for HeapScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A limit on the bytes a fix task holds, counted in `heap`.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryBudget {
+    limit: usize,
+    heap: HeapScope,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: usize, heap: HeapScope) -> Self {
+        Self { limit, heap }
+    }
+
+    /// Whether the task held more than the limit at some point, counting
+    /// what the current thread allocated with [`CountingAllocator`] or,
+    /// when it is not installed, the `arena_bytes` of the task.
+    pub fn exceeded(&self, arena_bytes: usize) -> bool {
+        self.heap.peak_bytes().max(arena_bytes) > self.limit
+    }
+}
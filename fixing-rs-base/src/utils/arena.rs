@@ -1,25 +1,45 @@
 use super::Pointer;
+use std::{cell::Cell, mem};
 use typed_arena::Arena;
 
 pub struct RefArena<T> {
     arena: Arena<T>,
+    len: Cell<usize>,
 }
 
 impl<T> RefArena<T> {
     pub fn new() -> Self {
         Self {
             arena: Arena::new(),
+            len: Cell::new(0),
         }
     }
 
     pub fn alloc<'a>(&'a self, value: T) -> Pointer<'a, T> {
+        self.len.set(self.len.get() + 1);
         Pointer::new(self.arena.alloc(value))
     }
 
+    /// Number of values allocated.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes taken by the values allocated, not counting memory they own.
+    pub fn bytes(&self) -> usize {
+        self.len() * mem::size_of::<T>()
+    }
+
     pub fn alloc_extend<I>(&self, iterable: I) -> &[T]
     where
         I: IntoIterator<Item = T>,
     {
-        self.arena.alloc_extend(iterable)
+        let values = self.arena.alloc_extend(iterable);
+        self.len.set(self.len.get() + values.len());
+        values
     }
 }
@@ -0,0 +1,317 @@
+use super::rng::split_mix;
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+/// Maps with up to this many keys find a key by going through their
+/// entries, which for so few keys costs less than keeping an index.
+const UNINDEXED_LEN: usize = 16;
+
+/// A treap on `u64` keys whose nodes are shared between versions: inserting
+/// copies the nodes on the path to the key only. Priorities are derived from
+/// the keys, so the shape of the tree does not depend on the order of
+/// insertions.
+struct Treap<T> {
+    root: Option<Rc<TreapNode<T>>>,
+}
+
+struct TreapNode<T> {
+    key: u64,
+    item: T,
+    left: Option<Rc<TreapNode<T>>>,
+    right: Option<Rc<TreapNode<T>>>,
+}
+
+impl<T> TreapNode<T> {
+    fn priority(&self) -> u64 {
+        split_mix(self.key)
+    }
+}
+
+impl<T> Clone for Treap<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T> Treap<T>
+where
+    T: Clone,
+{
+    fn new() -> Self {
+        Self { root: None }
+    }
+
+    fn get(&self, key: u64) -> Option<&T> {
+        let mut node = self.root.as_ref();
+        while let Some(n) = node {
+            node = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_ref(),
+                Ordering::Greater => n.right.as_ref(),
+                Ordering::Equal => return Some(&n.item),
+            };
+        }
+        None
+    }
+
+    fn insert(&self, key: u64, item: T) -> Self {
+        Self {
+            root: Some(Self::insert_at(self.root.as_ref(), key, item)),
+        }
+    }
+
+    fn insert_at(node: Option<&Rc<TreapNode<T>>>, key: u64, item: T) -> Rc<TreapNode<T>> {
+        let node = match node {
+            Some(node) => node,
+            None => {
+                return Rc::new(TreapNode {
+                    key,
+                    item,
+                    left: None,
+                    right: None,
+                })
+            }
+        };
+        match key.cmp(&node.key) {
+            Ordering::Equal => Rc::new(TreapNode {
+                key,
+                item,
+                left: node.left.clone(),
+                right: node.right.clone(),
+            }),
+            Ordering::Less => {
+                let left = Self::insert_at(node.left.as_ref(), key, item);
+                if left.priority() > node.priority() {
+                    // Rotate the new left child up.
+                    Rc::new(TreapNode {
+                        key: left.key,
+                        item: left.item.clone(),
+                        left: left.left.clone(),
+                        right: Some(Rc::new(TreapNode {
+                            key: node.key,
+                            item: node.item.clone(),
+                            left: left.right.clone(),
+                            right: node.right.clone(),
+                        })),
+                    })
+                } else {
+                    Rc::new(TreapNode {
+                        key: node.key,
+                        item: node.item.clone(),
+                        left: Some(left),
+                        right: node.right.clone(),
+                    })
+                }
+            }
+            Ordering::Greater => {
+                let right = Self::insert_at(node.right.as_ref(), key, item);
+                if right.priority() > node.priority() {
+                    // Rotate the new right child up.
+                    Rc::new(TreapNode {
+                        key: right.key,
+                        item: right.item.clone(),
+                        left: Some(Rc::new(TreapNode {
+                            key: node.key,
+                            item: node.item.clone(),
+                            left: node.left.clone(),
+                            right: right.left.clone(),
+                        })),
+                        right: right.right.clone(),
+                    })
+                } else {
+                    Rc::new(TreapNode {
+                        key: node.key,
+                        item: node.item.clone(),
+                        left: node.left.clone(),
+                        right: Some(right),
+                    })
+                }
+            }
+        }
+    }
+
+    /// The first node, in the order of the keys, whose item satisfies `f`.
+    fn find(&self, f: impl Fn(&T) -> bool) -> Option<&TreapNode<T>> {
+        fn find_at<'t, T>(
+            node: Option<&'t Rc<TreapNode<T>>>,
+            f: &impl Fn(&T) -> bool,
+        ) -> Option<&'t TreapNode<T>> {
+            let node = node?;
+            find_at(node.left.as_ref(), f)
+                .or_else(|| f(&node.item).then_some(&**node))
+                .or_else(|| find_at(node.right.as_ref(), f))
+        }
+        find_at(self.root.as_ref(), &f)
+    }
+
+    /// The items in the order of their keys.
+    fn iter(&self) -> TreapIter<'_, T> {
+        let mut result = TreapIter { stack: Vec::new() };
+        result.push_left(self.root.as_ref());
+        result
+    }
+
+    fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root, &other.root) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+struct TreapIter<'t, T> {
+    stack: Vec<&'t TreapNode<T>>,
+}
+
+impl<'t, T> TreapIter<'t, T> {
+    fn push_left(&mut self, mut node: Option<&'t Rc<TreapNode<T>>>) {
+        while let Some(n) = node {
+            self.stack.push(n);
+            node = n.left.as_ref();
+        }
+    }
+}
+
+impl<'t, T> Iterator for TreapIter<'t, T> {
+    type Item = &'t T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_ref());
+        Some(&node.item)
+    }
+}
+
+fn hash_of<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A map whose versions share their structure, so that a version with one
+/// more key takes O(log n) time and memory, while the older version remains
+/// valid. Like [`crate::containers::OrderedMap`], it iterates in the order keys
+/// were first inserted, independently of the addresses of the keys.
+pub struct PersistentMap<K, V> {
+    /// The entries by the position of their key in the order of insertion.
+    entries: Treap<(K, V)>,
+    /// The position of a key of each hash, kept once the map has more than
+    /// [`UNINDEXED_LEN`] keys. Of keys with the same hash, only the first
+    /// is indexed, and the others are found by going through the entries.
+    index: Option<Treap<usize>>,
+    len: usize,
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            index: self.index.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> PersistentMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            entries: Treap::new(),
+            index: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn position(&self, key: &K) -> Option<usize> {
+        if let Some(ref index) = self.index {
+            let position = *index.get(hash_of(key))?;
+            match self.entries.get(position as u64) {
+                Some((k, _)) if k == key => return Some(position),
+                _ => {}
+            }
+        }
+        self.entries
+            .find(|(k, _)| k == key)
+            .map(|node| node.key as usize)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let position = self.position(key)?;
+        self.entries.get(position as u64).map(|(_, v)| v)
+    }
+
+    /// The map with `key` set to `value`; a key already present keeps its
+    /// position.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        if let Some(position) = self.position(&key) {
+            return Self {
+                entries: self.entries.insert(position as u64, (key, value)),
+                index: self.index.clone(),
+                len: self.len,
+            };
+        }
+        let index = match self.index {
+            Some(ref index) => Some(Self::index_key(index, &key, self.len)),
+            None if self.len == UNINDEXED_LEN => Some(
+                self.entries
+                    .iter()
+                    .map(|(k, _)| k)
+                    .chain(std::iter::once(&key))
+                    .enumerate()
+                    .fold(Treap::new(), |index, (position, k)| {
+                        Self::index_key(&index, k, position)
+                    }),
+            ),
+            None => None,
+        };
+        Self {
+            entries: self.entries.insert(self.len as u64, (key, value)),
+            index,
+            len: self.len + 1,
+        }
+    }
+
+    fn index_key(index: &Treap<usize>, key: &K, position: usize) -> Treap<usize> {
+        let hash = hash_of(key);
+        match index.get(hash) {
+            Some(_) => index.clone(),
+            None => index.insert(hash, position),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    /// Whether both maps are the same version, which implies they are equal.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.entries.ptr_eq(&other.entries)
+    }
+}
+
+impl<K, V> Default for PersistentMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
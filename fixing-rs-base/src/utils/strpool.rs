@@ -24,6 +24,11 @@ impl<'a> StringPool<'a> {
         }
     }
 
+    /// Like `get`, but for strings that may not have been added.
+    pub fn find(&self, s: &str) -> Option<StringRef<'a>> {
+        self.pool.get(s).copied()
+    }
+
     pub fn get(&self, s: &str) -> Option<StringRef<'a>> {
         match self.pool.get(s) {
             Some(r) => Some(*r),
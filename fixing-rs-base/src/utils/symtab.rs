@@ -1,4 +1,5 @@
-use crate::{containers::Map, props::Prop};
+use super::PersistentMap;
+use crate::props::Prop;
 use std::{
     collections::hash_map::DefaultHasher,
     fmt::Debug,
@@ -6,16 +7,37 @@ use std::{
     rc::Rc,
 };
 
-#[derive(Debug)]
-pub struct SymTabContent<K, V>
+/// A variable, with the scope it was declared in.
+#[derive(Clone)]
+struct SymTabEntry<V> {
+    value: V,
+    /// `None` for variables of an enclosing scope.
+    scope: Option<usize>,
+}
+
+#[derive(Clone)]
+struct SymTabContent<K, V>
 where
     K: Hash + Ord + Clone + Debug,
     V: Hash + Clone + Eq + Debug,
 {
-    decled_vars: Map<K, V>,
-    hash: u64,
+    decled_vars: PersistentMap<K, SymTabEntry<V>>,
+    scope: usize,
+    /// Sum of the hashes of the variables of enclosing scopes.
+    outer_hash: u64,
+    /// Sum of the hashes of the variables of the current scope.
+    current_hash: u64,
+    /// Sum of the hashes the variables of the current scope will have once
+    /// a nested scope is entered.
+    current_outer_hash: u64,
 }
 
+/// Names declared so far, in the order they were first declared, grouped
+/// into nested scopes.
+///
+/// Versions share their structure, so declaring a name or entering a scope
+/// does not copy the table. The hash is updated with each declaration. The
+/// table is a single pointer, as props hold many of them.
 #[derive(Clone)]
 pub struct SymTab<K, V>
 where
@@ -33,7 +55,12 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "SymTab {{")?;
         for (k, v) in self.iter() {
-            write!(f, "{:?}: {:?}, ", k, v)?;
+            let scope = if self.is_current(k.clone()) {
+                ""
+            } else {
+                " outer"
+            };
+            write!(f, "{:?}: {:?}{}, ", k, v, scope)?;
         }
         write!(f, "}}")?;
 
@@ -41,35 +68,77 @@ where
     }
 }
 
-impl<K, V> SymTab<K, V>
+impl<K, V> SymTabContent<K, V>
 where
     K: Hash + Ord + Clone + Debug,
     V: Hash + Clone + Eq + Debug,
 {
-    pub fn new() -> Self {
-        Self::from_map(Map::new())
+    fn entry_hash(name: &K, value: &V, current: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        value.hash(&mut hasher);
+        current.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_value(&self) -> u64 {
+        self.outer_hash.wrapping_add(self.current_hash)
+    }
+
+    fn add_hash(&mut self, name: &K, value: &V, scope: Option<usize>) {
+        let outer = Self::entry_hash(name, value, false);
+        if scope == Some(self.scope) {
+            let current = Self::entry_hash(name, value, true);
+            self.current_hash = self.current_hash.wrapping_add(current);
+            self.current_outer_hash = self.current_outer_hash.wrapping_add(outer);
+        } else {
+            self.outer_hash = self.outer_hash.wrapping_add(outer);
+        }
     }
 
-    pub fn from_map(map: Map<K, V>) -> Self {
-        let hash = Self::compute_hash(&map);
+    fn remove_hash(&mut self, name: &K, value: &V, scope: Option<usize>) {
+        let outer = Self::entry_hash(name, value, false);
+        if scope == Some(self.scope) {
+            let current = Self::entry_hash(name, value, true);
+            self.current_hash = self.current_hash.wrapping_sub(current);
+            self.current_outer_hash = self.current_outer_hash.wrapping_sub(outer);
+        } else {
+            self.outer_hash = self.outer_hash.wrapping_sub(outer);
+        }
+    }
+}
+
+impl<K, V> SymTab<K, V>
+where
+    K: Hash + Ord + Clone + Debug,
+    V: Hash + Clone + Eq + Debug,
+{
+    pub fn new() -> Self {
         Self {
             content: Rc::new(SymTabContent {
-                decled_vars: map,
-                hash,
+                decled_vars: PersistentMap::new(),
+                scope: 0,
+                outer_hash: 0,
+                current_hash: 0,
+                current_outer_hash: 0,
             }),
         }
     }
 
-    fn compute_hash(m: &Map<K, V>) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        let mut keys = m.keys().collect::<Vec<_>>();
-        keys.sort();
-        for key in keys {
-            let val = m.get(key).unwrap();
-            key.hash(&mut hasher);
-            val.hash(&mut hasher);
+    /// `self` with `name` set to `value` in `scope`.
+    fn with(&self, name: K, value: V, scope: Option<usize>) -> Self {
+        let mut result = (*self.content).clone();
+        if let Some(old) = self.content.decled_vars.get(&name) {
+            result.remove_hash(&name, &old.value, old.scope);
+        }
+        result.add_hash(&name, &value, scope);
+        result.decled_vars = self
+            .content
+            .decled_vars
+            .insert(name, SymTabEntry { value, scope });
+        Self {
+            content: Rc::new(result),
         }
-        hasher.finish()
     }
 
     pub fn extend_checked(
@@ -78,33 +147,80 @@ where
         ty: V,
         check: impl FnOnce(Option<&V>) -> bool,
     ) -> Option<Self> {
-        let current = self.content.decled_vars.get(&name);
+        let current = self.content.decled_vars.get(&name).map(|x| &x.value);
         match check(current) {
             true => Some(self.extend(name, ty)),
             false => None,
         }
     }
 
+    /// Declares `name` in the current scope.
     pub fn extend(&self, name: K, ty: V) -> Self {
-        let mut new_map = self.content.decled_vars.clone();
-        new_map.insert(name, ty);
-        Self::from_map(new_map)
+        self.with(name, ty, Some(self.content.scope))
+    }
+
+    /// Declares `name` as if in an enclosing scope, so that the current
+    /// scope may still declare it.
+    pub fn extend_outer(&self, name: K, ty: V) -> Self {
+        self.with(name, ty, None)
     }
 
     pub fn extend_multiple(&self, name: impl Iterator<Item = K>, ty: V) -> Self {
-        let mut new_map = self.content.decled_vars.clone();
-        for k in name {
-            new_map.insert(k, ty.clone());
+        name.fold(self.clone(), |symtab, k| symtab.extend(k, ty.clone()))
+    }
+
+    /// Changes the value of `name`, keeping the scope it was declared in.
+    pub fn update(&self, name: K, ty: V) -> Self {
+        let scope = self.content.decled_vars.get(&name).and_then(|x| x.scope);
+        self.with(name, ty, scope)
+    }
+
+    /// Enters a nested scope, where the variables declared so far are
+    /// those of enclosing scopes.
+    pub fn new_scope(&self) -> Self {
+        let content = &self.content;
+        Self {
+            content: Rc::new(SymTabContent {
+                decled_vars: content.decled_vars.clone(),
+                scope: content.scope + 1,
+                outer_hash: content.outer_hash.wrapping_add(content.current_outer_hash),
+                current_hash: 0,
+                current_outer_hash: 0,
+            }),
         }
-        Self::from_map(new_map)
     }
 
     pub fn get(&self, name: K) -> Option<V> {
-        self.content.decled_vars.get(&name).cloned()
+        self.content.decled_vars.get(&name).map(|x| x.value.clone())
+    }
+
+    /// Whether `name` is declared in the current scope.
+    pub fn is_current(&self, name: K) -> bool {
+        self.content
+            .decled_vars
+            .get(&name)
+            .is_some_and(|x| x.scope == Some(self.content.scope))
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
-        self.content.decled_vars.iter()
+        self.content.decled_vars.iter().map(|(k, v)| (k, &v.value))
+    }
+
+    /// The variables declared in the current scope.
+    pub fn iter_current(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.content
+            .decled_vars
+            .iter()
+            .filter(|(_, v)| v.scope == Some(self.content.scope))
+            .map(|(k, v)| (k, &v.value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.content.decled_vars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.decled_vars.is_empty()
     }
 }
 
@@ -118,20 +234,36 @@ where
     }
 }
 
+impl<K, V> FromIterator<(K, V)> for SymTab<K, V>
+where
+    K: Hash + Ord + Clone + Debug,
+    V: Hash + Clone + Eq + Debug,
+{
+    /// Declares the names in the current scope, in order.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |symtab, (k, v)| symtab.extend(k, v))
+    }
+}
+
 impl<K, V> PartialEq for SymTab<K, V>
 where
     K: Hash + Ord + Clone + Debug,
     V: Hash + Clone + Eq + Debug,
 {
     fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.content, &other.content)
-            || (self.content.hash == other.content.hash
-                && self.content.decled_vars.len() == other.content.decled_vars.len()
-                && self
-                    .content
-                    .decled_vars
-                    .iter()
-                    .all(|(k, v)| other.content.decled_vars.get(k).map_or(false, |v2| v == v2)))
+        let (this, other) = (&self.content, &other.content);
+        if this.decled_vars.ptr_eq(&other.decled_vars) && this.scope == other.scope {
+            return true;
+        }
+        this.hash_value() == other.hash_value()
+            && this.decled_vars.len() == other.decled_vars.len()
+            && this.decled_vars.iter().all(|(k, v)| {
+                other.decled_vars.get(k).is_some_and(|v2| {
+                    v.value == v2.value
+                        && (v.scope == Some(this.scope)) == (v2.scope == Some(other.scope))
+                })
+            })
     }
 }
 
@@ -148,7 +280,7 @@ where
     V: Hash + Clone + Eq + Debug,
 {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.content.hash.hash(state);
+        self.content.hash_value().hash(state);
     }
 }
 
@@ -10,6 +10,8 @@ use quote::quote;
 use std::collections::{HashMap, HashSet};
 use syn::{spanned::Spanned, ImplItem, Result, Type};
 
+const DERIVES: &str = "derives";
+
 pub fn impl_syntactic_processor_inner(
     args: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
@@ -30,6 +32,7 @@ pub fn impl_syntactic_processor_inner(
     let mut m_symbolic_terminal = HashMap::new();
 
     let mut func_names = HashSet::new();
+    let mut has_derives = false;
 
     let str_type = syn::parse_str::<Type>("Option<&str>").unwrap();
 
@@ -39,7 +42,9 @@ pub fn impl_syntactic_processor_inner(
                 let sig = &method.sig;
                 let name = &sig.ident;
                 func_names.insert(name.to_string());
-                if let true = try_process::<1, _>(
+                if name == DERIVES {
+                    has_derives = true;
+                } else if let true = try_process::<1, _>(
                     method,
                     NON_TERMINAL_PREFIX,
                     non_terminals,
@@ -161,7 +166,22 @@ pub fn impl_syntactic_processor_inner(
         }
     }
 
+    // A `derives` method of the processor overrides the default of the
+    // trait, which builds the edges of every symbol.
+    let derives_quote = if has_derives {
+        let self_token = &impl_body_gen.self_token;
+        quote! {
+            fn derives(&self, symbol: ::fixing_rs_base::grammar::SymbolRef<'_>) -> bool {
+                <#self_token>::derives(self, symbol)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     Ok(impl_body_gen.generate(quote! (
+        #derives_quote
+
         type PG = #prop_type;
         fn process_non_terminal(
             &self,
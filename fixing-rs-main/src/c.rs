@@ -2,7 +2,11 @@ pub mod cenv;
 pub mod cenv_symbolic;
 pub mod csymtab;
 pub mod fixing;
+pub mod flow;
+pub mod macros;
 pub mod semantic;
+pub mod stdlib;
+pub mod strictness;
 pub mod syntactic;
 pub mod tokenizer;
 pub mod types;
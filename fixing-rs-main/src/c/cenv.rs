@@ -1,16 +1,17 @@
-use self::ast::{CEnvAstItem, CEnvAstParam, CEnvAstType, CEnvAstTypeBase, CEnvAstTypeExtra};
+use self::ast::{CEnvAstItem, CEnvAstParam, CEnvAstType, CEnvAstTypeExtra};
 use super::{
     csymtab::CDeclaredFunc,
-    types::{CFuncContentRef, CTypePool, CTypeRef},
+    types::{CFuncContentRef, CTypeComposed, CTypePool, CTypeRef, CTypeToken},
 };
 use fixing_rs_base::{
+    containers::{Map, OrderedMap, OrderedSet},
     grammar::OwnedToken,
+    naming::new_id_placeholder,
     utils::{RefArena, StringPool, StringRef},
 };
 use getset::{CopyGetters, Getters};
 use lalrpop_util::lexer::Token;
 use std::{
-    collections::{HashMap, HashSet},
     error::Error,
     fmt::{Debug, Display},
 };
@@ -32,23 +33,30 @@ pub struct CEnv<'a> {
     #[get = "pub"]
     identifiers: Vec<StringRef<'a>>,
     #[get = "pub"]
-    int_lits: HashMap<isize, StringRef<'a>>,
+    int_lits: Map<isize, StringRef<'a>>,
     #[get_copy = "pub"]
     types: &'a CTypePool<'a>,
     #[get_copy = "pub"]
     break_label: StringRef<'a>,
     #[get_copy = "pub"]
     continue_label: StringRef<'a>,
+    /// Holds the return type of the enclosing function in symbol tables.
+    #[get_copy = "pub"]
+    return_label: StringRef<'a>,
     #[get = "pub"]
-    functions: HashMap<StringRef<'a>, CDeclaredFunc<'a>>,
+    functions: OrderedMap<StringRef<'a>, CDeclaredFunc<'a>>,
     #[get = "pub"]
-    default_functions: HashMap<StringRef<'a>, CDeclaredFunc<'a>>,
+    default_functions: OrderedMap<StringRef<'a>, CDeclaredFunc<'a>>,
+    /// The function whose body is fixed, or `None` when the input is a
+    /// whole translation unit.
+    #[get_copy = "pub"]
+    current_func: Option<CFuncContentRef<'a>>,
     #[get_copy = "pub"]
-    current_func: CFuncContentRef<'a>,
+    current_func_name: Option<StringRef<'a>>,
     #[get = "pub"]
     str_pool: StringPool<'a>,
     #[get = "pub"]
-    globals: HashMap<StringRef<'a>, CTypeRef<'a>>,
+    globals: OrderedMap<StringRef<'a>, CTypeRef<'a>>,
     #[get = "pub"]
     params: Vec<(StringRef<'a>, CTypeRef<'a>)>,
 }
@@ -57,6 +65,7 @@ pub struct CEnv<'a> {
 pub enum CEnvBuildError {
     SyntaxError(lalrpop_util::ParseError<usize, OwnedToken, &'static str>),
     NoFunctions,
+    FailedToComposeType(Vec<CTypeToken>),
     FailedToDeriveConst,
     FailedToDerivePointer,
     FailedToDeriveArray,
@@ -79,12 +88,13 @@ impl<'input> From<lalrpop_util::ParseError<usize, Token<'input>, &'static str>>
 
 impl<'a> CEnv<'a> {
     fn gen_ty(ty: &CEnvAstType, types: &'a CTypePool<'a>) -> Result<CTypeRef<'a>, CEnvBuildError> {
-        // TODO fix type base
-        let mut result = match ty.base {
-            CEnvAstTypeBase::Void => types.type_void(),
-            CEnvAstTypeBase::Int => types.type_i32(),
-            CEnvAstTypeBase::Float => types.type_f64(),
-        };
+        let mut composed = CTypeComposed::new();
+        for token in ty.base.iter() {
+            composed = composed
+                .append_token(*token)
+                .ok_or_else(|| CEnvBuildError::FailedToComposeType(ty.base.clone()))?;
+        }
+        let mut result = types.construct_from_composed(&composed);
         for extra in ty.extra.iter() {
             match extra {
                 CEnvAstTypeExtra::Const => {
@@ -97,22 +107,45 @@ impl<'a> CEnv<'a> {
                         .derive_pointer(result)
                         .ok_or(CEnvBuildError::FailedToDerivePointer)?;
                 }
-                CEnvAstTypeExtra::Array => {
+                // Arrays are complete whether or not their size is given, as
+                // the element count is not part of the type.
+                CEnvAstTypeExtra::Array(_) => {
                     result = types
                         .derive_array(result)
                         .ok_or(CEnvBuildError::FailedToDeriveArray)?;
                 }
+                CEnvAstTypeExtra::Func(args) => {
+                    result = Self::gen_func(result, args, types)?;
+                }
             }
         }
         Ok(result)
     }
 
+    fn gen_func(
+        ret: CTypeRef<'a>,
+        args: &[CEnvAstParam],
+        types: &'a CTypePool<'a>,
+    ) -> Result<CTypeRef<'a>, CEnvBuildError> {
+        let mut args_types = Vec::new();
+        let mut va_args = false;
+        for arg in args.iter() {
+            match arg {
+                CEnvAstParam::VaArgs => va_args = true,
+                CEnvAstParam::WithName(_, arg_ty) | CEnvAstParam::WithoutName(arg_ty) => {
+                    args_types.push(Self::gen_ty(arg_ty, types)?);
+                }
+            }
+        }
+        Ok(types.derive_func(ret, args_types, va_args))
+    }
+
     fn gen_globals(
-        vars: &HashMap<StringRef<'a>, CTypeRef<'a>>,
-        functions: &HashMap<StringRef<'a>, CDeclaredFunc<'a>>,
+        vars: &OrderedMap<StringRef<'a>, CTypeRef<'a>>,
+        functions: &OrderedMap<StringRef<'a>, CDeclaredFunc<'a>>,
         types: &'a CTypePool<'a>,
-    ) -> HashMap<StringRef<'a>, CTypeRef<'a>> {
-        let mut result = HashMap::new();
+    ) -> OrderedMap<StringRef<'a>, CTypeRef<'a>> {
+        let mut result = OrderedMap::new();
         for (name, ty) in vars.iter() {
             result.insert(*name, *ty);
         }
@@ -181,7 +214,7 @@ impl<'a> CEnv<'a> {
     }
 
     pub fn is_true_id(&self, name: StringRef<'a>) -> bool {
-        name != self.break_label && name != self.continue_label
+        name != self.break_label && name != self.continue_label && name != self.return_label
     }
 
     pub fn build(
@@ -190,17 +223,20 @@ impl<'a> CEnv<'a> {
         types: &'a CTypePool<'a>,
         tokens: &Vec<fixing_rs_base::tokenizer::Token<'_, '_>>,
         max_new_id: usize,
+        stdlib: Option<&str>,
+        translation_unit: bool,
     ) -> Result<Self, CEnvBuildError> {
         let env_ast = ast_parser::FileParser::new().parse(env)?;
-        let mut identifiers = HashSet::new();
-        let mut int_lits = HashMap::new();
+        let mut identifiers = OrderedSet::new();
+        let mut int_lits = Map::default();
         let mut str_pool = StringPool::new(&arena.strings);
 
         let break_label = str_pool.get_or_add("break");
         let continue_label = str_pool.get_or_add("continue");
+        let return_label = str_pool.get_or_add("return");
 
-        let mut functions = HashMap::new();
-        let mut vars = HashMap::new();
+        let mut functions = OrderedMap::new();
+        let mut vars = OrderedMap::new();
 
         let mut current_function = None;
         let mut current_function_args = None;
@@ -209,7 +245,7 @@ impl<'a> CEnv<'a> {
             if token.symbol.name() == "IDENTIFIER" {
                 let name = str_pool.get_or_add(token.literal);
                 identifiers.insert(name);
-            } else if token.symbol.name() == "LITERAL_INT" {
+            } else if token.symbol.name() == "LITERAL_INT" && !is_macro_name(token.literal) {
                 let literal = Self::parse_literal_int(token.literal)?;
                 let name = format!("switch {}", token.literal);
                 let name = str_pool.get_or_add(name.as_str());
@@ -218,12 +254,20 @@ impl<'a> CEnv<'a> {
         }
 
         for i in 0..max_new_id {
-            let name = format!("__new_id_{}", i);
+            let name = new_id_placeholder(i);
             let name = str_pool.get_or_add(name.as_str());
             identifiers.insert(name);
         }
 
+        // A translation unit declares its own functions, so every function
+        // of its env is just a declaration.
+        let current_index = match translation_unit {
+            true => None,
+            false => env_ast.len().checked_sub(1),
+        };
+
         for (i, item) in env_ast.iter().enumerate() {
+            let is_current = current_index == Some(i);
             match item {
                 CEnvAstItem::FuncDecl(name, ty, args) => {
                     let name = str_pool.get_or_add(name);
@@ -238,14 +282,14 @@ impl<'a> CEnv<'a> {
                                 va_args = true;
                             }
                             CEnvAstParam::WithName(arg_name, arg_ty) => {
-                                if i == env_ast.len() - 1 {
+                                if is_current {
                                     let name = str_pool.get_or_add(arg_name);
                                     args_names.push(Some(name));
                                 }
                                 args_types.push(Self::gen_ty(arg_ty, &types)?);
                             }
                             CEnvAstParam::WithoutName(arg_ty) => {
-                                if i == env_ast.len() - 1 {
+                                if is_current {
                                     args_names.push(None);
                                 }
                                 args_types.push(Self::gen_ty(arg_ty, &types)?);
@@ -256,8 +300,8 @@ impl<'a> CEnv<'a> {
                     let func_content = types.get_func_content(func_type).unwrap();
                     let func = CDeclaredFunc::new(name, func_content);
                     functions.insert(name, func);
-                    if i == env_ast.len() - 1 {
-                        current_function = Some(func_content);
+                    if is_current {
+                        current_function = Some((name, func_content));
                         current_function_args = Some(args_names);
                     }
                 }
@@ -267,14 +311,67 @@ impl<'a> CEnv<'a> {
                     let ty = Self::gen_ty(&ty, &types)?;
                     vars.insert(name, ty);
                 }
+                // Macros are declared as the functions and constants they
+                // expand to.
+                CEnvAstItem::MacroDecl(name, ty, params) => {
+                    let name = str_pool.get_or_add(name);
+                    identifiers.insert(name);
+                    let ty = Self::gen_ty(&ty, &types)?;
+                    match params {
+                        Some(params) => {
+                            let func_type = Self::gen_func(ty, params, &types)?;
+                            let func_content = types.get_func_content(func_type).unwrap();
+                            functions.insert(name, CDeclaredFunc::new(name, func_content));
+                        }
+                        None => {
+                            vars.insert(name, types.derive_const(ty).unwrap_or(ty));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Library declarations are only added for names the input uses and
+        // the env does not declare.
+        if let Some(stdlib) = stdlib {
+            let stdlib_ast = ast_parser::FileParser::new().parse(stdlib)?;
+            for item in stdlib_ast.iter() {
+                let (name, ty) = match item {
+                    CEnvAstItem::FuncDecl(name, ty, _) | CEnvAstItem::VarDecl(name, ty) => {
+                        (name, ty)
+                    }
+                    CEnvAstItem::MacroDecl(..) => continue,
+                };
+                let name = match str_pool.find(name) {
+                    Some(name) if identifiers.contains(&name) => name,
+                    _ => continue,
+                };
+                if functions.contains_key(&name) || vars.contains_key(&name) {
+                    continue;
+                }
+                let ty = Self::gen_ty(ty, &types)?;
+                match item {
+                    CEnvAstItem::FuncDecl(_, _, args) => {
+                        let func_type = Self::gen_func(ty, args, &types)?;
+                        let func_content = types.get_func_content(func_type).unwrap();
+                        functions.insert(name, CDeclaredFunc::new(name, func_content));
+                    }
+                    CEnvAstItem::VarDecl(_, _) => {
+                        vars.insert(name, ty);
+                    }
+                    CEnvAstItem::MacroDecl(..) => unreachable!(),
+                }
             }
         }
 
-        let current_func = current_function.ok_or(CEnvBuildError::NoFunctions)?;
-        let current_func_args = current_function_args.unwrap();
+        if !translation_unit && current_function.is_none() {
+            return Err(CEnvBuildError::NoFunctions);
+        }
+        let current_func_name = current_function.map(|(name, _)| name);
+        let current_func = current_function.map(|(_, func)| func);
         let identifiers: Vec<_> = identifiers.into_iter().collect();
 
-        let mut default_functions = HashMap::new();
+        let mut default_functions = OrderedMap::new();
         let default_function = types.derive_func(types.type_i32(), vec![], true);
         let default_function = types.get_func_content(default_function).unwrap();
         for id in identifiers.iter() {
@@ -283,7 +380,10 @@ impl<'a> CEnv<'a> {
         }
 
         let globals = Self::gen_globals(&vars, &functions, types);
-        let params = Self::gen_params(&current_func, &current_func_args);
+        let params = match (current_func, current_function_args) {
+            (Some(func), Some(args)) => Self::gen_params(&func, &args),
+            _ => Vec::new(),
+        };
 
         Ok(Self {
             identifiers,
@@ -291,15 +391,23 @@ impl<'a> CEnv<'a> {
             types,
             break_label,
             continue_label,
+            return_label,
             functions,
             default_functions,
             str_pool,
             current_func,
+            current_func_name,
             globals,
             params,
         })
     }
 }
 
+/// Object-like macros standing for integer literals keep their name as the
+/// literal, and have no value here.
+fn is_macro_name(literal: &str) -> bool {
+    literal.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
 pub mod ast;
 lalrpop_mod!(pub ast_parser, "/grammars/c_env.rs");
@@ -1,27 +1,35 @@
+use crate::c::types::CTypeToken;
+
 pub enum CEnvAstItem<'input> {
-    FuncDecl(&'input str, CEnvAstType, Vec<CEnvAstParam<'input>>),
-    VarDecl(&'input str, CEnvAstType),
+    FuncDecl(&'input str, CEnvAstType<'input>, Vec<CEnvAstParam<'input>>),
+    VarDecl(&'input str, CEnvAstType<'input>),
+    /// An object-like macro expanding to a value of the type, or a
+    /// function-like macro taking the parameters.
+    MacroDecl(
+        &'input str,
+        CEnvAstType<'input>,
+        Option<Vec<CEnvAstParam<'input>>>,
+    ),
 }
 
 pub enum CEnvAstParam<'input> {
-    WithName(&'input str, CEnvAstType),
-    WithoutName(CEnvAstType),
+    WithName(&'input str, CEnvAstType<'input>),
+    WithoutName(CEnvAstType<'input>),
     VaArgs,
 }
 
-pub enum CEnvAstTypeBase {
-    Void,
-    Int,
-    Float,
-}
-
-pub enum CEnvAstTypeExtra {
+pub enum CEnvAstTypeExtra<'input> {
     Const,
     Pointer,
-    Array,
+    /// An array of the given number of elements, or of a size the env does
+    /// not give.
+    Array(Option<usize>),
+    Func(Vec<CEnvAstParam<'input>>),
 }
 
-pub struct CEnvAstType {
-    pub base: CEnvAstTypeBase,
-    pub extra: Vec<CEnvAstTypeExtra>,
+/// A type written as base type keywords, e.g. `unsigned long`, followed by
+/// derivations applied left to right, e.g. `.const.*` or `.(:int).*`.
+pub struct CEnvAstType<'input> {
+    pub base: Vec<CTypeToken>,
+    pub extra: Vec<CEnvAstTypeExtra<'input>>,
 }
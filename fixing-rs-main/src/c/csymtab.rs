@@ -9,20 +9,19 @@ pub struct VarInfo<'a> {
     #[get_copy = "pub"]
     pub ty: CTypeRef<'a>,
     #[get_copy = "pub"]
-    pub current_scope: bool,
+    pub initialized: bool,
+    #[get_copy = "pub"]
+    pub used: bool,
 }
 
 impl Debug for VarInfo<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{:?} {}",
+            "{:?}{}{}",
             self.ty,
-            if self.current_scope {
-                "current"
-            } else {
-                "outer"
-            }
+            if self.initialized { "" } else { " uninit" },
+            if self.used { "" } else { " unused" }
         )
     }
 }
@@ -31,38 +30,29 @@ impl<'a> VarInfo<'a> {
     pub fn new(ty: CTypeRef<'a>) -> Self {
         Self {
             ty,
-            current_scope: true,
+            initialized: true,
+            used: true,
         }
     }
-    pub fn inner_scope(&self) -> Self {
+    pub fn new_local(ty: CTypeRef<'a>, initialized: bool) -> Self {
         Self {
-            ty: self.ty,
-            current_scope: false,
+            ty,
+            initialized,
+            used: false,
         }
     }
 }
 
 pub type CSymTab<'a> = SymTab<StringRef<'a>, VarInfo<'a>>;
 
-pub trait CSymTabExt {
-    fn new_scope(&self) -> Self;
-}
-
-impl<'a> CSymTabExt for CSymTab<'a> {
-    fn new_scope(&self) -> Self {
-        let m = self
-            .iter()
-            .map(|(k, v)| (k.clone(), v.inner_scope()))
-            .collect();
-        Self::from_map(m)
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
 pub enum CIdSelector<'a> {
     FuncName(CSymTab<'a>),
     Identifier(CSymTab<'a>),
     NewIdentifier(CSymTab<'a>),
+    /// The name of a function being declared, which may repeat an earlier
+    /// declaration of the function.
+    FuncDecl(CSymTab<'a>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
@@ -1,9 +1,12 @@
 use super::{cenv::CEnvBuildError, tokenizer::CParseError};
 use crate::c::{
     cenv::{CEnv, CEnvArena},
+    macros::CMacros,
     semantic::CSProcessor,
+    stdlib::CStdlib,
+    strictness::CStrictness,
     syntactic::CGProcessor,
-    tokenizer::CTokenizer,
+    tokenizer::{directives, restore_directives, CTokenizer},
     types::{CTypeArena, CTypePool},
 };
 use fixing_rs_base::{
@@ -13,11 +16,29 @@ use fixing_rs_base::{
     },
     fixing_info::FixingInfo,
     grammar::Grammar,
+    naming::NamingScope,
     tokenizer::Tokenizer,
 };
 use std::time::Instant;
 
-pub struct CFixingInputProcessor;
+#[derive(Default)]
+pub struct CFixingInputProcessor {
+    pub strictness: CStrictness,
+    pub stdlib: CStdlib,
+    /// Whether inputs are whole translation units rather than the body of
+    /// the last function of their env.
+    pub translation_unit: bool,
+}
+
+impl CFixingInputProcessor {
+    pub fn new(strictness: CStrictness, stdlib: CStdlib, translation_unit: bool) -> Self {
+        Self {
+            strictness,
+            stdlib,
+            translation_unit,
+        }
+    }
+}
 
 impl FixingInputProcessorBase for CFixingInputProcessor {
     fn info(&self) -> &FixingInfo {
@@ -36,24 +57,103 @@ impl FixingInputProcessor for CFixingInputProcessor {
         do_fix: impl DoFix,
     ) -> Result<FixTaskResult, FixTaskError<Self::TokenizerError, Self::EnvLoadError>> {
         let symbol_ref = grammar.get_symbol_ref();
-        let tokens = CTokenizer
+        let macros = CMacros::new(input_str, env_str).map_err(|e| FixTaskError::EnvLoadError(e))?;
+        let mut tokens = CTokenizer
             .tokenize(input_str, symbol_ref)
             .map_err(|e| FixTaskError::TokenizerError(e))?;
+        macros.apply(&mut tokens, symbol_ref);
+        // Macros of the input come first, so that the env can override them
+        // and its last function stays the current one.
+        let env_str = macros.env() + env_str;
         let arena = CEnvArena::new();
         let types_arena = CTypeArena::new();
         let types = CTypePool::new(&types_arena);
-        let env = CEnv::build(&arena, env_str, &types, &tokens, info.max_new_id)
-            .map_err(|e| FixTaskError::EnvLoadError(e))?;
-        let sproc = CSProcessor::new(&env);
-        let gproc = CGProcessor;
+        let env = CEnv::build(
+            &arena,
+            &env_str,
+            &types,
+            &tokens,
+            info.max_new_id,
+            self.stdlib.env(),
+            self.translation_unit,
+        )
+        .map_err(|e| FixTaskError::EnvLoadError(e))?;
+        let sproc = CSProcessor::new(&env, self.strictness);
+        let gproc = CGProcessor::new(grammar, self.translation_unit);
+        let naming_scope = NamingScope::new(
+            env.identifiers()
+                .iter()
+                .chain(env.globals().keys())
+                .chain(env.default_functions().keys())
+                .map(|x| x.to_string()),
+            c_declared_type,
+        );
 
-        do_fix.do_fix(grammar, &tokens, &gproc, &sproc, info, time_before_load)
+        let result = do_fix.do_fix(
+            grammar,
+            None,
+            &tokens,
+            &gproc,
+            &sproc,
+            naming_scope,
+            info,
+            time_before_load,
+        )?;
+        // Directives are not tokens; they are written back where they were.
+        let directives = directives(input_str);
+        if let (Some(output), Some(outputs), false) =
+            (&info.output_name, &result.outputs, directives.is_empty())
+        {
+            let lines = restore_directives(&result.input, outputs, &directives);
+            let lines: String = lines.iter().map(|x| format!("{}\n", x)).collect();
+            std::fs::write(output, lines).expect("Unable to write output file.");
+        }
+        Ok(result)
     }
 
     type TokenizerError = CParseError;
     type EnvLoadError = CEnvBuildError;
 }
 
+const C_BASIC_TYPES: [&str; 6] = ["char", "short", "int", "long", "float", "double"];
+const C_TYPE_QUALIFIERS: [&str; 4] = ["signed", "unsigned", "const", "void"];
+
+/// Finds the basic type of the declaration introducing `tokens[position]`,
+/// looking through declarators (`*`, `const`, `(`) and earlier declarations
+/// of the same list.
+fn c_declared_type(tokens: &[String], position: usize) -> Option<String> {
+    let mut index = position;
+    while index > 0 && ["*", "const", "("].contains(&tokens[index - 1].as_str()) {
+        index -= 1;
+    }
+    if index > 0 && tokens[index - 1] == "," {
+        index = tokens[..index]
+            .iter()
+            .rposition(|x| [";", "{", "}"].contains(&x.as_str()))
+            .map(|x| x + 1)
+            .unwrap_or(0);
+        let end = tokens[index..position]
+            .iter()
+            .position(|x| {
+                !C_BASIC_TYPES.contains(&x.as_str()) && !C_TYPE_QUALIFIERS.contains(&x.as_str())
+            })
+            .map(|x| x + index)
+            .unwrap_or(position);
+        return tokens[index..end]
+            .iter()
+            .rfind(|x| C_BASIC_TYPES.contains(&x.as_str()))
+            .cloned();
+    }
+    tokens[..index]
+        .iter()
+        .rev()
+        .take_while(|x| {
+            C_BASIC_TYPES.contains(&x.as_str()) || C_TYPE_QUALIFIERS.contains(&x.as_str())
+        })
+        .find(|x| C_BASIC_TYPES.contains(&x.as_str()))
+        .cloned()
+}
+
 pub const C_GRAMMAR: &'static str = include_str!("c_grammar");
 pub const C_GRAMMAR_FILE: &'static str = "src/c/c_grammar";
 pub const C_PROP_G: &'static str = "CProp";
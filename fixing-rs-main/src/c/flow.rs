@@ -0,0 +1,241 @@
+use super::csymtab::{CSymTab, VarInfo};
+use fixing_rs_base::{containers::Map, utils::StringRef};
+use std::ops::Deref;
+
+/// Variables read, written and used by an expression, as bit sets over the
+/// variables tracked by a [`CFlowVars`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CFlowBits {
+    /// Variables whose value may be read before the expression writes them.
+    pub reads: u128,
+    /// Variables written whenever the expression is evaluated.
+    pub writes: u128,
+    /// Variables referenced other than as the target of an assignment.
+    pub uses: u128,
+    /// The variable the expression consists of, if any.
+    pub target: u128,
+}
+
+const NO_FLOW: CFlowBits = CFlowBits {
+    reads: 0,
+    writes: 0,
+    uses: 0,
+    target: 0,
+};
+
+/// The [`CFlowBits`] of an expression, boxed and only kept when some bit is
+/// set. Without flow analysis no variable is tracked, so the props holding
+/// them stay as small as if they had no flow.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Prop)]
+pub struct CFlow(Option<Box<CFlowBits>>);
+
+impl CFlow {
+    fn new(bits: CFlowBits) -> Self {
+        Self((bits != NO_FLOW).then(|| Box::new(bits)))
+    }
+
+    pub fn read(var: u128) -> Self {
+        Self::new(CFlowBits {
+            reads: var,
+            writes: 0,
+            uses: var,
+            target: var,
+        })
+    }
+
+    /// Both flows, evaluated in an unspecified order.
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(CFlowBits {
+            reads: self.reads | other.reads,
+            writes: self.writes | other.writes,
+            uses: self.uses | other.uses,
+            target: 0,
+        })
+    }
+
+    /// `self`, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self::new(CFlowBits {
+            reads: self.reads | (next.reads & !self.writes),
+            writes: self.writes | next.writes,
+            uses: self.uses | next.uses,
+            target: 0,
+        })
+    }
+
+    /// `self`, then `next` if at all.
+    pub fn then_maybe(&self, next: &Self) -> Self {
+        Self::new(CFlowBits {
+            writes: self.writes,
+            ..*self.then(next)
+        })
+    }
+
+    /// Either `self` or `other`.
+    pub fn either(&self, other: &Self) -> Self {
+        Self::new(CFlowBits {
+            writes: self.writes & other.writes,
+            ..*self.union(other)
+        })
+    }
+
+    /// `self` with its value discarded.
+    pub fn value(&self) -> Self {
+        Self::new(CFlowBits {
+            target: 0,
+            ..**self
+        })
+    }
+
+    /// Assigning `value` to `self`.
+    pub fn assign(&self, value: &Self) -> Self {
+        if self.target != 0 {
+            Self::new(CFlowBits {
+                writes: value.writes | self.target,
+                ..**value
+            })
+            .value()
+        } else {
+            self.union(value)
+        }
+    }
+
+    /// Reading and writing `self`, as in `x++` or `x += 1`.
+    pub fn update(&self) -> Self {
+        Self::new(CFlowBits {
+            writes: self.writes | self.target,
+            target: 0,
+            ..**self
+        })
+    }
+
+    /// Taking the address of `self`, which may initialize it.
+    pub fn address(&self) -> Self {
+        Self::new(CFlowBits {
+            reads: self.reads & !self.target,
+            writes: self.writes | self.target,
+            uses: self.uses,
+            target: 0,
+        })
+    }
+
+    /// `self` in an unevaluated context, as in `sizeof`.
+    pub fn unevaluated(&self) -> Self {
+        Self::new(CFlowBits {
+            uses: self.uses,
+            ..NO_FLOW
+        })
+    }
+}
+
+impl Deref for CFlow {
+    type Target = CFlowBits;
+
+    fn deref(&self) -> &CFlowBits {
+        self.0.as_deref().unwrap_or(&NO_FLOW)
+    }
+}
+
+/// Assigns the locals tracked by the flow analysis to bits of a [`CFlow`].
+/// Only the first 128 candidate names are tracked; locals with other names
+/// are treated as always initialized and used.
+pub struct CFlowVars<'a> {
+    bits: Map<StringRef<'a>, u128>,
+}
+
+impl<'a> CFlowVars<'a> {
+    pub fn new(names: impl IntoIterator<Item = StringRef<'a>>) -> Self {
+        let bits = names
+            .into_iter()
+            .take(128)
+            .enumerate()
+            .map(|(i, x)| (x, 1u128 << i))
+            .collect();
+        Self { bits }
+    }
+
+    pub fn bit(&self, name: StringRef<'a>) -> u128 {
+        self.bits.get(&name).copied().unwrap_or(0)
+    }
+
+    /// Variables of `symtab` that are not definitely initialized.
+    pub fn uninitialized(&self, symtab: &CSymTab<'a>) -> u128 {
+        symtab
+            .iter()
+            .filter(|(_, info)| !info.initialized)
+            .fold(0, |acc, (name, _)| acc | self.bit(*name))
+    }
+
+    /// `symtab` after evaluating `flow`.
+    pub fn apply(&self, symtab: &CSymTab<'a>, flow: &CFlow) -> CSymTab<'a> {
+        if flow.writes == 0 && flow.uses == 0 {
+            return symtab.clone();
+        }
+        map_infos(symtab, |name, info| {
+            let bit = self.bit(name);
+            VarInfo {
+                initialized: info.initialized || flow.writes & bit != 0,
+                used: info.used || flow.uses & bit != 0,
+                ..info.clone()
+            }
+        })
+    }
+}
+
+/// `symtab` with the information on each variable replaced by `f`. Only the
+/// variables whose information changes are updated.
+fn map_infos<'a>(
+    symtab: &CSymTab<'a>,
+    f: impl Fn(StringRef<'a>, &VarInfo<'a>) -> VarInfo<'a>,
+) -> CSymTab<'a> {
+    symtab.iter().fold(symtab.clone(), |result, (name, info)| {
+        let new_info = f(*name, info);
+        match new_info == *info {
+            true => result,
+            false => result.update(*name, new_info),
+        }
+    })
+}
+
+/// Variables of `base` as they are in `from`, a later state of the same
+/// scope or of a scope nested in it. Variables declared in the nested scope
+/// are dropped.
+pub fn inherit_flags<'a>(base: &CSymTab<'a>, from: &CSymTab<'a>, nested: bool) -> CSymTab<'a> {
+    map_infos(base, |name, info| match from.get(name) {
+        Some(from_info) if !(nested && from.is_current(name)) => VarInfo {
+            initialized: from_info.initialized,
+            used: from_info.used,
+            ..info.clone()
+        },
+        _ => info.clone(),
+    })
+}
+
+/// The state after either `a` or `b`, two states of the same scope: a
+/// variable is initialized if it is in both, and used if it is in either.
+pub fn join<'a>(a: &CSymTab<'a>, b: &CSymTab<'a>) -> CSymTab<'a> {
+    map_infos(a, |name, info| match b.get(name) {
+        Some(b_info) => VarInfo {
+            initialized: info.initialized && b_info.initialized,
+            used: info.used || b_info.used,
+            ..info.clone()
+        },
+        None => info.clone(),
+    })
+}
+
+/// Whether a variable declared in the current scope of `symtab` is never
+/// used.
+pub fn has_unused(symtab: &CSymTab<'_>) -> bool {
+    symtab.iter_current().any(|(_, info)| !info.used)
+}
+
+/// `symtab` with every variable initialized and used, as globals and
+/// parameters always are.
+pub fn settle<'a>(symtab: &CSymTab<'a>) -> CSymTab<'a> {
+    map_infos(symtab, |_, info| VarInfo {
+        initialized: true,
+        used: true,
+        ..info.clone()
+    })
+}
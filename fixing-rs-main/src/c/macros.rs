@@ -0,0 +1,201 @@
+use super::{
+    cenv::{
+        ast::{CEnvAstItem, CEnvAstType, CEnvAstTypeExtra},
+        ast_parser, CEnvBuildError,
+    },
+    types::CTypeToken,
+};
+use fixing_rs_base::{containers::Map, grammar::GrammarSymbolsRef, tokenizer::Token};
+use std::fmt::Write;
+
+/// Keywords no constant expression holds.
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "int", "long", "register", "return", "short",
+    "signed", "static", "struct", "switch", "typedef", "union", "unsigned", "void", "volatile",
+    "while",
+];
+
+/// `code` with each string literal and character constant replaced by `0`.
+fn without_quoted(code: &str) -> String {
+    let mut result = String::new();
+    let mut quote = None;
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                result.push('0');
+            }
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+/// The literal an object-like macro stands for in the token stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CMacroLiteral {
+    Int,
+    Float,
+    String,
+}
+
+impl CMacroLiteral {
+    pub fn terminal(&self) -> &'static str {
+        match self {
+            CMacroLiteral::Int => "LITERAL_INT",
+            CMacroLiteral::Float => "LITERAL_FLOAT",
+            CMacroLiteral::String => "LITERAL_STRING",
+        }
+    }
+
+    /// The type of the literal, in the format of envs.
+    pub fn env_type(&self) -> &'static str {
+        match self {
+            CMacroLiteral::Int => "int",
+            CMacroLiteral::Float => "double",
+            CMacroLiteral::String => "char.const.*",
+        }
+    }
+
+    /// Guesses the literal an expansion evaluates to: a string if it holds a
+    /// string literal, a floating point number if it holds a floating point
+    /// constant and an integer otherwise. Expansions that are not constant
+    /// expressions, e.g. empty ones or ones holding braces, semicolons or
+    /// keywords such as type names, stand for no literal.
+    pub fn of_expansion(expansion: &str) -> Option<Self> {
+        let code = without_quoted(expansion);
+        let words: Vec<&str> = code
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '_')
+            .filter(|x| !x.is_empty())
+            .collect();
+        if code.trim().is_empty()
+            || code.contains(['{', '}', ';', '#'])
+            || words.iter().any(|x| C_KEYWORDS.contains(x))
+        {
+            return None;
+        }
+        if expansion.contains('"') {
+            return Some(CMacroLiteral::String);
+        }
+        let is_float = |word: &&str| {
+            word.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && word.contains(|c: char| c.is_ascii_digit())
+                && !word.starts_with("0x")
+                && !word.starts_with("0X")
+                && word.contains(['.', 'e', 'E'])
+        };
+        if words.iter().any(is_float) {
+            Some(CMacroLiteral::Float)
+        } else {
+            Some(CMacroLiteral::Int)
+        }
+    }
+
+    /// The literal a value of `ty` can be written as, if any.
+    pub fn of_type(ty: &CEnvAstType) -> Option<Self> {
+        let mut pointers = 0;
+        for extra in ty.extra.iter() {
+            match extra {
+                CEnvAstTypeExtra::Const => {}
+                CEnvAstTypeExtra::Pointer => pointers += 1,
+                _ => return None,
+            }
+        }
+        let has = |token: CTypeToken| ty.base.contains(&token);
+        match pointers {
+            0 if has(CTypeToken::Float) || has(CTypeToken::Double) => Some(CMacroLiteral::Float),
+            0 if !has(CTypeToken::Void) => Some(CMacroLiteral::Int),
+            1 if has(CTypeToken::Char) => Some(CMacroLiteral::String),
+            _ => None,
+        }
+    }
+}
+
+/// Macros the input may use, defined by `#define` lines of the input or by
+/// `=MACRO` items of the env. Macros of the env take precedence.
+///
+/// Object-like macros standing for a literal become that literal's terminal,
+/// so they can be used wherever the literal can, e.g. as array sizes or case
+/// labels, while fixes keep their names.
+#[derive(Debug, Default)]
+pub struct CMacros {
+    literals: Map<String, CMacroLiteral>,
+    /// Macros defined by the input, with the literal of object-like ones.
+    defined: Vec<(String, Option<CMacroLiteral>)>,
+}
+
+impl CMacros {
+    pub fn new(input: &str, env: &str) -> Result<Self, CEnvBuildError> {
+        let mut result = Self::default();
+        for line in input.lines() {
+            if let Some((name, literal)) = Self::parse_define(line) {
+                if let Some(literal) = literal {
+                    result.literals.insert(name.to_string(), literal);
+                }
+                result.defined.push((name.to_string(), literal));
+            }
+        }
+        for item in ast_parser::FileParser::new().parse(env)? {
+            if let CEnvAstItem::MacroDecl(name, ty, params) = item {
+                match params.map_or(CMacroLiteral::of_type(&ty), |_| None) {
+                    Some(literal) => result.literals.insert(name.to_string(), literal),
+                    None => result.literals.remove(name),
+                };
+            }
+        }
+        Ok(result)
+    }
+
+    /// The name of the macro a `#define` line defines, with its literal if it
+    /// is object-like. Object-like macros standing for no literal are left
+    /// out, their uses being taken as identifiers.
+    fn parse_define(line: &str) -> Option<(&str, Option<CMacroLiteral>)> {
+        let directive = line.trim().strip_prefix('#')?.trim_start();
+        let rest = directive.strip_prefix("define")?.trim_start();
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let (name, expansion) = rest.split_at(end);
+        if name.is_empty() {
+            None
+        } else if expansion.starts_with('(') {
+            Some((name, None))
+        } else {
+            CMacroLiteral::of_expansion(expansion).map(|x| (name, Some(x)))
+        }
+    }
+
+    /// Turns identifiers naming object-like macros into the literals they
+    /// stand for.
+    pub fn apply<'a>(&self, tokens: &mut [Token<'a, '_>], grammar: GrammarSymbolsRef<'a>) {
+        for token in tokens.iter_mut() {
+            if token.symbol.name() != "IDENTIFIER" {
+                continue;
+            }
+            if let Some(literal) = self.literals.get(token.literal) {
+                token.symbol = grammar.symbolic_terminals[literal.terminal()];
+            }
+        }
+    }
+
+    /// Env items declaring the macros defined by the input. The arguments of
+    /// function-like macros are not checked, and they expand to an `int`.
+    pub fn env(&self) -> String {
+        let mut result = String::new();
+        for (name, literal) in self.defined.iter() {
+            match literal {
+                Some(literal) => writeln!(result, "=MACRO {}:{};", name, literal.env_type()),
+                None => writeln!(result, "=MACRO {}:int-...;", name),
+            }
+            .unwrap();
+        }
+        result
+    }
+}
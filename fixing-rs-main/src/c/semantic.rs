@@ -1,12 +1,15 @@
 use super::{
     cenv::CEnv,
-    csymtab::{CIdSelected, CIdSelector, CSymTab, CSymTabExt, VarInfo},
+    csymtab::{CDeclaredFunc, CIdSelected, CIdSelector, CSymTab, VarInfo},
+    flow::{self, CFlow, CFlowVars},
+    strictness::CStrictness,
     syntactic::CProp,
     types::{CExprInfo, CFuncContentRef, CType, CTypeComposed, CTypeRef, CTypeToken},
 };
 use fixing_rs_base::{
     props::{PropArray, PropEmpty},
     union_prop,
+    utils::StringRef,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop, ValueEnum)]
@@ -15,6 +18,21 @@ pub enum CExprSuffixType {
     SelfInc,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CExprSuffix {
+    pub kind: CExprSuffixType,
+    pub flow: CFlow,
+}
+
+impl CExprSuffix {
+    pub fn new(kind: CExprSuffixType) -> Self {
+        Self {
+            kind,
+            flow: CFlow::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop, ValueEnum)]
 pub enum CExprPrefixType {
     Neg,
@@ -44,6 +62,31 @@ pub enum CBinOpType {
     Comma,
 }
 
+/// Whether an integer literal is `0`, which also serves as the null pointer
+/// constant. Inserted literals are always `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop, ValueEnum)]
+pub enum CIntLiteral {
+    Zero,
+    NonZero,
+}
+
+impl CIntLiteral {
+    pub fn from_literal(literal: &str) -> Self {
+        let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+        let digits = digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+            .unwrap_or(digits);
+        let is_zero =
+            (!digits.is_empty() && digits.chars().all(|c| c == '0')) || literal == "'\\0'";
+        if is_zero {
+            CIntLiteral::Zero
+        } else {
+            CIntLiteral::NonZero
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
 pub enum CArgs<'a> {
     VaArgs(CSymTab<'a>),
@@ -104,15 +147,15 @@ impl<'a> CArgs<'a> {
             CArgs::Args(sym_tab, _, _) => sym_tab.clone(),
         }
     }
-    pub fn can_take(&self, ty: CTypeRef<'a>) -> bool {
-        if ty.is_void() {
+    pub fn can_take(&self, expr: &CExprInfo<'a>, strictness: &CStrictness) -> bool {
+        if expr.ty.is_void() {
             return false;
         }
         match self {
             CArgs::VaArgs(_) => true,
             CArgs::Args(_, func, i) => match func.args().get(*i) {
                 None => true,
-                Some(to_ty) => CType::can_cast_to(ty, *to_ty),
+                Some(to_ty) => CType::can_convert_to(expr, *to_ty, strictness),
             },
         }
     }
@@ -147,6 +190,103 @@ impl<'a> CDeclIdSyn<'a> {
     }
 }
 
+/// The state after a statement, and how control may leave it. The flags
+/// stay `false` unless missing returns are rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CStmtSyn<'a> {
+    pub symtab: CSymTab<'a>,
+    /// Every path through the statement ends in a `return`.
+    pub returns: bool,
+    /// Some path through the statement leaves the enclosing loop or switch.
+    pub breaks: bool,
+    /// Some path through the statement goes on with the next iteration of
+    /// the enclosing loop.
+    pub continues: bool,
+}
+
+impl<'a> CStmtSyn<'a> {
+    pub fn new(symtab: CSymTab<'a>) -> Self {
+        Self {
+            symtab,
+            returns: false,
+            breaks: false,
+            continues: false,
+        }
+    }
+
+    /// `self`, then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            symtab: next.symtab.clone(),
+            returns: self.returns || (!self.breaks && !self.continues && next.returns),
+            breaks: self.breaks || (!self.returns && next.breaks),
+            continues: self.continues || (!self.returns && next.continues),
+        }
+    }
+}
+
+/// The condition of a `for` loop.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CForCond {
+    pub flow: CFlow,
+    /// The condition is empty or a non-zero constant.
+    pub always: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub enum CSwitchContent<'a> {
+    Label { symtab: CSymTab<'a>, default: bool },
+    Stmt(CStmtSyn<'a>),
+}
+
+impl<'a> CSwitchContent<'a> {
+    pub fn symtab(&self) -> &CSymTab<'a> {
+        match self {
+            CSwitchContent::Label { symtab, .. } => symtab,
+            CSwitchContent::Stmt(stmt) => &stmt.symtab,
+        }
+    }
+}
+
+/// A switch block from some point to its end.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CSwitchBlock<'a> {
+    /// The statements up to the end of the block.
+    pub rest: CStmtSyn<'a>,
+    /// Control entering at any of the labels up to the end of the block
+    /// returns.
+    pub labels_return: bool,
+    pub has_default: bool,
+}
+
+/// The parameters of a function, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CParams<'a> {
+    /// The scope of the function body with the parameters declared.
+    pub symtab: CSymTab<'a>,
+    pub types: Vec<CTypeRef<'a>>,
+    /// Some parameter has no name, which only prototypes allow.
+    pub unnamed: bool,
+}
+
+impl<'a> CParams<'a> {
+    pub fn new(symtab: CSymTab<'a>) -> Self {
+        Self {
+            symtab,
+            types: Vec::new(),
+            unnamed: false,
+        }
+    }
+}
+
+/// A function declarator, shared by prototypes and definitions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct CFuncHead<'a> {
+    pub name: StringRef<'a>,
+    pub ty: CTypeRef<'a>,
+    pub params: CParams<'a>,
+}
+
 union_prop!(
     CInhProp<'a>,
     Empty,
@@ -165,27 +305,151 @@ union_prop!(
     {
         Empty(PropEmpty),
         SymTab(CSymTab<'a>),
-        Suffix(CExprSuffixType),
+        Suffix(CExprSuffix),
         Prefix(CExprPrefixType),
         BinOp(CBinOpType),
         Ty(CTypeRef<'a>),
         TyBasic(CTypeToken),
         TyComposed(CTypeComposed),
+        IntLiteral(CIntLiteral),
         IdSelected(CIdSelected<'a>),
         Expr(CExprInfo<'a>),
-        DeclId(CDeclIdSyn<'a>)
+        DeclId(CDeclIdSyn<'a>),
+        Flow(CFlow),
+        ForCond(CForCond),
+        Stmt(CStmtSyn<'a>),
+        SwitchContent(CSwitchContent<'a>),
+        SwitchBlock(CSwitchBlock<'a>),
+        Params(CParams<'a>),
+        FuncHead(CFuncHead<'a>)
     }
 );
 
 pub struct CSProcessor<'a> {
     env: &'a CEnv<'a>,
     void_info: VarInfo<'a>,
+    strictness: CStrictness,
+    flow_vars: CFlowVars<'a>,
 }
 
 impl<'a> CSProcessor<'a> {
-    pub fn new(env: &'a CEnv<'a>) -> Self {
+    pub fn new(env: &'a CEnv<'a>, strictness: CStrictness) -> Self {
         let void_info = VarInfo::new(env.types().type_void());
-        Self { env, void_info }
+        // Names that only locals can have come first, so they are tracked
+        // even when there are too many names to track all of them.
+        let is_declared = |x: &StringRef<'a>| {
+            env.globals().contains_key(x)
+                || env.functions().contains_key(x)
+                || env.params().iter().any(|(name, _)| name == x)
+        };
+        let mut names: Vec<_> = env
+            .identifiers()
+            .iter()
+            .copied()
+            .filter(|x| env.is_true_id(*x))
+            .collect();
+        names.sort_by_key(is_declared);
+        let flow_vars = CFlowVars::new(names);
+        Self {
+            env,
+            void_info,
+            strictness,
+            flow_vars,
+        }
+    }
+
+    fn var_flow(&self, name: StringRef<'a>) -> CFlow {
+        if self.strictness.flow_analysis() {
+            CFlow::read(self.flow_vars.bit(name))
+        } else {
+            CFlow::default()
+        }
+    }
+
+    fn local_info(&self, name: StringRef<'a>, ty: CTypeRef<'a>, has_init: bool) -> VarInfo<'a> {
+        if self.strictness.flow_analysis() && self.flow_vars.bit(name) != 0 {
+            VarInfo {
+                used: self.strictness.unused_variables,
+                ..VarInfo::new_local(
+                    ty,
+                    has_init || ty.is_array() || self.strictness.uninitialized_reads,
+                )
+            }
+        } else {
+            VarInfo::new(ty)
+        }
+    }
+
+    /// `symtab` after evaluating `flow`, unless `flow` reads a local that is
+    /// not initialized yet.
+    fn eval(&self, symtab: &CSymTab<'a>, flow: &CFlow) -> Option<CSymTab<'a>> {
+        if self.reads_initialized(symtab, flow) {
+            Some(self.flow_vars.apply(symtab, flow))
+        } else {
+            None
+        }
+    }
+
+    fn reads_initialized(&self, symtab: &CSymTab<'a>, flow: &CFlow) -> bool {
+        self.strictness.uninitialized_reads
+            || flow.reads & self.flow_vars.uninitialized(symtab) == 0
+    }
+
+    /// The state of `outer` after the nested scope ending in `inner`, unless
+    /// a local of the nested scope is never used.
+    fn leave_scope(&self, outer: &CSymTab<'a>, inner: &CSymTab<'a>) -> Option<CSymTab<'a>> {
+        if !self.strictness.flow_analysis() {
+            Some(outer.clone())
+        } else if !self.strictness.unused_variables && flow::has_unused(inner) {
+            None
+        } else {
+            Some(flow::inherit_flags(outer, inner, true))
+        }
+    }
+
+    fn checks_return(&self) -> bool {
+        !self.strictness.missing_return
+    }
+
+    /// Whether a loop whose condition is `always` true and whose body is
+    /// `body` only ends by returning: gcc does not warn about falling off
+    /// the end after it.
+    fn loops_forever(&self, always: bool, body: &CStmtSyn<'a>) -> bool {
+        self.checks_return() && always && !body.breaks
+    }
+
+    /// The function `name` as declared in `symtab` rather than in the env.
+    fn declared_func(
+        &self,
+        symtab: &CSymTab<'a>,
+        name: StringRef<'a>,
+    ) -> Option<CDeclaredFunc<'a>> {
+        let content = self.env.types().get_func_content(symtab.get(name)?.ty)?;
+        Some(CDeclaredFunc::new(name, content))
+    }
+
+    /// The return type of the function whose body `symtab` is in.
+    fn return_type(&self, symtab: &CSymTab<'a>) -> Option<CTypeRef<'a>> {
+        symtab.get(self.env.return_label()).map(|info| info.ty)
+    }
+
+    /// Whether a definition of `name` returning `ret` is rejected when
+    /// control reaches the end of `body`. Reaching the end of `main`
+    /// returns 0.
+    fn falls_off(&self, name: StringRef<'a>, ret: CTypeRef<'a>, body: &CStmtSyn<'a>) -> bool {
+        self.checks_return()
+            && ret != self.env.types().type_void()
+            && name.as_str() != "main"
+            && !body.returns
+    }
+
+    /// The state of `base` after `from`, which may run zero or more times.
+    fn maybe(&self, base: &CSymTab<'a>, from: &CSymTab<'a>) -> CSymTab<'a> {
+        if self.strictness.flow_analysis() {
+            flow::join(base, &flow::inherit_flags(base, from, false))
+        } else {
+            base.clone()
+        }
     }
 }
 
@@ -203,8 +467,8 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         _s1: &PropEmpty,
-    ) -> CExprSuffixType {
-        CExprSuffixType::SelfInc
+    ) -> CExprSuffix {
+        CExprSuffix::new(CExprSuffixType::SelfInc)
     }
 
     // nts exprsuffix : 1 --
@@ -213,8 +477,8 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         _s1: &PropEmpty,
-    ) -> CExprSuffixType {
-        CExprSuffixType::SelfInc
+    ) -> CExprSuffix {
+        CExprSuffix::new(CExprSuffixType::SelfInc)
     }
 
     // nts exprsuffix : 2 [ expr ]
@@ -225,9 +489,12 @@ impl<'a> CSProcessor<'a> {
         _s1: &PropEmpty,
         expr: &CExprInfo<'a>,
         _s3: &PropEmpty,
-    ) -> Option<CExprSuffixType> {
+    ) -> Option<CExprSuffix> {
         if expr.ty.is_integer() {
-            Some(CExprSuffixType::Slice)
+            Some(CExprSuffix {
+                kind: CExprSuffixType::Slice,
+                flow: expr.flow.value(),
+            })
         } else {
             None
         }
@@ -607,7 +874,7 @@ impl<'a> CSProcessor<'a> {
     ) -> Option<CExprInfo<'a>> {
         match id {
             CIdSelected::Identifier(id) => match inh.get(*id) {
-                Some(d) => Some(CExprInfo::new(d.ty(), true)),
+                Some(d) => Some(CExprInfo::new(d.ty(), true).with_flow(self.var_flow(*id))),
                 None => None,
             },
             _ => unreachable!(),
@@ -630,18 +897,18 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         expr: &CExprInfo<'a>,
-        op: &CExprSuffixType,
+        op: &CExprSuffix,
     ) -> Option<CExprInfo<'a>> {
-        match op {
+        match op.kind {
             CExprSuffixType::SelfInc => {
                 if expr.is_lvalue && expr.ty.can_self_inc() {
-                    Some(CExprInfo::new(expr.ty, false))
+                    Some(CExprInfo::new(expr.ty, false).with_flow(expr.flow.update()))
                 } else {
                     None
                 }
             }
             CExprSuffixType::Slice => match expr.ty.get_slice() {
-                Some(ty) => Some(CExprInfo::new(ty, true)),
+                Some(ty) => Some(CExprInfo::new(ty, true).with_flow(expr.flow.union(&op.flow))),
                 None => None,
             },
         }
@@ -657,7 +924,7 @@ impl<'a> CSProcessor<'a> {
     ) -> Option<CExprInfo<'a>> {
         // ++ --
         if expr.is_lvalue && expr.ty.can_self_inc() {
-            Some(CExprInfo::new(expr.ty, false))
+            Some(CExprInfo::new(expr.ty, false).with_flow(expr.flow.update()))
         } else {
             None
         }
@@ -672,7 +939,18 @@ impl<'a> CSProcessor<'a> {
         op: &CBinOpType,
         expr2: &CExprInfo<'a>,
     ) -> Option<CExprInfo<'a>> {
-        match op {
+        let flow = match op {
+            CBinOpType::Assign => expr1.flow.assign(&expr2.flow),
+            CBinOpType::PlusAssign
+            | CBinOpType::MulAssign
+            | CBinOpType::ModAssign
+            | CBinOpType::MoveAssign
+            | CBinOpType::BitOpAssign => expr1.flow.update().union(&expr2.flow),
+            CBinOpType::BoolOp => expr1.flow.then_maybe(&expr2.flow),
+            CBinOpType::Comma => expr1.flow.then(&expr2.flow),
+            _ => expr1.flow.union(&expr2.flow),
+        };
+        let result = match op {
             CBinOpType::Mult => {
                 CType::mult_type(expr1.ty, expr2.ty).map(|ty| CExprInfo::new(ty, false))
             }
@@ -688,8 +966,10 @@ impl<'a> CSProcessor<'a> {
             }
             CBinOpType::Compare => CType::compare_type(expr1.ty, expr2.ty, self.env.types())
                 .map(|ty| CExprInfo::new(ty, false)),
-            CBinOpType::Equals => CType::equals_type(expr1.ty, expr2.ty, self.env.types())
-                .map(|ty| CExprInfo::new(ty, false)),
+            CBinOpType::Equals => {
+                CType::equals_type(expr1, expr2, self.env.types(), &self.strictness)
+                    .map(|ty| CExprInfo::new(ty, false))
+            }
             CBinOpType::BitOp => {
                 CType::bitop_type(expr1.ty, expr2.ty).map(|ty| CExprInfo::new(ty, false))
             }
@@ -697,7 +977,8 @@ impl<'a> CSProcessor<'a> {
                 .map(|ty| CExprInfo::new(ty, false)),
             CBinOpType::Assign => {
                 if expr1.is_lvalue {
-                    CType::assign_type(expr1.ty, expr2.ty).map(|ty| CExprInfo::new(ty, false))
+                    CType::assign_type(expr1.ty, expr2, &self.strictness)
+                        .map(|ty| CExprInfo::new(ty, false))
                 } else {
                     None
                 }
@@ -750,7 +1031,8 @@ impl<'a> CSProcessor<'a> {
                 }
             }
             CBinOpType::Comma => Some(CExprInfo::new(expr2.ty, false)),
-        }
+        };
+        result.map(|x| x.with_flow(flow))
     }
 
     // nts expr : 5 expr ? expr : expr
@@ -765,7 +1047,9 @@ impl<'a> CSProcessor<'a> {
         expr3: &CExprInfo<'a>,
     ) -> Option<CExprInfo<'a>> {
         if expr1.ty.is_scalar() {
-            CType::condition_type(expr2.ty, expr3.ty).map(|ty| CExprInfo::new(ty, false))
+            let flow = expr1.flow.then(&expr2.flow.either(&expr3.flow));
+            CType::condition_type(expr2.ty, expr3.ty)
+                .map(|ty| CExprInfo::new(ty, false).with_flow(flow))
         } else {
             None
         }
@@ -809,11 +1093,13 @@ impl<'a> CSProcessor<'a> {
         _inh: &CSymTab<'a>,
         id: &CIdSelected<'a>,
         _s2: &PropEmpty,
-        _s3: &PropEmpty,
+        args: &CFlow,
         _s4: &PropEmpty,
     ) -> CExprInfo<'a> {
         match id {
-            CIdSelected::FuncName(func) => CExprInfo::new(func.content.ret(), false),
+            CIdSelected::FuncName(func) => {
+                CExprInfo::new(func.content.ret(), false).with_flow(args.clone())
+            }
             _ => unreachable!(),
         }
     }
@@ -829,7 +1115,7 @@ impl<'a> CSProcessor<'a> {
         expr: &CExprInfo<'a>,
     ) -> Option<CExprInfo<'a>> {
         if CType::can_cast_to(expr.ty, *ty) {
-            Some(CExprInfo::new(*ty, false))
+            Some(CExprInfo::new(*ty, false).with_flow(expr.flow.value()))
         } else {
             None
         }
@@ -841,9 +1127,9 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         _s1: &PropEmpty,
-        _s2: &CExprInfo<'a>,
+        expr: &CExprInfo<'a>,
     ) -> CExprInfo<'a> {
-        CExprInfo::new(self.env.types().type_u64(), false)
+        CExprInfo::new(self.env.types().type_u64(), false).with_flow(expr.flow.unevaluated())
     }
 
     // nts expr : 10 sizeof ( typeExpr )
@@ -867,7 +1153,11 @@ impl<'a> CSProcessor<'a> {
         op: &CExprPrefixType,
         expr: &CExprInfo<'a>,
     ) -> Option<CExprInfo<'a>> {
-        match op {
+        let flow = match op {
+            CExprPrefixType::Ref => expr.flow.address(),
+            _ => expr.flow.value(),
+        };
+        let result = match op {
             CExprPrefixType::Neg => {
                 if expr.ty.is_arithmetic() {
                     Some(CExprInfo::new(expr.ty, false))
@@ -903,13 +1193,14 @@ impl<'a> CSProcessor<'a> {
                     None
                 }
             }
-        }
+        };
+        result.map(|x| x.with_flow(flow))
     }
 
     // nts args : 0
-    fn nts_args_0(&self, _g: &PropArray<CProp>, inh: &CArgs<'a>) -> Option<PropEmpty> {
+    fn nts_args_0(&self, _g: &PropArray<CProp>, inh: &CArgs<'a>) -> Option<CFlow> {
         match inh.can_zero() {
-            true => Some(PropEmpty),
+            true => Some(CFlow::default()),
             false => None,
         }
     }
@@ -919,6 +1210,11 @@ impl<'a> CSProcessor<'a> {
         inh.to_zero_more()
     }
 
+    // nts args : 1 argsOther
+    fn nts_args_1(&self, _g: &PropArray<CProp>, _inh: &CArgs<'a>, args: &CFlow) -> CFlow {
+        args.clone()
+    }
+
     // nti 0 argsOther : 0 expr
     fn nti_argsOther_0_0(&self, _g: &PropArray<CProp>, inh: &CArgs<'a>) -> Option<CSymTab<'a>> {
         match inh.can_end_now() {
@@ -933,9 +1229,9 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         inh: &CArgs<'a>,
         expr: &CExprInfo<'a>,
-    ) -> Option<PropEmpty> {
-        if inh.can_take(expr.ty) {
-            Some(PropEmpty)
+    ) -> Option<CFlow> {
+        if inh.can_take(expr, &self.strictness) {
+            Some(expr.flow.value())
         } else {
             None
         }
@@ -957,22 +1253,40 @@ impl<'a> CSProcessor<'a> {
         expr: &CExprInfo<'a>,
         _s2: &PropEmpty,
     ) -> Option<CArgs<'a>> {
-        if inh.can_take(expr.ty) {
+        if inh.can_take(expr, &self.strictness) {
             Some(inh.next_param())
         } else {
             None
         }
     }
 
+    // nts argsOther : 1 expr , argsOther
+    fn nts_argsOther_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CArgs<'a>,
+        expr: &CExprInfo<'a>,
+        _s2: &PropEmpty,
+        args: &CFlow,
+    ) -> CFlow {
+        expr.flow.union(args)
+    }
+
     // nts literal : 0 LITERAL_INT
     fn nts_literal_0(
         &self,
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
-        _s1: &PropEmpty,
+        literal: &CIntLiteral,
     ) -> CExprInfo<'a> {
         // TODO fix int literal type
-        CExprInfo::new(self.env.types().type_i32(), false)
+        match literal {
+            CIntLiteral::Zero => CExprInfo::new_null_pointer_constant(self.env.types().type_i32()),
+            CIntLiteral::NonZero => CExprInfo {
+                is_nonzero_constant: true,
+                ..CExprInfo::new(self.env.types().type_i32(), false)
+            },
+        }
     }
 
     // nts literal : 1 LITERAL_FLOAT
@@ -1002,18 +1316,18 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         decl: &CSymTab<'a>,
-    ) -> CSymTab<'a> {
-        decl.clone()
+    ) -> CStmtSyn<'a> {
+        CStmtSyn::new(decl.clone())
     }
 
     // nts stmtOrDecl : 1 stmt
     fn nts_stmtOrDecl_1(
         &self,
         _g: &PropArray<CProp>,
-        inh: &CSymTab<'a>,
-        _s1: &PropEmpty,
-    ) -> CSymTab<'a> {
-        inh.clone()
+        _inh: &CSymTab<'a>,
+        stmt: &CStmtSyn<'a>,
+    ) -> CStmtSyn<'a> {
+        stmt.clone()
     }
 
     // nti 1 stmt : 0 { stmtList }
@@ -1026,6 +1340,32 @@ impl<'a> CSProcessor<'a> {
         inh.new_scope()
     }
 
+    // nts stmt : 0 { stmtList }
+    fn nts_stmt_0(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        stmts: &CStmtSyn<'a>,
+        _s3: &PropEmpty,
+    ) -> Option<CStmtSyn<'a>> {
+        Some(CStmtSyn {
+            symtab: self.leave_scope(inh, &stmts.symtab)?,
+            ..stmts.clone()
+        })
+    }
+
+    // nts stmt : 1 expr ;
+    fn nts_stmt_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        expr: &CExprInfo<'a>,
+        _s2: &PropEmpty,
+    ) -> Option<CStmtSyn<'a>> {
+        self.eval(inh, &expr.flow).map(CStmtSyn::new)
+    }
+
     // nti 4 stmt : 2 if ( expr ) stmt
     fn nti_stmt_2_4(
         &self,
@@ -1037,12 +1377,31 @@ impl<'a> CSProcessor<'a> {
         _s4: &PropEmpty,
     ) -> Option<CSymTab<'a>> {
         if expr.ty.is_scalar() {
-            Some(inh.clone())
+            self.eval(inh, &expr.flow)
         } else {
             None
         }
     }
 
+    // nts stmt : 2 if ( expr ) stmt
+    fn nts_stmt_2(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        expr: &CExprInfo<'a>,
+        _s4: &PropEmpty,
+        stmt: &CStmtSyn<'a>,
+    ) -> CStmtSyn<'a> {
+        CStmtSyn {
+            symtab: self.maybe(&self.flow_vars.apply(inh, &expr.flow), &stmt.symtab),
+            returns: false,
+            breaks: stmt.breaks,
+            continues: stmt.continues,
+        }
+    }
+
     // nti 4 stmt : 3 if ( expr ) stmt else stmt
     fn nti_stmt_3_4(
         &self,
@@ -1054,12 +1413,53 @@ impl<'a> CSProcessor<'a> {
         _s4: &PropEmpty,
     ) -> Option<CSymTab<'a>> {
         if expr.ty.is_scalar() {
-            Some(inh.clone())
+            self.eval(inh, &expr.flow)
         } else {
             None
         }
     }
 
+    // nti 6 stmt : 3 if ( expr ) stmt else stmt
+    fn nti_stmt_3_6(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        expr: &CExprInfo<'a>,
+        _s4: &PropEmpty,
+        _s5: &CStmtSyn<'a>,
+        _s6: &PropEmpty,
+    ) -> CSymTab<'a> {
+        self.flow_vars.apply(inh, &expr.flow)
+    }
+
+    // nts stmt : 3 if ( expr ) stmt else stmt
+    fn nts_stmt_3(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &CExprInfo<'a>,
+        _s4: &PropEmpty,
+        then_stmt: &CStmtSyn<'a>,
+        _s6: &PropEmpty,
+        else_stmt: &CStmtSyn<'a>,
+    ) -> CStmtSyn<'a> {
+        let symtab = if self.strictness.flow_analysis() {
+            flow::join(&then_stmt.symtab, &else_stmt.symtab)
+        } else {
+            then_stmt.symtab.clone()
+        };
+        CStmtSyn {
+            symtab,
+            returns: then_stmt.returns && else_stmt.returns,
+            breaks: then_stmt.breaks || else_stmt.breaks,
+            continues: then_stmt.continues || else_stmt.continues,
+        }
+    }
+
     // nts stmt : 4 break ;
     fn nts_stmt_4(
         &self,
@@ -1067,9 +1467,12 @@ impl<'a> CSProcessor<'a> {
         inh: &CSymTab<'a>,
         _s1: &PropEmpty,
         _s2: &PropEmpty,
-    ) -> Option<PropEmpty> {
+    ) -> Option<CStmtSyn<'a>> {
         match inh.get(self.env.break_label()) {
-            Some(_) => Some(PropEmpty),
+            Some(_) => Some(CStmtSyn {
+                breaks: self.checks_return(),
+                ..CStmtSyn::new(inh.clone())
+            }),
             None => None,
         }
     }
@@ -1081,9 +1484,12 @@ impl<'a> CSProcessor<'a> {
         inh: &CSymTab<'a>,
         _s1: &PropEmpty,
         _s2: &PropEmpty,
-    ) -> Option<PropEmpty> {
+    ) -> Option<CStmtSyn<'a>> {
         match inh.get(self.env.continue_label()) {
-            Some(_) => Some(PropEmpty),
+            Some(_) => Some(CStmtSyn {
+                continues: self.checks_return(),
+                ..CStmtSyn::new(inh.clone())
+            }),
             None => None,
         }
     }
@@ -1105,20 +1511,28 @@ impl<'a> CSProcessor<'a> {
     fn nts_stmt_6(
         &self,
         _g: &PropArray<CProp>,
-        _inh: &CSymTab<'a>,
+        inh: &CSymTab<'a>,
         _s1: &PropEmpty,
-        _s2: &PropEmpty,
+        stmt: &CStmtSyn<'a>,
         _s3: &PropEmpty,
         _s4: &PropEmpty,
         expr: &CExprInfo<'a>,
         _s6: &PropEmpty,
         _s7: &PropEmpty,
-    ) -> Option<PropEmpty> {
-        if expr.ty.is_scalar() {
-            Some(PropEmpty)
-        } else {
-            None
+    ) -> Option<CStmtSyn<'a>> {
+        if !expr.ty.is_scalar() {
+            return None;
         }
+        let after = self.eval(&stmt.symtab, &expr.flow)?;
+        let symtab = if self.strictness.flow_analysis() {
+            flow::inherit_flags(inh, &after, false)
+        } else {
+            inh.clone()
+        };
+        Some(CStmtSyn {
+            returns: stmt.returns || self.loops_forever(expr.is_nonzero_constant, stmt),
+            ..CStmtSyn::new(symtab)
+        })
     }
 
     // nti 4 stmt : 7 while ( expr ) stmt
@@ -1132,7 +1546,7 @@ impl<'a> CSProcessor<'a> {
         _s4: &PropEmpty,
     ) -> Option<CSymTab<'a>> {
         if expr.ty.is_scalar() {
-            Some(inh.extend_multiple(
+            Some(self.eval(inh, &expr.flow)?.extend_multiple(
                 [self.env.break_label(), self.env.continue_label()].into_iter(),
                 self.void_info.clone(),
             ))
@@ -1141,6 +1555,23 @@ impl<'a> CSProcessor<'a> {
         }
     }
 
+    // nts stmt : 7 while ( expr ) stmt
+    fn nts_stmt_7(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        expr: &CExprInfo<'a>,
+        _s4: &PropEmpty,
+        stmt: &CStmtSyn<'a>,
+    ) -> CStmtSyn<'a> {
+        CStmtSyn {
+            returns: self.loops_forever(expr.is_nonzero_constant, stmt),
+            ..CStmtSyn::new(self.maybe(&self.flow_vars.apply(inh, &expr.flow), &stmt.symtab))
+        }
+    }
+
     // nti 2 stmt : 8 for ( for1 for2 ; for3 ) stmt
     fn nti_stmt_8_2(
         &self,
@@ -1172,10 +1603,10 @@ impl<'a> CSProcessor<'a> {
         _s1: &PropEmpty,
         _s2: &PropEmpty,
         for1: &CSymTab<'a>,
-        _s4: &PropEmpty,
+        for2: &CForCond,
         _s5: &PropEmpty,
     ) -> CSymTab<'a> {
-        for1.clone()
+        self.flow_vars.apply(for1, &for2.flow)
     }
 
     // nti 7 stmt : 8 for ( for1 for2 ; for3 ) stmt
@@ -1186,17 +1617,48 @@ impl<'a> CSProcessor<'a> {
         _s1: &PropEmpty,
         _s2: &PropEmpty,
         for1: &CSymTab<'a>,
-        _s4: &PropEmpty,
+        for2: &CForCond,
         _s5: &PropEmpty,
-        _s6: &PropEmpty,
+        _s6: &CFlow,
         _s7: &PropEmpty,
     ) -> CSymTab<'a> {
-        for1.extend_multiple(
+        self.flow_vars.apply(for1, &for2.flow).extend_multiple(
             [self.env.break_label(), self.env.continue_label()].into_iter(),
             self.void_info.clone(),
         )
     }
 
+    // nts stmt : 8 for ( for1 for2 ; for3 ) stmt
+    fn nts_stmt_8(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        for1: &CSymTab<'a>,
+        for2: &CForCond,
+        _s5: &PropEmpty,
+        for3: &CFlow,
+        _s7: &PropEmpty,
+        stmt: &CStmtSyn<'a>,
+    ) -> Option<CStmtSyn<'a>> {
+        let returns = self.loops_forever(for2.always, stmt);
+        if !self.strictness.flow_analysis() {
+            return Some(CStmtSyn {
+                returns,
+                ..CStmtSyn::new(inh.clone())
+            });
+        }
+        let cond = self.flow_vars.apply(for1, &for2.flow);
+        let body = flow::inherit_flags(&cond, &stmt.symtab, false);
+        let step = self.eval(&body, for3)?;
+        let symtab = self.leave_scope(inh, &flow::join(&cond, &step))?;
+        Some(CStmtSyn {
+            returns,
+            ..CStmtSyn::new(symtab)
+        })
+    }
+
     // nti 5 stmt : 9 switch ( expr ) { switchBlock }
     fn nti_stmt_9_5(
         &self,
@@ -1210,7 +1672,8 @@ impl<'a> CSProcessor<'a> {
     ) -> Option<CSymTab<'a>> {
         if expr.ty.is_integer() {
             Some(
-                inh.new_scope()
+                self.eval(inh, &expr.flow)?
+                    .new_scope()
                     .extend(self.env.break_label(), self.void_info.clone()),
             )
         } else {
@@ -1218,16 +1681,41 @@ impl<'a> CSProcessor<'a> {
         }
     }
 
+    // nts stmt : 9 switch ( expr ) { switchBlock }
+    fn nts_stmt_9(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        expr: &CExprInfo<'a>,
+        _s4: &PropEmpty,
+        _s5: &PropEmpty,
+        block: &CSwitchBlock<'a>,
+        _s7: &PropEmpty,
+    ) -> Option<CStmtSyn<'a>> {
+        let after = self.flow_vars.apply(inh, &expr.flow);
+        let symtab = self.leave_scope(&after, &block.rest.symtab)?;
+        Some(CStmtSyn {
+            returns: block.has_default && block.labels_return,
+            continues: block.rest.continues,
+            ..CStmtSyn::new(self.maybe(&after, &symtab))
+        })
+    }
+
     // nts stmt : 12 return ;
     fn nts_stmt_12(
         &self,
         _g: &PropArray<CProp>,
-        _inh: &CSymTab<'a>,
+        inh: &CSymTab<'a>,
         _s1: &PropEmpty,
         _s2: &PropEmpty,
-    ) -> Option<PropEmpty> {
-        if self.env.current_func().ret() == self.env.types().type_void() {
-            Some(PropEmpty)
+    ) -> Option<CStmtSyn<'a>> {
+        if self.return_type(inh)? == self.env.types().type_void() {
+            Some(CStmtSyn {
+                returns: self.checks_return(),
+                ..CStmtSyn::new(inh.clone())
+            })
         } else {
             None
         }
@@ -1240,7 +1728,7 @@ impl<'a> CSProcessor<'a> {
         inh: &CSymTab<'a>,
         _s1: &PropEmpty,
     ) -> Option<CSymTab<'a>> {
-        if self.env.current_func().ret() != self.env.types().type_void() {
+        if self.return_type(inh)? != self.env.types().type_void() {
             Some(inh.clone())
         } else {
             None
@@ -1251,26 +1739,60 @@ impl<'a> CSProcessor<'a> {
     fn nts_stmt_13(
         &self,
         _g: &PropArray<CProp>,
-        _inh: &CSymTab<'a>,
+        inh: &CSymTab<'a>,
         _s1: &PropEmpty,
         expr: &CExprInfo<'a>,
         _s3: &PropEmpty,
-    ) -> Option<PropEmpty> {
-        if CType::can_cast_to(expr.ty, self.env.current_func().ret()) {
-            Some(PropEmpty)
+    ) -> Option<CStmtSyn<'a>> {
+        if CType::can_convert_to(expr, self.return_type(inh)?, &self.strictness) {
+            Some(CStmtSyn {
+                returns: self.checks_return(),
+                ..CStmtSyn::new(self.eval(inh, &expr.flow)?)
+            })
         } else {
             None
         }
     }
 
+    // nts switchBlock : 0
+    fn nts_switchBlock_0(&self, _g: &PropArray<CProp>, inh: &CSymTab<'a>) -> CSwitchBlock<'a> {
+        CSwitchBlock {
+            rest: CStmtSyn::new(inh.clone()),
+            labels_return: true,
+            has_default: false,
+        }
+    }
+
     // nti 1 switchBlock : 1 switchBlockContent switchBlock
     fn nti_switchBlock_1_1(
         &self,
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
-        block: &CSymTab<'a>,
+        content: &CSwitchContent<'a>,
     ) -> CSymTab<'a> {
-        block.clone()
+        content.symtab().clone()
+    }
+
+    // nts switchBlock : 1 switchBlockContent switchBlock
+    fn nts_switchBlock_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        content: &CSwitchContent<'a>,
+        block: &CSwitchBlock<'a>,
+    ) -> CSwitchBlock<'a> {
+        match content {
+            CSwitchContent::Label { default, .. } if self.checks_return() => CSwitchBlock {
+                rest: block.rest.clone(),
+                labels_return: block.labels_return && block.rest.returns,
+                has_default: block.has_default || *default,
+            },
+            CSwitchContent::Label { .. } => block.clone(),
+            CSwitchContent::Stmt(stmt) => CSwitchBlock {
+                rest: stmt.then(&block.rest),
+                ..block.clone()
+            },
+        }
     }
 
     // nts switchBlockContent : 0 case INT_LITERAL :
@@ -1279,10 +1801,13 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         inh: &CSymTab<'a>,
         _s1: &PropEmpty,
-        _s2: &PropEmpty,
+        _s2: &CIntLiteral,
         _s3: &PropEmpty,
-    ) -> CSymTab<'a> {
-        inh.clone()
+    ) -> CSwitchContent<'a> {
+        CSwitchContent::Label {
+            symtab: inh.clone(),
+            default: false,
+        }
     }
 
     // nts switchBlockContent : 1 default :
@@ -1292,8 +1817,11 @@ impl<'a> CSProcessor<'a> {
         inh: &CSymTab<'a>,
         _s1: &PropEmpty,
         _s2: &PropEmpty,
-    ) -> CSymTab<'a> {
-        inh.clone()
+    ) -> CSwitchContent<'a> {
+        CSwitchContent::Label {
+            symtab: inh.clone(),
+            default: true,
+        }
     }
 
     // nts switchBlockContent : 2 decl
@@ -1302,18 +1830,23 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
         decl: &CSymTab<'a>,
-    ) -> CSymTab<'a> {
-        decl.clone()
+    ) -> CSwitchContent<'a> {
+        CSwitchContent::Stmt(CStmtSyn::new(decl.clone()))
     }
 
     // nts switchBlockContent : 3 stmt
     fn nts_switchBlockContent_3(
         &self,
         _g: &PropArray<CProp>,
-        inh: &CSymTab<'a>,
-        _s1: &PropEmpty,
-    ) -> CSymTab<'a> {
-        inh.clone()
+        _inh: &CSymTab<'a>,
+        stmt: &CStmtSyn<'a>,
+    ) -> CSwitchContent<'a> {
+        CSwitchContent::Stmt(stmt.clone())
+    }
+
+    // nts stmtList : 0
+    fn nts_stmtList_0(&self, _g: &PropArray<CProp>, inh: &CSymTab<'a>) -> CStmtSyn<'a> {
+        CStmtSyn::new(inh.clone())
     }
 
     // nti 1 stmtList : 1 stmtOrDecl stmtList
@@ -1321,9 +1854,20 @@ impl<'a> CSProcessor<'a> {
         &self,
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
-        stmt: &CSymTab<'a>,
+        stmt: &CStmtSyn<'a>,
     ) -> CSymTab<'a> {
-        stmt.clone()
+        stmt.symtab.clone()
+    }
+
+    // nts stmtList : 1 stmtOrDecl stmtList
+    fn nts_stmtList_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        stmt: &CStmtSyn<'a>,
+        stmts: &CStmtSyn<'a>,
+    ) -> CStmtSyn<'a> {
+        stmt.then(stmts)
     }
 
     // nti 1 decl : 0 type declList ;
@@ -1415,14 +1959,14 @@ impl<'a> CSProcessor<'a> {
         _inh: &CDeclInh<'a>,
         op: &CDeclIdSyn<'a>,
         _s2: &PropEmpty,
-        _s3: &PropEmpty,
-    ) -> CSymTab<'a> {
-        op.symtab.clone()
+        init: &CFlow,
+    ) -> Option<CSymTab<'a>> {
+        self.eval(&op.symtab, init)
     }
 
     // nti 0 declId : 0 IDENTIFIER
     fn nti_declId_0_0(&self, _g: &PropArray<CProp>, inh: &CDeclInh<'a>) -> Option<CIdSelector<'a>> {
-        if inh.decl_type.is_complete() || (inh.has_init && inh.decl_type.is_array()) {
+        if inh.decl_type.is_complete() || (inh.has_init && inh.decl_type.is_incomplete_array()) {
             Some(CIdSelector::NewIdentifier(inh.symtab.clone()))
         } else {
             None
@@ -1438,7 +1982,8 @@ impl<'a> CSProcessor<'a> {
     ) -> CDeclIdSyn<'a> {
         match id {
             CIdSelected::NewIdentifier(id) => {
-                let symtab = inh.symtab.extend(*id, VarInfo::new(inh.decl_type));
+                let info = self.local_info(*id, inh.decl_type, inh.has_init);
+                let symtab = inh.symtab.extend(*id, info);
                 CDeclIdSyn::new(symtab, inh.decl_type)
             }
             _ => unreachable!(),
@@ -1556,7 +2101,7 @@ impl<'a> CSProcessor<'a> {
         _inh: &CDeclInh<'a>,
         decl_id: &CDeclIdSyn<'a>,
         _s2: &PropEmpty,
-        _s3: &PropEmpty,
+        _s3: &CIntLiteral,
         _s4: &PropEmpty,
     ) -> CDeclIdSyn<'a> {
         decl_id.clone()
@@ -1568,7 +2113,7 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         inh: &CDeclInh<'a>,
     ) -> Option<CSymTab<'a>> {
-        if inh.decl_type.is_array() {
+        if inh.decl_type.is_array() || inh.decl_type.is_incomplete_array() {
             None
         } else {
             Some(inh.symtab.clone())
@@ -1581,9 +2126,9 @@ impl<'a> CSProcessor<'a> {
         _g: &PropArray<CProp>,
         inh: &CDeclInh<'a>,
         expr: &CExprInfo<'a>,
-    ) -> Option<PropEmpty> {
-        if CType::can_cast_to(expr.ty, inh.decl_type) {
-            Some(PropEmpty)
+    ) -> Option<CFlow> {
+        if CType::can_convert_to(expr, inh.decl_type, &self.strictness) {
+            Some(expr.flow.value())
         } else {
             None
         }
@@ -1596,9 +2141,9 @@ impl<'a> CSProcessor<'a> {
         inh: &CDeclInh<'a>,
         _s1: &PropEmpty,
         _s2: &PropEmpty,
-    ) -> Option<PropEmpty> {
+    ) -> Option<CFlow> {
         if inh.decl_type.is_array() {
-            Some(PropEmpty)
+            Some(CFlow::default())
         } else {
             None
         }
@@ -1622,6 +2167,40 @@ impl<'a> CSProcessor<'a> {
         }
     }
 
+    // nts initializer : 2 { initializerList }
+    fn nts_initializer_2(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CDeclInh<'a>,
+        _s1: &PropEmpty,
+        list: &CFlow,
+        _s3: &PropEmpty,
+    ) -> CFlow {
+        list.clone()
+    }
+
+    // nts initializerList : 0 initializer
+    fn nts_initializerList_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CDeclInh<'a>,
+        init: &CFlow,
+    ) -> CFlow {
+        init.clone()
+    }
+
+    // nts initializerList : 1 initializerList , initializerList
+    fn nts_initializerList_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CDeclInh<'a>,
+        list1: &CFlow,
+        _s2: &PropEmpty,
+        list2: &CFlow,
+    ) -> CFlow {
+        list1.union(list2)
+    }
+
     // nts for1 : 0 ;
     fn nts_for1_0(&self, _g: &PropArray<CProp>, inh: &CSymTab<'a>, _s1: &PropEmpty) -> CSymTab<'a> {
         inh.clone()
@@ -1647,26 +2226,47 @@ impl<'a> CSProcessor<'a> {
         &self,
         _g: &PropArray<CProp>,
         inh: &CSymTab<'a>,
-        _s1: &CExprInfo<'a>,
+        expr: &CExprInfo<'a>,
         _s2: &PropEmpty,
-    ) -> CSymTab<'a> {
-        inh.clone()
+    ) -> Option<CSymTab<'a>> {
+        self.eval(inh, &expr.flow)
+    }
+
+    // nts for2 : 0
+    fn nts_for2_0(&self, _g: &PropArray<CProp>, _inh: &CSymTab<'a>) -> CForCond {
+        CForCond {
+            flow: CFlow::default(),
+            always: true,
+        }
     }
 
     // nts for2 : 1 expr
     fn nts_for2_1(
         &self,
         _g: &PropArray<CProp>,
-        _inh: &CSymTab<'a>,
+        inh: &CSymTab<'a>,
         expr: &CExprInfo<'a>,
-    ) -> Option<PropEmpty> {
-        if expr.ty.is_scalar() {
-            Some(PropEmpty)
+    ) -> Option<CForCond> {
+        if expr.ty.is_scalar() && self.reads_initialized(inh, &expr.flow) {
+            Some(CForCond {
+                flow: expr.flow.value(),
+                always: expr.is_nonzero_constant,
+            })
         } else {
             None
         }
     }
 
+    // nts for3 : 0
+    fn nts_for3_0(&self, _g: &PropArray<CProp>, _inh: &CSymTab<'a>) -> CFlow {
+        CFlow::default()
+    }
+
+    // nts for3 : 1 expr
+    fn nts_for3_1(&self, _g: &PropArray<CProp>, _inh: &CSymTab<'a>, expr: &CExprInfo<'a>) -> CFlow {
+        expr.flow.value()
+    }
+
     // nts typeBasic : 0 signed
     fn nts_typeBasic_0(
         &self,
@@ -1825,22 +2425,322 @@ impl<'a> CSProcessor<'a> {
     }
 
     // nti 0 functionBody : 0 stmtList
-    fn nti_functionBody_0_0(&self, _g: &PropArray<CProp>, _inh: &PropEmpty) -> CSymTab<'a> {
-        CSymTab::from_map(
+    fn nti_functionBody_0_0(&self, _g: &PropArray<CProp>, _inh: &PropEmpty) -> Option<CSymTab<'a>> {
+        let func = self.env.current_func()?;
+        let globals: CSymTab<'a> = self
+            .env
+            .globals()
+            .iter()
+            .map(|(k, v)| (*k, VarInfo::new(*v)))
+            .collect();
+        Some(
             self.env
-                .globals()
+                .params()
                 .iter()
-                .map(|(k, v)| (*k, VarInfo::new(*v).inner_scope()))
-                .chain(
-                    self.env
-                        .params()
-                        .iter()
-                        .map(|(k, v)| (*k, VarInfo::new(*v))),
-                )
-                .collect(),
+                .map(|(k, v)| (*k, VarInfo::new(*v)))
+                .chain([(self.env.return_label(), VarInfo::new(func.ret()))])
+                .fold(globals.new_scope(), |symtab, (k, v)| symtab.extend(k, v)),
         )
     }
 
+    // nts functionBody : 0 stmtList
+    fn nts_functionBody_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &PropEmpty,
+        stmts: &CStmtSyn<'a>,
+    ) -> Option<PropEmpty> {
+        if !self.strictness.unused_variables && flow::has_unused(&stmts.symtab) {
+            return None;
+        }
+        let name = self.env.current_func_name()?;
+        let func = self.env.current_func()?;
+        if self.falls_off(name, func.ret(), stmts) {
+            None
+        } else {
+            Some(PropEmpty)
+        }
+    }
+
+    // nti 1 param : 0 type declId
+    fn nti_param_0_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        ty: &CTypeComposed,
+    ) -> CDeclInh<'a> {
+        let ty = self.env.types().construct_from_composed(ty);
+        // Parameters are initialized by the caller, which also lets them
+        // have incomplete array types.
+        CDeclInh::new(inh.clone(), ty, true)
+    }
+
+    // nts param : 0 type declId
+    fn nts_param_0(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &CTypeComposed,
+        decl: &CDeclIdSyn<'a>,
+    ) -> Option<CParams<'a>> {
+        // Array parameters are pointers.
+        let ty = match decl.decl_type.get_slice() {
+            Some(element) if decl.decl_type.is_array() || decl.decl_type.is_incomplete_array() => {
+                self.env.types().derive_pointer(element)?
+            }
+            _ => decl.decl_type,
+        };
+        let (name, _) = decl
+            .symtab
+            .iter_current()
+            .find(|(name, _)| !inh.is_current(**name))?;
+        Some(CParams {
+            symtab: inh.extend(*name, VarInfo::new(ty)),
+            types: vec![ty],
+            unnamed: false,
+        })
+    }
+
+    // nts param : 1 typeExpr
+    fn nts_param_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        ty: &CTypeRef<'a>,
+    ) -> Option<CParams<'a>> {
+        // `(void)` is an empty parameter list.
+        if ty.is_void() {
+            None
+        } else {
+            Some(CParams {
+                symtab: inh.clone(),
+                types: vec![*ty],
+                unnamed: true,
+            })
+        }
+    }
+
+    // nts paramList : 0 param
+    fn nts_paramList_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        param: &CParams<'a>,
+    ) -> CParams<'a> {
+        param.clone()
+    }
+
+    // nti 2 paramList : 1 param , paramList
+    fn nti_paramList_1_2(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        param: &CParams<'a>,
+        _s2: &PropEmpty,
+    ) -> CSymTab<'a> {
+        param.symtab.clone()
+    }
+
+    // nts paramList : 1 param , paramList
+    fn nts_paramList_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        param: &CParams<'a>,
+        _s2: &PropEmpty,
+        rest: &CParams<'a>,
+    ) -> CParams<'a> {
+        CParams {
+            symtab: rest.symtab.clone(),
+            types: param
+                .types
+                .iter()
+                .chain(rest.types.iter())
+                .copied()
+                .collect(),
+            unnamed: param.unnamed || rest.unnamed,
+        }
+    }
+
+    // nts params : 0
+    fn nts_params_0(&self, _g: &PropArray<CProp>, inh: &CSymTab<'a>) -> CParams<'a> {
+        CParams::new(inh.clone())
+    }
+
+    // nts params : 1 void
+    fn nts_params_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> CParams<'a> {
+        CParams::new(inh.clone())
+    }
+
+    // nts params : 2 paramList
+    fn nts_params_2(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        params: &CParams<'a>,
+    ) -> CParams<'a> {
+        params.clone()
+    }
+
+    // nti 1 funcHead : 0 typeExpr IDENTIFIER ( params )
+    fn nti_funcHead_0_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &CTypeRef<'a>,
+    ) -> CIdSelector<'a> {
+        CIdSelector::FuncDecl(inh.clone())
+    }
+
+    // nti 3 funcHead : 0 typeExpr IDENTIFIER ( params )
+    fn nti_funcHead_0_3(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        _s1: &CTypeRef<'a>,
+        _s2: &CIdSelected<'a>,
+        _s3: &PropEmpty,
+    ) -> CSymTab<'a> {
+        inh.new_scope()
+    }
+
+    // nts funcHead : 0 typeExpr IDENTIFIER ( params )
+    fn nts_funcHead_0(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        ret: &CTypeRef<'a>,
+        id: &CIdSelected<'a>,
+        _s3: &PropEmpty,
+        params: &CParams<'a>,
+        _s5: &PropEmpty,
+    ) -> Option<CFuncHead<'a>> {
+        let name = match id {
+            CIdSelected::NewIdentifier(id) => *id,
+            _ => unreachable!(),
+        };
+        let ty = self
+            .env
+            .types()
+            .derive_func(*ret, params.types.clone(), false);
+        // A function may only be declared again with the same type.
+        match inh.get(name) {
+            Some(info) if inh.is_current(name) && info.ty != ty => None,
+            _ => Some(CFuncHead {
+                name,
+                ty,
+                params: params.clone(),
+            }),
+        }
+    }
+
+    // nts externalDecl : 0 decl
+    fn nts_externalDecl_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        decls: &CSymTab<'a>,
+    ) -> CSymTab<'a> {
+        flow::settle(decls)
+    }
+
+    // nts externalDecl : 1 funcHead ;
+    fn nts_externalDecl_1(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        head: &CFuncHead<'a>,
+        _s2: &PropEmpty,
+    ) -> CSymTab<'a> {
+        inh.extend(head.name, VarInfo::new(head.ty))
+    }
+
+    // nti 2 externalDecl : 2 funcHead { stmtList }
+    fn nti_externalDecl_2_2(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        head: &CFuncHead<'a>,
+        _s2: &PropEmpty,
+    ) -> Option<CSymTab<'a>> {
+        if head.params.unnamed {
+            return None;
+        }
+        let ret = self.env.types().get_func_content(head.ty)?.ret();
+        let symtab = head
+            .params
+            .symtab
+            .extend(self.env.return_label(), VarInfo::new(ret));
+        // The function may call itself unless a parameter hides it.
+        match symtab.get(head.name) {
+            Some(_) if symtab.is_current(head.name) => Some(symtab),
+            _ => Some(symtab.extend_outer(head.name, VarInfo::new(head.ty))),
+        }
+    }
+
+    // nts externalDecl : 2 funcHead { stmtList }
+    fn nts_externalDecl_2(
+        &self,
+        _g: &PropArray<CProp>,
+        inh: &CSymTab<'a>,
+        head: &CFuncHead<'a>,
+        _s2: &PropEmpty,
+        stmts: &CStmtSyn<'a>,
+        _s4: &PropEmpty,
+    ) -> Option<CSymTab<'a>> {
+        if !self.strictness.unused_variables && flow::has_unused(&stmts.symtab) {
+            return None;
+        }
+        let ret = self.env.types().get_func_content(head.ty)?.ret();
+        if self.falls_off(head.name, ret, stmts) {
+            None
+        } else {
+            Some(inh.extend(head.name, VarInfo::new(head.ty)))
+        }
+    }
+
+    // nti 1 externalDeclList : 1 externalDecl externalDeclList
+    fn nti_externalDeclList_1_1(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &CSymTab<'a>,
+        decl: &CSymTab<'a>,
+    ) -> CSymTab<'a> {
+        decl.clone()
+    }
+
+    // nti 0 translationUnit : 0 functionBody
+    fn nti_translationUnit_0_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.env.current_func().map(|_| PropEmpty)
+    }
+
+    // nti 0 translationUnit : 1 externalDeclList
+    fn nti_translationUnit_1_0(
+        &self,
+        _g: &PropArray<CProp>,
+        _inh: &PropEmpty,
+    ) -> Option<CSymTab<'a>> {
+        match self.env.current_func() {
+            Some(_) => None,
+            None => Some(
+                self.env
+                    .globals()
+                    .iter()
+                    .map(|(k, v)| (*k, VarInfo::new(*v)))
+                    .collect(),
+            ),
+        }
+    }
+
     fn sts_IDENTIFIER(
         &self,
         _g: &PropArray<CProp>,
@@ -1870,7 +2770,11 @@ impl<'a> CSProcessor<'a> {
                             }
                         }
                         None => {
-                            if sym_tab.get(literal).is_none() {
+                            if let Some(func) = self.declared_func(sym_tab, literal) {
+                                vec![CIdSelected::FuncName(func)]
+                            } else if self.strictness.implicit_function_decl
+                                && sym_tab.get(literal).is_none()
+                            {
                                 match self.env.default_functions().get(&literal) {
                                     Some(func) => vec![CIdSelected::FuncName(func.clone())],
                                     None => vec![],
@@ -1902,12 +2806,23 @@ impl<'a> CSProcessor<'a> {
                             None
                         }
                     })
-                    .chain(self.env.default_functions().values().flat_map(|func| {
-                        match sym_tab.get(func.name) {
-                            Some(_) => None,
-                            None => Some(CIdSelected::FuncName(func.clone())),
-                        }
-                    }))
+                    .chain(
+                        sym_tab
+                            .iter()
+                            .filter(|(name, _)| !self.env.functions().contains_key(*name))
+                            .flat_map(|(name, _)| self.declared_func(sym_tab, *name))
+                            .map(CIdSelected::FuncName),
+                    )
+                    .chain(
+                        self.env
+                            .default_functions()
+                            .values()
+                            .filter(|_| self.strictness.implicit_function_decl)
+                            .flat_map(|func| match sym_tab.get(func.name) {
+                                Some(_) => None,
+                                None => Some(CIdSelected::FuncName(func.clone())),
+                            }),
+                    )
                     .collect(),
             },
             CIdSelector::Identifier(sym_tab) => match literal {
@@ -1929,34 +2844,45 @@ impl<'a> CSProcessor<'a> {
                     })
                     .collect(),
             },
+            CIdSelector::FuncDecl(sym_tab) => {
+                let can_declare = |name: StringRef<'a>| match sym_tab.get(name) {
+                    Some(_) => {
+                        !sym_tab.is_current(name) || self.declared_func(sym_tab, name).is_some()
+                    }
+                    None => true,
+                };
+                match literal {
+                    Some(literal) => {
+                        let literal = self.env.str_pool().get(literal).unwrap();
+                        if can_declare(literal) {
+                            vec![CIdSelected::NewIdentifier(literal)]
+                        } else {
+                            vec![]
+                        }
+                    }
+                    None => self
+                        .env
+                        .identifiers()
+                        .iter()
+                        .filter(|id| can_declare(**id))
+                        .map(|id| CIdSelected::NewIdentifier(*id))
+                        .collect(),
+                }
+            }
             CIdSelector::NewIdentifier(sym_tab) => {
                 if let Some(literal) = literal {
                     let literal = self.env.str_pool().get(literal).unwrap();
                     match sym_tab.get(literal) {
-                        Some(s) => {
-                            if s.current_scope() {
-                                vec![]
-                            } else {
-                                vec![CIdSelected::NewIdentifier(literal)]
-                            }
-                        }
-                        None => {
-                            vec![CIdSelected::NewIdentifier(literal)]
-                        }
+                        Some(_) if sym_tab.is_current(literal) => vec![],
+                        _ => vec![CIdSelected::NewIdentifier(literal)],
                     }
                 } else {
                     self.env
                         .identifiers()
                         .iter()
                         .flat_map(|id| match sym_tab.get(*id) {
-                            Some(s) => {
-                                if s.current_scope() {
-                                    None
-                                } else {
-                                    Some(CIdSelected::NewIdentifier(*id))
-                                }
-                            }
-                            None => Some(CIdSelected::NewIdentifier(*id)),
+                            Some(_) if sym_tab.is_current(*id) => None,
+                            _ => Some(CIdSelected::NewIdentifier(*id)),
                         })
                         .collect()
                 }
@@ -1982,16 +2908,19 @@ impl<'a> CSProcessor<'a> {
         &self,
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
-        _literal: Option<&str>,
-    ) -> PropEmpty {
-        PropEmpty
+        literal: Option<&str>,
+    ) -> CIntLiteral {
+        match literal {
+            Some(literal) => CIntLiteral::from_literal(literal),
+            None => CIntLiteral::Zero,
+        }
     }
 
     fn stg_LITERAL_INT(
         &self,
         _g: &PropArray<CProp>,
         _inh: &CSymTab<'a>,
-        _syn: &PropEmpty,
+        _syn: &CIntLiteral,
         literal: Option<&str>,
     ) -> String {
         match literal {
@@ -0,0 +1,23 @@
+use clap::ValueEnum;
+
+/// Versions of the built-in standard library environment. A released
+/// version never changes, so fix results stay reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CStdlib {
+    /// Only the functions declared in the env are known.
+    #[default]
+    None,
+    /// Prototypes of `stdio.h`, `stdlib.h`, `string.h`, `math.h` and
+    /// `ctype.h`.
+    V1,
+}
+
+impl CStdlib {
+    /// The env declaring the library, in the same format as per-task envs.
+    pub fn env(&self) -> Option<&'static str> {
+        match self {
+            CStdlib::None => None,
+            CStdlib::V1 => Some(include_str!("stdlib/v1.env")),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use clap::ValueEnum;
+
+/// Preset strictness levels, named after the compiler settings they mimic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CStrictnessLevel {
+    /// gcc with default flags in C89 mode: questionable conversions are
+    /// only warnings.
+    #[default]
+    C89Permissive,
+    /// C99, where calling an undeclared function is an error.
+    C99,
+    /// gcc with `-Werror`: implicit integer/pointer conversions, conversions
+    /// between unrelated pointers and conversions discarding `const` are
+    /// errors as well.
+    GccWerror,
+    /// gcc with `-Wall -Werror`: reading a local before it is initialized,
+    /// leaving a local unused and reaching the end of a function without
+    /// returning a value are errors too.
+    GccWallWerror,
+}
+
+/// Which implicit conversions and uses of locals the C semantic processor
+/// accepts. Explicit casts and lvalue requirements are checked the same way
+/// at every level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CStrictness {
+    /// Calls to undeclared functions declare them implicitly
+    /// (`-Wimplicit-function-declaration`).
+    pub implicit_function_decl: bool,
+    /// Integers and pointers convert to each other implicitly, and may be
+    /// compared with each other (`-Wint-conversion`). The null pointer
+    /// constant `0` converts to any pointer regardless.
+    pub int_conversion: bool,
+    /// Pointers to unrelated types convert to each other implicitly, and may
+    /// be compared with each other (`-Wincompatible-pointer-types`).
+    pub incompatible_pointer_types: bool,
+    /// Pointers to `const` convert implicitly to pointers to non-`const`
+    /// (`-Wdiscarded-qualifiers`).
+    pub discarded_qualifiers: bool,
+    /// Locals may be read before they are definitely initialized
+    /// (`-Wuninitialized`).
+    pub uninitialized_reads: bool,
+    /// Locals may be declared without ever being used (`-Wunused-variable`,
+    /// `-Wunused-but-set-variable`).
+    pub unused_variables: bool,
+    /// Control may reach the end of a function returning a value
+    /// (`-Wreturn-type`). Reaching the end of `main` is always accepted.
+    pub missing_return: bool,
+}
+
+impl CStrictness {
+    pub fn new(level: CStrictnessLevel) -> Self {
+        match level {
+            CStrictnessLevel::C89Permissive => Self {
+                implicit_function_decl: true,
+                int_conversion: true,
+                incompatible_pointer_types: true,
+                discarded_qualifiers: true,
+                uninitialized_reads: true,
+                unused_variables: true,
+                missing_return: true,
+            },
+            CStrictnessLevel::C99 => Self {
+                implicit_function_decl: false,
+                ..Self::new(CStrictnessLevel::C89Permissive)
+            },
+            CStrictnessLevel::GccWerror => Self {
+                implicit_function_decl: false,
+                int_conversion: false,
+                incompatible_pointer_types: false,
+                discarded_qualifiers: false,
+                ..Self::new(CStrictnessLevel::C89Permissive)
+            },
+            CStrictnessLevel::GccWallWerror => Self {
+                uninitialized_reads: false,
+                unused_variables: false,
+                missing_return: false,
+                ..Self::new(CStrictnessLevel::GccWerror)
+            },
+        }
+    }
+
+    /// Whether locals need to be tracked through statements at all. When
+    /// they are not, every local counts as initialized and used.
+    pub fn flow_analysis(&self) -> bool {
+        !self.uninitialized_reads || !self.unused_variables
+    }
+}
+
+impl Default for CStrictness {
+    fn default() -> Self {
+        Self::new(CStrictnessLevel::default())
+    }
+}
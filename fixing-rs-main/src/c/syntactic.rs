@@ -1,4 +1,8 @@
-use fixing_rs_base::{props::PropEmpty, union_prop};
+use fixing_rs_base::{
+    grammar::{Grammar, GrammarSymbolsRef, SymbolRef},
+    props::PropEmpty,
+    union_prop,
+};
 
 pub enum CExprOps {
     Plus,
@@ -51,11 +55,36 @@ union_prop!(
     }
 );
 
-pub struct CGProcessor;
+/// Builds the edges of either function bodies or translation units, by the
+/// start rule the input is fixed with, but never of both.
+pub struct CGProcessor {
+    derived: Vec<bool>,
+}
+
+impl CGProcessor {
+    pub fn new<'a>(grammar: &'a Grammar<'a>, translation_unit: bool) -> Self {
+        let GrammarSymbolsRef {
+            non_terminals,
+            start_symbol,
+            ..
+        } = grammar.get_symbol_ref();
+        let body = match translation_unit {
+            true => "externalDeclList",
+            false => "functionBody",
+        };
+        let mut derived = grammar.derivable_from(non_terminals[body]);
+        derived[start_symbol.symbol_id()] = true;
+        Self { derived }
+    }
+}
 
 #[impl_syntactic_processor(g_prop = "CProp", grammar_file = "fixing-rs-main/src/c/c_grammar")]
 #[allow(non_snake_case)]
 impl CGProcessor {
+    fn derives(&self, symbol: SymbolRef<'_>) -> bool {
+        self.derived[symbol.symbol_id()]
+    }
+
     // exprbinop : 0 *
     fn nt_exprbinop_0(&self, _s1: &PropEmpty) -> OperatorPrecedence {
         OperatorPrecedence::Multiplicative
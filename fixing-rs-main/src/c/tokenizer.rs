@@ -1,4 +1,5 @@
 use fixing_rs_base::{
+    edit_script::{edit_script, TokenEdit},
     grammar::GrammarSymbolsRef,
     tokenizer::{Token, Tokenizer},
 };
@@ -35,7 +36,8 @@ impl Tokenizer for CTokenizer {
         let mut result = Vec::new();
         for (line_id, line) in input.split("\n").enumerate() {
             let line = line.trim();
-            if line.len() == 0 {
+            // Preprocessor directives are not part of the token stream.
+            if line.len() == 0 || line.starts_with('#') {
                 continue;
             }
             let line_split: [&str; 3] = line
@@ -63,3 +65,55 @@ impl Tokenizer for CTokenizer {
         Ok(result)
     }
 }
+
+/// Preprocessor directive lines of a token file, each with the number of
+/// tokens before it.
+pub fn directives(input: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut tokens = 0;
+    for line in input.split("\n") {
+        let line = line.trim();
+        if line.starts_with('#') {
+            result.push((tokens, line));
+        } else if line.len() != 0 {
+            tokens += 1;
+        }
+    }
+    result
+}
+
+/// The lines of the output of a fix turning `input` into `output`, with the
+/// directives of the input back in place: each one before the output token
+/// its next input token became, after tokens inserted before that one, or
+/// before the tokens replacing it.
+pub fn restore_directives(
+    input: &[impl AsRef<str>],
+    output: &[String],
+    directives: &[(usize, &str)],
+) -> Vec<String> {
+    let edits = edit_script(input, output);
+    let position = |index: usize| {
+        let mut shift = 0;
+        let before = |x: &&TokenEdit| x.begin < index || (x.begin == index && x.end == index);
+        for edit in edits.iter().filter(before) {
+            if edit.end > index {
+                return (edit.begin as isize + shift) as usize;
+            }
+            shift += edit.replacement.len() as isize - (edit.end - edit.begin) as isize;
+        }
+        (index as isize + shift) as usize
+    };
+    let mut directives = directives
+        .iter()
+        .map(|(index, line)| (position(*index), *line))
+        .peekable();
+    let mut result = Vec::new();
+    for (index, token) in output.iter().enumerate() {
+        while let Some((_, line)) = directives.next_if(|(x, _)| *x <= index) {
+            result.push(line.to_string());
+        }
+        result.push(token.clone());
+    }
+    result.extend(directives.map(|(_, line)| line.to_string()));
+    result
+}
@@ -1,3 +1,4 @@
+use super::{flow::CFlow, strictness::CStrictness};
 use fixing_rs_base::utils::{Pointer, RefArena};
 use getset::{CopyGetters, Getters};
 use std::{cell::RefCell, collections::HashMap, fmt::Debug};
@@ -479,6 +480,14 @@ impl<'a> CType<'a> {
         }
     }
 
+    pub fn is_incomplete_array(&self) -> bool {
+        match self.content {
+            CTypeContent::IncompleteArray(_) => true,
+            CTypeContent::Const(ty) => ty.is_incomplete_array(),
+            _ => false,
+        }
+    }
+
     pub fn is_func(&self) -> bool {
         match self.content {
             CTypeContent::Func(_) => true,
@@ -688,15 +697,26 @@ impl<'a> CType<'a> {
     }
 
     pub fn equals_type(
-        ty1: CTypeRef<'a>,
-        ty2: CTypeRef<'a>,
+        expr1: &CExprInfo<'a>,
+        expr2: &CExprInfo<'a>,
         pool: &CTypePool<'a>,
+        strictness: &CStrictness,
     ) -> Option<CTypeRef<'a>> {
-        if (ty1.is_integer() || ty1.is_ptr() || ty1.is_array())
-            && (ty2.is_integer() || ty2.is_ptr() || ty2.is_array())
+        let (ty1, ty2) = (expr1.ty, expr2.ty);
+        let accepted = if ty1.is_arithmetic() && ty2.is_arithmetic() {
+            true
+        } else if (ty1.is_ptr() || ty1.is_array()) && (ty2.is_ptr() || ty2.is_array()) {
+            strictness.incompatible_pointer_types || CType::pointers_compatible(ty1, ty2)
+        } else if (ty1.is_integer() && (ty2.is_ptr() || ty2.is_array()))
+            || ((ty1.is_ptr() || ty1.is_array()) && ty2.is_integer())
         {
-            Some(pool.type_i32())
-        } else if ty1.is_arithmetic() && ty2.is_arithmetic() {
+            strictness.int_conversion
+                || expr1.is_null_pointer_constant
+                || expr2.is_null_pointer_constant
+        } else {
+            false
+        };
+        if accepted {
             Some(pool.type_i32())
         } else {
             None
@@ -723,10 +743,14 @@ impl<'a> CType<'a> {
         }
     }
 
-    pub fn assign_type(ty1: CTypeRef<'a>, ty2: CTypeRef<'a>) -> Option<CTypeRef<'a>> {
+    pub fn assign_type(
+        ty1: CTypeRef<'a>,
+        expr2: &CExprInfo<'a>,
+        strictness: &CStrictness,
+    ) -> Option<CTypeRef<'a>> {
         if ty1.is_const() || ty1.is_array() || ty1.is_func() {
             None
-        } else if CType::can_cast_to(ty2, ty1) {
+        } else if CType::can_convert_to(expr2, ty1, strictness) {
             Some(ty1)
         } else {
             None
@@ -768,6 +792,46 @@ impl<'a> CType<'a> {
             || ((from.is_integer() || from.is_ptr()) && (to.is_integer() || to.is_ptr()))
             || (from.is_arithmetic() && to.is_arithmetic())
     }
+
+    /// Whether `from` converts to `to` without a cast, as in assignments,
+    /// initializers, arguments and returns.
+    pub fn can_convert_to(
+        from: &CExprInfo<'a>,
+        to: CTypeRef<'a>,
+        strictness: &CStrictness,
+    ) -> bool {
+        let from_ty = from.ty;
+        if !CType::can_cast_to(from_ty, to) {
+            false
+        } else if CType::remove_qualifier(from_ty) == CType::remove_qualifier(to) {
+            true
+        } else if from_ty.is_integer() && to.is_ptr() {
+            strictness.int_conversion || from.is_null_pointer_constant
+        } else if from_ty.is_ptr() && to.is_integer() {
+            strictness.int_conversion
+        } else if let (Some(from_target), Some(to_target)) =
+            (from_ty.remove_pointer_array(), to.remove_pointer_array())
+        {
+            (strictness.discarded_qualifiers || !from_target.is_const() || to_target.is_const())
+                && (strictness.incompatible_pointer_types
+                    || CType::pointers_compatible(from_ty, to))
+        } else {
+            true
+        }
+    }
+
+    /// Whether two pointers point to the same type, ignoring qualifiers, or
+    /// one of them points to `void`.
+    pub fn pointers_compatible(ty1: CTypeRef<'a>, ty2: CTypeRef<'a>) -> bool {
+        match (ty1.remove_pointer_array(), ty2.remove_pointer_array()) {
+            (Some(ty1), Some(ty2)) => {
+                ty1.is_void()
+                    || ty2.is_void()
+                    || CType::remove_qualifier(ty1) == CType::remove_qualifier(ty2)
+            }
+            _ => false,
+        }
+    }
 }
 
 pub struct CTypeDerive<'a> {
@@ -812,10 +876,14 @@ pub struct CFuncContent<'a> {
 
 pub type CFuncContentRef<'a> = Pointer<'a, CFuncContent<'a>>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
 pub struct CExprInfo<'a> {
     pub ty: CTypeRef<'a>,
     pub is_lvalue: bool,
+    pub is_null_pointer_constant: bool,
+    /// The expression is a non-zero integer literal, maybe parenthesized.
+    pub is_nonzero_constant: bool,
+    pub flow: CFlow,
 }
 
 impl<'a> CExprInfo<'a> {
@@ -823,6 +891,26 @@ impl<'a> CExprInfo<'a> {
         if !is_lvalue {
             ty = CType::remove_qualifier(ty);
         }
-        Self { ty, is_lvalue }
+        Self {
+            ty,
+            is_lvalue,
+            is_null_pointer_constant: false,
+            is_nonzero_constant: false,
+            flow: CFlow::default(),
+        }
+    }
+
+    pub fn new_null_pointer_constant(ty: CTypeRef<'a>) -> Self {
+        Self {
+            ty,
+            is_lvalue: false,
+            is_null_pointer_constant: true,
+            is_nonzero_constant: false,
+            flow: CFlow::default(),
+        }
+    }
+
+    pub fn with_flow(self, flow: CFlow) -> Self {
+        Self { flow, ..self }
     }
 }
@@ -0,0 +1,65 @@
+use crate::{
+    cmd::{language, language_fixer},
+    fixing::{parse_option, CmdFiles, FixCmd},
+};
+use clap::Parser;
+use fixing_rs_base::{
+    check::CheckResult,
+    fixing::FixTaskInfo,
+    language::{check_dyn, LanguageOptions, LanguageRegistry},
+};
+use std::process;
+
+/// Checks that inputs are valid as they are, without fixing them. Prints
+/// `valid` or the first error of each input, exiting with status 1 if any
+/// input is not valid.
+#[derive(Parser, Clone)]
+pub struct CheckCmd {
+    #[arg(long)]
+    lang: String,
+    /// Option of the language, as `KEY=VALUE`; may be repeated.
+    #[arg(long, value_parser = parse_option)]
+    option: Vec<(String, String)>,
+    /// Output files, if given, are ignored.
+    #[command(subcommand)]
+    files: CmdFiles,
+}
+
+impl CheckCmd {
+    pub fn run(self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let results = self.check(registry)?;
+        if !results.iter().all(|(_, x)| x == &Some(CheckResult::Valid)) {
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    /// Checks each input, printing and returning its result, `None` if the
+    /// task could not be loaded.
+    pub fn check(
+        &self,
+        registry: &LanguageRegistry,
+    ) -> Result<Vec<(String, Option<CheckResult>)>, clap::Error> {
+        let files = FixCmd::to_files(self.files.clone(), &FixTaskInfo::new("", "", 0));
+        let (names, infos): (Vec<_>, Vec<_>) = files.map(|x| (x.input_name.clone(), x)).unzip();
+        let language = language(registry, &self.lang)?;
+        let mut options = LanguageOptions::default();
+        options.extend(self.option.iter().cloned());
+        let fixer = language_fixer(language, &options)?;
+        let results = check_dyn(infos.into_iter(), fixer.as_ref());
+        Ok(names
+            .into_iter()
+            .zip(results)
+            .map(|(name, result)| match result {
+                Ok(result) => {
+                    println!("{}: {}", name, result);
+                    (name, Some(result))
+                }
+                Err(e) => {
+                    println!("{}: error: {:?}", name, e);
+                    (name, None)
+                }
+            })
+            .collect())
+    }
+}
@@ -1,7 +1,20 @@
-use crate::{fixing::FixCmd, gensrc::GenSrcCmd, mem_limit::limit_memory};
-use clap::{Parser, Subcommand};
+use crate::{
+    check::CheckCmd, eval::EvalCmd, fixing::FixCmd, gensrc::GenSrcCmd, lsp::LspCmd,
+    mem_limit::limit_memory, mutate::MutateCmd, serve::ServeCmd,
+};
+use clap::{
+    builder::PossibleValuesParser, error::ErrorKind, ArgMatches, Command, CommandFactory,
+    FromArgMatches, Parser, Subcommand,
+};
+use fixing_rs_base::language::{Fixer, Language, LanguageOptions, LanguageRegistry};
 use log::error;
-use std::panic::catch_unwind;
+use std::{
+    ffi::OsString,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// Subcommands taking a `lang` argument, checked against the registry.
+const LANG_SUBCOMMANDS: [&str; 6] = ["fix", "gen-src", "lsp", "eval", "mutate", "check"];
 
 #[derive(Parser)]
 pub struct MainCmd {
@@ -16,34 +29,103 @@ pub struct MainCmd {
 pub enum Cmd {
     Fix(FixCmd),
     GenSrc(GenSrcCmd),
+    Lsp(LspCmd),
+    Serve(ServeCmd),
+    Eval(EvalCmd),
+    Mutate(MutateCmd),
+    Check(CheckCmd),
 }
 
 impl Cmd {
-    pub fn run(self) {
+    pub fn run(self, registry: LanguageRegistry) -> Result<(), clap::Error> {
         match self {
-            Cmd::Fix(fix_cmd) => fix_cmd.run(),
-            Cmd::GenSrc(gen_src_cmd) => gen_src_cmd.run(),
+            Cmd::Fix(fix_cmd) => fix_cmd.run(&registry)?,
+            Cmd::GenSrc(gen_src_cmd) => gen_src_cmd.run(&registry)?,
+            Cmd::Lsp(lsp_cmd) => lsp_cmd.run(&registry)?,
+            Cmd::Serve(serve_cmd) => serve_cmd.run(registry),
+            Cmd::Eval(eval_cmd) => eval_cmd.run(&registry)?,
+            Cmd::Mutate(mutate_cmd) => mutate_cmd.run(&registry)?,
+            Cmd::Check(check_cmd) => check_cmd.run(&registry)?,
+        }
+        Ok(())
+    }
+}
+
+impl MainCmd {
+    /// The command line, with the languages of `registry` as the values of
+    /// `lang`.
+    pub fn command_for(registry: &LanguageRegistry) -> Command {
+        let names: Vec<_> = registry.names().collect();
+        let mut command = Self::command();
+        for name in LANG_SUBCOMMANDS {
+            command = command.mut_subcommand(name, |x| {
+                x.mut_arg("lang", |x| {
+                    x.value_parser(PossibleValuesParser::new(names.clone()))
+                        .ignore_case(true)
+                })
+            });
         }
+        command
+    }
+
+    pub fn try_parse_for<I, T>(args: I, registry: &LanguageRegistry) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches: ArgMatches = Self::command_for(registry).try_get_matches_from(args)?;
+        Self::from_arg_matches(&matches)
     }
 }
 
-pub fn run() {
-    run_cmd(MainCmd::parse());
+/// The language registered as `name`, an unknown one being reported as a
+/// command line error.
+pub fn language<'r>(
+    registry: &'r LanguageRegistry,
+    name: &str,
+) -> Result<&'r dyn Language, clap::Error> {
+    registry.get(name).ok_or_else(|| {
+        clap::Error::raw(
+            ErrorKind::InvalidValue,
+            format!("unknown language: {}", name),
+        )
+    })
+}
+
+/// Builds the fixer of a language, invalid options being reported as a
+/// command line error.
+pub fn language_fixer(
+    language: &dyn Language,
+    options: &LanguageOptions,
+) -> Result<Box<dyn Fixer>, clap::Error> {
+    language.fixer(options).map_err(|e| {
+        clap::Error::raw(
+            ErrorKind::InvalidValue,
+            format!("invalid option of {}: {}", language.name(), e),
+        )
+    })
+}
+
+/// Runs the command line with the languages of `registry`.
+pub fn run(registry: LanguageRegistry) {
+    let result = MainCmd::try_parse_for(std::env::args_os(), &registry);
+    let result = result.and_then(|cmd| run_cmd(cmd, registry));
+    if let Err(e) = result {
+        e.format(&mut MainCmd::command()).exit();
+    }
 }
 
-pub fn run_cmd(cmd: MainCmd) {
+pub fn run_cmd(cmd: MainCmd, registry: LanguageRegistry) -> Result<(), clap::Error> {
     if let Some(memory_limit) = cmd.memory_limit {
         if memory_limit != 0 {
             limit_memory(memory_limit).unwrap();
         }
     }
 
-    let result = catch_unwind(move || {
-        cmd.cmd.run();
-    });
+    let result = catch_unwind(AssertUnwindSafe(move || cmd.cmd.run(registry)));
 
     match result {
-        Ok(_) => {}
+        Ok(result) => result,
         Err(p) => {
             if let Some(s) = p.downcast_ref::<String>() {
                 error!("PANIC:{}", s);
@@ -52,6 +134,7 @@ pub fn run_cmd(cmd: MainCmd) {
             } else {
                 error!("PANIC:Unknown error");
             }
+            Ok(())
         }
     }
 }
@@ -0,0 +1,414 @@
+use crate::{
+    cmd::{language, language_fixer},
+    fixing::{parse_option, FixCmd, Naming},
+};
+use clap::Parser;
+use csv::Reader;
+use fixing_rs_base::{
+    containers::OrderedMap,
+    edit_script::edit_distance,
+    fixing::{FixTaskInfo, FixTaskResult},
+    language::{fix_dyn, DynFixTaskError, Language, LanguageOptions, LanguageRegistry},
+};
+use serde_json::{json, Value};
+use std::{
+    fmt::{self, Display},
+    fs,
+    process::{self, Command},
+    time::Duration,
+};
+
+/// Percentiles of the time taken by tasks reported by `eval`.
+const PERCENTILES: [usize; 4] = [50, 90, 99, 100];
+
+/// Runs the fixer over the tasks of a manifest and compares its fixes with
+/// the expected ones.
+#[derive(Parser, Clone)]
+pub struct EvalCmd {
+    #[arg(long)]
+    lang: String,
+    #[arg(long)]
+    max_len: usize,
+    #[arg(long)]
+    max_new_id: usize,
+    /// CSV file of `input,env,expected,category` records, the last two
+    /// columns being optional. The expected fix holds one token per line, as
+    /// the output of `fix` does.
+    #[arg(long)]
+    manifest: String,
+    /// Shell command telling whether a fix compiles, run with `FIX` naming a
+    /// file holding the source of the fix and `INPUT` and `ENV` the files of
+    /// the task.
+    #[arg(long)]
+    oracle: Option<String>,
+    /// File the summary and the outcome of each task are written to as
+    /// JSON.
+    #[arg(long)]
+    json: Option<String>,
+    /// Time budget of each task, in seconds.
+    #[arg(long)]
+    time_limit: Option<u64>,
+    #[arg(long)]
+    memory_budget: Option<usize>,
+    #[arg(long)]
+    prune: bool,
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    #[arg(long, value_enum, default_value_t = Naming::Placeholder)]
+    naming: Naming,
+    #[arg(long, required_if_eq("naming", "vocabulary"))]
+    naming_vocabulary: Option<String>,
+    /// Option of the language, as `KEY=VALUE`; may be repeated.
+    #[arg(long, value_parser = parse_option)]
+    option: Vec<(String, String)>,
+}
+
+struct ManifestEntry {
+    input: String,
+    env: String,
+    expected: Option<String>,
+    category: String,
+}
+
+/// How the fixer did on one task of a manifest.
+#[derive(Debug, Clone)]
+pub struct TaskOutcome {
+    pub input: String,
+    pub category: String,
+    /// Length of the expected fix, or of the fix found when none is
+    /// expected.
+    pub length: Option<usize>,
+    pub fixed: bool,
+    /// Whether the fix is the expected one, if one is expected.
+    pub exact: Option<bool>,
+    /// Whether the fix is as long as the expected one, if one is expected.
+    pub same_length: Option<bool>,
+    /// Whether the oracle accepts the fix, if one is configured and a fix
+    /// was found.
+    pub compiles: Option<bool>,
+    /// Seconds taken by the task, if it did not fail.
+    pub time: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Numbers of tasks of a group with each outcome.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accuracy {
+    pub tasks: usize,
+    pub fixed: usize,
+    /// Tasks with an expected fix, and those fixed with exactly that fix or
+    /// with a fix of the same length.
+    pub expected: usize,
+    pub exact: usize,
+    pub same_length: usize,
+    /// Tasks whose fix was checked by the oracle, and those it accepted.
+    pub checked: usize,
+    pub compiles: usize,
+}
+
+impl Accuracy {
+    fn add(&mut self, task: &TaskOutcome) {
+        self.tasks += 1;
+        self.fixed += task.fixed as usize;
+        if let (Some(exact), Some(same_length)) = (task.exact, task.same_length) {
+            self.expected += 1;
+            self.exact += exact as usize;
+            self.same_length += same_length as usize;
+        }
+        if let Some(compiles) = task.compiles {
+            self.checked += 1;
+            self.compiles += compiles as usize;
+        }
+    }
+
+    fn rates(&self) -> [Option<f64>; 4] {
+        let rate = |count: usize, total: usize| (total != 0).then(|| count as f64 / total as f64);
+        [
+            rate(self.fixed, self.tasks),
+            rate(self.exact, self.expected),
+            rate(self.same_length, self.expected),
+            rate(self.compiles, self.checked),
+        ]
+    }
+
+    fn to_json(&self) -> Value {
+        let [fixed_rate, exact_rate, same_length_rate, compiles_rate] = self.rates();
+        json!({
+            "tasks": self.tasks,
+            "fixed": self.fixed,
+            "expected": self.expected,
+            "exact": self.exact,
+            "same_length": self.same_length,
+            "checked": self.checked,
+            "compiles": self.compiles,
+            "fixed_rate": fixed_rate,
+            "exact_rate": exact_rate,
+            "same_length_rate": same_length_rate,
+            "compiles_rate": compiles_rate,
+        })
+    }
+}
+
+/// The outcomes of the tasks of a manifest, summed up by category and by
+/// length. Displays as a table.
+pub struct Evaluation {
+    pub tasks: Vec<TaskOutcome>,
+    pub overall: Accuracy,
+    pub by_category: OrderedMap<String, Accuracy>,
+    /// By increasing length, tasks of unknown length first.
+    pub by_length: OrderedMap<Option<usize>, Accuracy>,
+    /// Percentiles of [`PERCENTILES`] of the seconds taken by tasks that did
+    /// not fail.
+    pub time_percentiles: Vec<(usize, f64)>,
+}
+
+impl Evaluation {
+    pub fn new(tasks: Vec<TaskOutcome>) -> Self {
+        let mut overall = Accuracy::default();
+        let mut by_category: OrderedMap<String, Accuracy> = OrderedMap::new();
+        let mut by_length: OrderedMap<Option<usize>, Accuracy> = OrderedMap::new();
+        for task in tasks.iter() {
+            overall.add(task);
+            by_category
+                .entry(task.category.clone())
+                .or_default()
+                .add(task);
+            by_length.entry(task.length).or_default().add(task);
+        }
+        by_length.sort_keys();
+
+        let mut times: Vec<f64> = tasks.iter().flat_map(|x| x.time).collect();
+        times.sort_by(|x, y| x.total_cmp(y));
+        let time_percentiles = if times.is_empty() {
+            Vec::new()
+        } else {
+            PERCENTILES
+                .iter()
+                .map(|p| {
+                    let rank = (p * times.len()).div_ceil(100);
+                    (*p, times[rank.max(1) - 1])
+                })
+                .collect()
+        };
+
+        Self {
+            tasks,
+            overall,
+            by_category,
+            by_length,
+            time_percentiles,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let tasks: Vec<Value> = self
+            .tasks
+            .iter()
+            .map(|x| {
+                json!({
+                    "input": x.input,
+                    "category": x.category,
+                    "length": x.length,
+                    "fixed": x.fixed,
+                    "exact": x.exact,
+                    "same_length": x.same_length,
+                    "compiles": x.compiles,
+                    "time": x.time,
+                    "error": x.error,
+                })
+            })
+            .collect();
+        let by_category: serde_json::Map<String, Value> = self
+            .by_category
+            .iter()
+            .map(|(category, accuracy)| (category.clone(), accuracy.to_json()))
+            .collect();
+        let by_length: serde_json::Map<String, Value> = self
+            .by_length
+            .iter()
+            .map(|(length, accuracy)| (length_name(*length), accuracy.to_json()))
+            .collect();
+        let time: serde_json::Map<String, Value> = self
+            .time_percentiles
+            .iter()
+            .map(|(p, time)| (format!("p{}", p), json!(time)))
+            .collect();
+        json!({
+            "overall": self.overall.to_json(),
+            "by_category": by_category,
+            "by_length": by_length,
+            "time": time,
+            "tasks": tasks,
+        })
+    }
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>6} {:>7} {:>7} {:>9} {:>9}",
+            "group", "tasks", "fixed", "exact", "same_len", "compiles"
+        )?;
+        let mut row = |name: &str, accuracy: &Accuracy| {
+            write!(f, "{:<24} {:>6}", name, accuracy.tasks)?;
+            for (rate, width) in accuracy.rates().into_iter().zip([7, 7, 9, 9]) {
+                match rate {
+                    Some(rate) => write!(f, " {:>1$.1}%", rate * 100.0, width - 1)?,
+                    None => write!(f, " {:>1$}", "-", width)?,
+                }
+            }
+            writeln!(f)
+        };
+        row("all", &self.overall)?;
+        for (category, accuracy) in self.by_category.iter() {
+            row(&format!("category {}", category), accuracy)?;
+        }
+        for (length, accuracy) in self.by_length.iter() {
+            row(&format!("length {}", length_name(*length)), accuracy)?;
+        }
+        write!(f, "time")?;
+        for (p, time) in self.time_percentiles.iter() {
+            write!(f, " p{}:{:.3}s", p, time)?;
+        }
+        writeln!(f)
+    }
+}
+
+fn length_name(length: Option<usize>) -> String {
+    match length {
+        Some(length) => length.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+impl EvalCmd {
+    pub fn run(self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let evaluation = self.evaluate(registry)?;
+        print!("{}", evaluation);
+        if let Some(ref path) = self.json {
+            let report = serde_json::to_string_pretty(&evaluation.to_json()).unwrap();
+            fs::write(path, report).expect("Unable to write JSON report.");
+        }
+        Ok(())
+    }
+
+    pub fn evaluate(&self, registry: &LanguageRegistry) -> Result<Evaluation, clap::Error> {
+        let entries = Self::read_manifest(&self.manifest);
+        let naming = FixCmd::naming_strategy(self.naming, self.naming_vocabulary.as_deref());
+        let language = language(registry, &self.lang)?;
+        let mut options = LanguageOptions::default();
+        options.extend(self.option.iter().cloned());
+        let fixer = language_fixer(language, &options)?;
+        let infos = entries.iter().map(|entry| FixTaskInfo {
+            max_new_id: self.max_new_id,
+            naming: naming.clone(),
+            time_limit: self.time_limit.map(Duration::from_secs),
+            memory_budget: self.memory_budget,
+            prune: self.prune,
+            threads: self.threads,
+            ..FixTaskInfo::new(entry.input.clone(), entry.env.clone(), self.max_len)
+        });
+        let results = fix_dyn(infos, fixer.as_ref());
+        let tasks = entries
+            .iter()
+            .zip(results.iter())
+            .enumerate()
+            .map(|(index, (entry, result))| self.outcome(language, index, entry, result))
+            .collect();
+        Ok(Evaluation::new(tasks))
+    }
+
+    fn outcome(
+        &self,
+        language: &dyn Language,
+        index: usize,
+        entry: &ManifestEntry,
+        result: &Result<FixTaskResult, DynFixTaskError>,
+    ) -> TaskOutcome {
+        let mut outcome = TaskOutcome {
+            input: entry.input.clone(),
+            category: entry.category.clone(),
+            length: None,
+            fixed: false,
+            exact: None,
+            same_length: None,
+            compiles: None,
+            time: None,
+            error: None,
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                outcome.error = Some(format!("{:?}", e));
+                return outcome;
+            }
+        };
+        let finished = match result.time_after_find.last() {
+            Some(x) => *x,
+            None => result.time_after_reachability_built,
+        };
+        outcome.time = Some((finished - result.time_before_load).as_secs_f64());
+        outcome.fixed = result.outputs.is_some();
+        outcome.length = result.found_length;
+        if let Some(ref expected) = entry.expected {
+            let expected: Vec<String> = fs::read_to_string(expected)
+                .expect("Unable to read expected fix.")
+                .lines()
+                .map(|x| x.to_string())
+                .collect();
+            let length = edit_distance(&result.input, &expected);
+            outcome.length = Some(length);
+            outcome.exact = Some(result.outputs.as_ref() == Some(&expected));
+            outcome.same_length = Some(result.found_length == Some(length));
+        }
+        if let (Some(oracle), Some(fix)) = (&self.oracle, &result.outputs) {
+            outcome.compiles = Some(Self::check(oracle, language, index, entry, fix));
+        }
+        outcome
+    }
+
+    fn check(
+        oracle: &str,
+        language: &dyn Language,
+        index: usize,
+        entry: &ManifestEntry,
+        fix: &[String],
+    ) -> bool {
+        let path = std::env::temp_dir().join(format!("fixing-rs-eval-{}-{}", process::id(), index));
+        fs::write(&path, language.detokenize(fix)).expect("Unable to write fix for the oracle.");
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(oracle)
+            .env("FIX", &path)
+            .env("INPUT", &entry.input)
+            .env("ENV", &entry.env)
+            .status()
+            .expect("Unable to run the oracle.");
+        fs::remove_file(&path).ok();
+        status.success()
+    }
+
+    fn read_manifest(path: &str) -> Vec<ManifestEntry> {
+        let file = fs::File::open(path).expect("Unable to open manifest.");
+        let mut reader = Reader::from_reader(file);
+        let mut entries = Vec::new();
+        for item in reader.records() {
+            let item = item.unwrap();
+            if item.is_empty() {
+                continue;
+            }
+            if item.len() < 2 || item.len() > 4 {
+                panic!("Manifest wrong record length.");
+            }
+            let column = |i: usize| item.get(i).filter(|x| !x.is_empty());
+            entries.push(ManifestEntry {
+                input: item[0].to_string(),
+                env: item[1].to_string(),
+                expected: column(2).map(|x| x.to_string()),
+                category: column(3).unwrap_or("-").to_string(),
+            });
+        }
+        entries
+    }
+}
@@ -1,27 +1,63 @@
-use std::ffi::OsString;
+use std::{ffi::OsString, sync::Arc};
 
-use crate::{
-    c::fixing::CFixingInputProcessor, grammars::SupportedGrammar,
-    mj::fixing::MJFixingInputProcessor,
-};
-use clap::{Parser, Subcommand};
+use crate::cmd::{language, language_fixer};
+use clap::{Parser, Subcommand, ValueEnum};
 use csv::Reader;
-use fixing_rs_base::fixing::{fix, FixTaskInfo};
+use fixing_rs_base::{
+    error_location::{parse_diagnostics, ErrorLocation},
+    fixing::FixTaskInfo,
+    language::{fix_dyn, LanguageOptions, LanguageRegistry},
+    naming::{NamingStrategy, NearbyNaming, PlaceholderNaming, TypeNaming, VocabularyNaming},
+};
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Naming {
+    /// Keep the `__new_id_N` placeholders.
+    Placeholder,
+    /// Name after the declared type.
+    Type,
+    /// Name after the closest identifier.
+    Nearby,
+    /// Take names from `--naming-vocabulary`.
+    Vocabulary,
+}
 
 #[derive(Parser, Clone)]
 pub struct FixCmd {
-    #[arg(long, value_enum)]
-    lang: SupportedGrammar,
+    #[arg(long)]
+    lang: String,
     #[arg(long)]
     max_len: usize,
     #[arg(long)]
     max_new_id: usize,
     #[arg(long)]
     verbose_gen: bool,
+    /// Drop syntactic edges that cannot be part of a fix within `--max-len`.
+    #[arg(long)]
+    prune: bool,
+    /// Number of threads building syntactic reachability.
+    #[arg(long, default_value_t = 1)]
+    threads: usize,
+    /// File the engine counters of each task are appended to.
+    #[arg(long)]
+    profile: Option<String>,
+    /// Bytes each task may allocate once its input is loaded; a task going
+    /// over it fails without affecting the next ones.
+    #[arg(long)]
+    memory_budget: Option<usize>,
     #[arg(long)]
     dump_cnf: Option<OsString>,
     #[arg(long)]
     solver_timeout: Option<u64>,
+    /// How new declarations are named in the output.
+    #[arg(long, value_enum, default_value_t = Naming::Placeholder)]
+    naming: Naming,
+    /// File listing candidate names, one per line.
+    #[arg(long, required_if_eq("naming", "vocabulary"))]
+    naming_vocabulary: Option<String>,
+    /// Option of the language, as `KEY=VALUE`; may be repeated.
+    #[arg(long, value_parser = parse_option)]
+    option: Vec<(String, String)>,
     #[command(subcommand)]
     files: CmdFiles,
 }
@@ -35,6 +71,15 @@ pub enum CmdFiles {
         env: String,
         #[arg(long)]
         output: Option<String>,
+        /// Token index or `line:column` reported as erroneous; may be
+        /// repeated. Positions are only accepted for inputs that are source
+        /// text, not token files.
+        #[arg(long)]
+        error_loc: Vec<ErrorLocation>,
+        /// File holding gcc/clang diagnostics for the input, which must be
+        /// source text.
+        #[arg(long)]
+        diagnostics: Option<String>,
     },
     Multiple {
         #[arg(long)]
@@ -43,34 +88,48 @@ pub enum CmdFiles {
 }
 
 impl FixCmd {
-    pub fn run(self) {
-        let files = Self::to_files(self.max_len, self.max_new_id, self.files, self.verbose_gen);
-        match self.lang {
-            SupportedGrammar::MJ => {
-                fix(files, &MJFixingInputProcessor);
-            }
-            SupportedGrammar::C => {
-                fix(files, &CFixingInputProcessor);
-            }
+    pub fn run(self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let naming = Self::naming_strategy(self.naming, self.naming_vocabulary.as_deref());
+        let template = FixTaskInfo {
+            max_new_id: self.max_new_id,
+            verbose_gen: self.verbose_gen,
+            naming,
+            memory_budget: self.memory_budget,
+            prune: self.prune,
+            threads: self.threads,
+            profile: self.profile,
+            ..FixTaskInfo::new("", "", self.max_len)
         };
+        let files = Self::to_files(self.files, &template);
+        let language = language(registry, &self.lang)?;
+        let mut options = LanguageOptions::default();
+        options.extend(self.option);
+        let fixer = language_fixer(language, &options)?;
+        fix_dyn(files, fixer.as_ref());
+        Ok(())
     }
 
-    pub fn to_files(
-        max_len: usize,
-        max_new_id: usize,
-        files: CmdFiles,
-        verbose_gen: bool,
-    ) -> impl Iterator<Item = FixTaskInfo> {
+    /// The tasks of `files`, set up as `template` but for their input, env,
+    /// output and error locations.
+    pub fn to_files(files: CmdFiles, template: &FixTaskInfo) -> impl Iterator<Item = FixTaskInfo> {
         let mut inputs = Vec::new();
         match files {
-            CmdFiles::Single { input, env, output } => {
+            CmdFiles::Single {
+                input,
+                env,
+                output,
+                mut error_loc,
+                diagnostics,
+            } => {
+                if let Some(diagnostics) = diagnostics {
+                    error_loc.extend(Self::read_diagnostics(&diagnostics));
+                }
                 inputs.push(FixTaskInfo {
                     input_name: input,
                     env_name: env,
                     output_name: output,
-                    max_len,
-                    max_new_id,
-                    verbose_gen,
+                    error_locations: error_loc,
+                    ..template.clone()
                 });
             }
             CmdFiles::Multiple { file_list } => {
@@ -82,7 +141,7 @@ impl FixCmd {
                         continue;
                     }
                     let len = item.len();
-                    if len != 2 && len != 3 {
+                    if len < 2 || len > 4 {
                         panic!("Input file wrong record length.");
                     }
                     let input = item.get(0).unwrap().to_string();
@@ -97,17 +156,48 @@ impl FixCmd {
                         }
                         None => None,
                     };
+                    let error_locations = match item.get(3) {
+                        Some(x) if x.len() != 0 => Self::read_diagnostics(x),
+                        _ => Vec::new(),
+                    };
                     inputs.push(FixTaskInfo {
                         input_name: input,
                         env_name: env,
                         output_name: output,
-                        max_len,
-                        max_new_id,
-                        verbose_gen,
+                        error_locations,
+                        ..template.clone()
                     });
                 }
             }
         }
         inputs.into_iter()
     }
+
+    pub fn naming_strategy(naming: Naming, vocabulary: Option<&str>) -> Arc<dyn NamingStrategy> {
+        match naming {
+            Naming::Placeholder => Arc::new(PlaceholderNaming),
+            Naming::Type => Arc::new(TypeNaming),
+            Naming::Nearby => Arc::new(NearbyNaming),
+            Naming::Vocabulary => {
+                let path = vocabulary.expect("No naming vocabulary given.");
+                let names =
+                    std::fs::read_to_string(path).expect("Unable to read naming vocabulary.");
+                Arc::new(VocabularyNaming::new(
+                    names.lines().map(|x| x.trim().to_string()),
+                ))
+            }
+        }
+    }
+
+    fn read_diagnostics(path: &str) -> Vec<ErrorLocation> {
+        let diagnostics = std::fs::read_to_string(path).expect("Unable to read diagnostics file.");
+        parse_diagnostics(&diagnostics)
+    }
+}
+
+pub fn parse_option(option: &str) -> Result<(String, String), String> {
+    match option.split_once('=') {
+        Some((key, value)) => Ok((key.to_string(), value.to_string())),
+        None => Err("expected KEY=VALUE".to_string()),
+    }
 }
@@ -1,12 +1,17 @@
-use crate::grammars::SupportedGrammar;
+use crate::cmd::language;
 use clap::{Parser, ValueEnum};
-use fixing_rs_base::gensrc::{gen_g_src, gen_s_src, gen_s_symbolic_src};
+use fixing_rs_base::{
+    gensrc::{gen_g_src, gen_s_src, gen_s_symbolic_src},
+    language::LanguageRegistry,
+};
 
 #[derive(Parser)]
 pub struct GenSrcCmd {
-    #[clap(value_enum)]
-    lang: SupportedGrammar,
-    #[clap(value_enum)]
+    /// Indices are explicit as the parser of `lang` is replaced when the
+    /// languages are known, which moves it after `ty` otherwise.
+    #[clap(index = 1)]
+    lang: String,
+    #[clap(value_enum, index = 2)]
     ty: GenSrcType,
 }
 
@@ -18,8 +23,8 @@ pub enum GenSrcType {
 }
 
 impl GenSrcCmd {
-    pub fn run(&self) {
-        let grammar = self.lang.fixing_info();
+    pub fn run(&self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let grammar = language(registry, &self.lang)?.info();
         match self.ty {
             GenSrcType::G => {
                 let result = gen_g_src(grammar).unwrap();
@@ -34,5 +39,6 @@ impl GenSrcCmd {
                 println!("{}", result);
             }
         }
+        Ok(())
     }
 }
@@ -1,18 +1,147 @@
-use crate::{c::fixing::C_FIXING_INFO, mj::fixing::MJ_FIXING_INFO};
+use crate::{
+    c::{
+        fixing::{CFixingInputProcessor, C_FIXING_INFO},
+        stdlib::CStdlib,
+        strictness::{CStrictness, CStrictnessLevel},
+    },
+    json::{
+        fixing::{JsonFixingInputProcessor, JSON_FIXING_INFO},
+        tokenizer as json_tokenizer,
+    },
+    mj::{
+        document::MJDocumentFormat,
+        fixing::{MJFixingInputProcessor, MJ_FIXING_INFO},
+    },
+    py::{
+        fixing::{PyFixingInputProcessor, PY_FIXING_INFO},
+        tokenizer,
+    },
+};
 use clap::ValueEnum;
-use fixing_rs_base::fixing_info::FixingInfo;
+use fixing_rs_base::{
+    fixing_info::FixingInfo,
+    language::{
+        check_option_keys, DocumentFormat, Fixer, Language, LanguageOptions, LanguageRegistry,
+        TokenFile,
+    },
+};
 
-#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
-pub enum SupportedGrammar {
-    MJ,
-    C,
+pub struct MJLanguage;
+
+impl Language for MJLanguage {
+    fn name(&self) -> &'static str {
+        "mj"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        &MJ_FIXING_INFO
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        check_option_keys(options, &[])?;
+        Ok(Box::new(MJFixingInputProcessor))
+    }
+
+    fn token_file(&self) -> Option<TokenFile> {
+        Some(TokenFile::Lines)
+    }
+
+    fn document_format(&self) -> Option<&dyn DocumentFormat> {
+        Some(&MJDocumentFormat)
+    }
 }
 
-impl SupportedGrammar {
-    pub fn fixing_info(self) -> &'static FixingInfo {
-        match self {
-            Self::MJ => &MJ_FIXING_INFO,
-            Self::C => &C_FIXING_INFO,
-        }
+/// Takes the options `c_strictness`, `c_stdlib` and `c_translation_unit`.
+pub struct CLanguage;
+
+impl Language for CLanguage {
+    fn name(&self) -> &'static str {
+        "c"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        &C_FIXING_INFO
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        check_option_keys(options, &["c_strictness", "c_stdlib", "c_translation_unit"])?;
+        let strictness = match options.get("c_strictness") {
+            Some(level) => CStrictness::new(CStrictnessLevel::from_str(level, true)?),
+            None => CStrictness::default(),
+        };
+        let stdlib = match options.get("c_stdlib") {
+            Some(version) => CStdlib::from_str(version, true)?,
+            None => CStdlib::default(),
+        };
+        let translation_unit = match options.get("c_translation_unit") {
+            Some(x) => x
+                .parse()
+                .map_err(|_| format!("Invalid c_translation_unit: {}", x))?,
+            None => false,
+        };
+        Ok(Box::new(CFixingInputProcessor::new(
+            strictness,
+            stdlib,
+            translation_unit,
+        )))
     }
+
+    fn token_file(&self) -> Option<TokenFile> {
+        Some(TokenFile::Tagged)
+    }
+}
+
+/// Fixes are printed back as indented source.
+pub struct PyLanguage;
+
+impl Language for PyLanguage {
+    fn name(&self) -> &'static str {
+        "py"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        &PY_FIXING_INFO
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        check_option_keys(options, &[])?;
+        Ok(Box::new(PyFixingInputProcessor))
+    }
+
+    fn detokenize(&self, tokens: &[String]) -> String {
+        tokenizer::detokenize(tokens)
+    }
+}
+
+/// Config files checked against a schema; fixes are printed back indented.
+pub struct JsonLanguage;
+
+impl Language for JsonLanguage {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        &JSON_FIXING_INFO
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        check_option_keys(options, &[])?;
+        Ok(Box::new(JsonFixingInputProcessor))
+    }
+
+    fn detokenize(&self, tokens: &[String]) -> String {
+        json_tokenizer::detokenize(tokens)
+    }
+}
+
+/// The languages built into this crate, the ones `fixing-rs-main` runs with.
+pub fn registry() -> LanguageRegistry {
+    let mut registry = LanguageRegistry::new();
+    registry
+        .register(MJLanguage)
+        .register(CLanguage)
+        .register(PyLanguage)
+        .register(JsonLanguage);
+    registry
 }
@@ -0,0 +1,5 @@
+pub mod fixing;
+pub mod schema;
+pub mod semantic;
+pub mod syntactic;
+pub mod tokenizer;
@@ -0,0 +1,88 @@
+use super::{
+    schema::{JsonSchema, JsonSchemaError},
+    semantic::JsonSProcessor,
+    syntactic::JsonGProcessor,
+    tokenizer::{detokenize, JsonTokenizer, JsonTokenizerError},
+};
+use fixing_rs_base::{
+    fixing::{
+        FixTaskError, FixTaskInfo, FixTaskResult, FixingInputProcessor, FixingInputProcessorBase,
+    },
+    fixing_info::FixingInfo,
+    grammar::Grammar,
+    naming::NamingScope,
+    tokenizer::Tokenizer,
+    utils::RefArena,
+};
+use std::time::Instant;
+
+pub struct JsonFixingInputProcessor;
+
+impl FixingInputProcessorBase for JsonFixingInputProcessor {
+    fn info(&self) -> &'static FixingInfo {
+        &JSON_FIXING_INFO
+    }
+}
+
+impl FixingInputProcessor for JsonFixingInputProcessor {
+    fn process<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+        do_fix: impl fixing_rs_base::fixing::DoFix,
+    ) -> Result<FixTaskResult, FixTaskError<Self::TokenizerError, Self::EnvLoadError>> {
+        let symbol_ref = grammar.get_symbol_ref();
+        let schema = JsonSchema::build(env_str).map_err(|e| FixTaskError::EnvLoadError(e))?;
+        let tokens = JsonTokenizer
+            .tokenize(input_str, symbol_ref)
+            .map_err(|e| FixTaskError::TokenizerError(e))?;
+        let strs = RefArena::new();
+        let gproc = JsonGProcessor;
+        let sproc = JsonSProcessor::new(schema, &strs, &tokens);
+        // Documents declare no names.
+        let naming_scope = NamingScope::new(std::iter::empty(), |_, _| None);
+
+        let result = do_fix.do_fix(
+            grammar,
+            Some(input_str),
+            &tokens,
+            &gproc,
+            &sproc,
+            naming_scope,
+            info,
+            time_before_load,
+        )?;
+        if let (Some(output), Some(outputs)) = (&info.output_name, &result.outputs) {
+            std::fs::write(output, detokenize(outputs)).expect("Unable to write output file.");
+        }
+        Ok(result)
+    }
+
+    type TokenizerError = JsonTokenizerError;
+    type EnvLoadError = JsonSchemaError;
+}
+
+pub const JSON_GRAMMAR: &str = include_str!("json");
+pub const JSON_GRAMMAR_FILE: &str = "src/json/json";
+pub const JSON_PROP_G: &str = "JsonProp";
+pub const JSON_PROP_SI: &str = "JsonInhProp";
+pub const JSON_PROP_SS: &str = "JsonSynProp";
+pub const JSON_ENTITY_I: &str = "JsonInhEntity<'s>";
+pub const JSON_CONTAINER_I: &str = "JsonInhEntityArena";
+pub const JSON_ENTITY_S: &str = "JsonSynEntity<'s>";
+pub const JSON_CONTAINER_S: &str = "JsonSynEntityArena";
+
+pub const JSON_FIXING_INFO: FixingInfo = FixingInfo {
+    grammar: JSON_GRAMMAR,
+    grammar_file: JSON_GRAMMAR_FILE,
+    prop_g: JSON_PROP_G,
+    prop_si: JSON_PROP_SI,
+    prop_ss: JSON_PROP_SS,
+    entity_i: JSON_ENTITY_I,
+    entity_s: JSON_ENTITY_S,
+    container_i: JSON_CONTAINER_I,
+    container_s: JSON_CONTAINER_S,
+};
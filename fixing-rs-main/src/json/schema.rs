@@ -0,0 +1,224 @@
+use fixing_rs_base::{
+    containers::{Map, OrderedMap},
+    grammar::OwnedToken,
+};
+use lalrpop_util::lexer::Token;
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+lalrpop_mod!(json_schema_parser, "/grammars/json_schema.rs");
+
+pub enum JsonSchemaAst<'input> {
+    Name(&'input str),
+    Array(Box<JsonSchemaAst<'input>>),
+    Object(Vec<JsonSchemaAstField<'input>>),
+    Enum(Vec<&'input str>),
+}
+
+pub enum JsonSchemaAstField<'input> {
+    /// A key, whether it is required, and the type of its value.
+    Field(&'input str, bool, JsonSchemaAst<'input>),
+    /// `...`: keys not listed may appear, with values of any type.
+    Rest,
+}
+
+/// A type of the schema, as an index into its types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop)]
+pub struct JsonTypeRef(usize);
+
+#[derive(Debug, Clone)]
+pub struct JsonObjectType {
+    /// The type of the value of each key, and whether the key is required.
+    pub fields: OrderedMap<String, (JsonTypeRef, bool)>,
+    pub open: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum JsonType {
+    Any,
+    String,
+    Number,
+    Boolean,
+    Null,
+    Enum(Vec<String>),
+    Array(JsonTypeRef),
+    Object(JsonObjectType),
+}
+
+#[derive(Debug)]
+pub enum JsonSchemaError {
+    SyntaxError(lalrpop_util::ParseError<usize, OwnedToken, &'static str>),
+    EmptySchema,
+    DuplicateType(String),
+    UndefinedType(String),
+    CyclicType(String),
+    DuplicateField(String),
+}
+
+impl Display for JsonSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl Error for JsonSchemaError {}
+
+impl<'input> From<lalrpop_util::ParseError<usize, Token<'input>, &'static str>>
+    for JsonSchemaError
+{
+    fn from(input: lalrpop_util::ParseError<usize, Token<'input>, &'static str>) -> Self {
+        Self::SyntaxError(input.map_token(|x| x.into()))
+    }
+}
+
+const BUILTIN_TYPES: &[(&str, JsonType)] = &[
+    ("any", JsonType::Any),
+    ("string", JsonType::String),
+    ("number", JsonType::Number),
+    ("boolean", JsonType::Boolean),
+    ("null", JsonType::Null),
+];
+
+/// The expected shape of a document, given as type declarations such as
+/// `type Root = { name: string, mode?: "debug" | "release", deps: [Dep], ... };`.
+/// The first declared type is the type of the document. Keys marked `?` may
+/// be omitted, and `...` allows keys that are not listed.
+#[derive(Debug)]
+pub struct JsonSchema {
+    types: Vec<JsonType>,
+    root: JsonTypeRef,
+}
+
+impl JsonSchema {
+    pub fn build(env: &str) -> Result<Self, JsonSchemaError> {
+        let decls = json_schema_parser::FileParser::new().parse(env)?;
+        let mut result = Self {
+            types: Vec::new(),
+            root: JsonTypeRef(0),
+        };
+        let mut names = Map::default();
+        for (name, ty) in BUILTIN_TYPES {
+            names.insert(*name, result.add(ty.clone()));
+        }
+
+        // Every declaration that is not an alias gets a slot, filled once all
+        // names are known.
+        let mut slots = Vec::new();
+        let mut aliases = Vec::new();
+        for (name, ast) in decls.iter() {
+            if names.contains_key(name) || aliases.iter().any(|(x, _)| x == name) {
+                return Err(JsonSchemaError::DuplicateType(name.to_string()));
+            }
+            match ast {
+                JsonSchemaAst::Name(target) => aliases.push((*name, *target)),
+                _ => {
+                    let slot = result.add(JsonType::Any);
+                    names.insert(*name, slot);
+                    slots.push((slot, ast));
+                }
+            }
+        }
+        while !aliases.is_empty() {
+            let count = aliases.len();
+            aliases.retain(|(name, target)| match names.get(target) {
+                Some(ty) => {
+                    names.insert(*name, *ty);
+                    false
+                }
+                None => true,
+            });
+            if aliases.len() == count {
+                let (name, target) = aliases[0];
+                return Err(match decls.iter().any(|(x, _)| *x == target) {
+                    true => JsonSchemaError::CyclicType(name.to_string()),
+                    false => JsonSchemaError::UndefinedType(target.to_string()),
+                });
+            }
+        }
+        for (slot, ast) in slots {
+            result.types[slot.0] = result.convert_type(ast, &names)?;
+        }
+
+        let (root, _) = decls.first().ok_or(JsonSchemaError::EmptySchema)?;
+        result.root = names[root];
+        Ok(result)
+    }
+
+    fn convert(
+        &mut self,
+        ast: &JsonSchemaAst,
+        names: &Map<&str, JsonTypeRef>,
+    ) -> Result<JsonTypeRef, JsonSchemaError> {
+        match ast {
+            JsonSchemaAst::Name(name) => names
+                .get(name)
+                .copied()
+                .ok_or_else(|| JsonSchemaError::UndefinedType(name.to_string())),
+            _ => {
+                let ty = self.convert_type(ast, names)?;
+                Ok(self.add(ty))
+            }
+        }
+    }
+
+    /// The type of `ast`, which is not a name.
+    fn convert_type(
+        &mut self,
+        ast: &JsonSchemaAst,
+        names: &Map<&str, JsonTypeRef>,
+    ) -> Result<JsonType, JsonSchemaError> {
+        Ok(match ast {
+            JsonSchemaAst::Name(_) => unreachable!(),
+            JsonSchemaAst::Array(element) => JsonType::Array(self.convert(element, names)?),
+            JsonSchemaAst::Object(fields) => {
+                let mut result = JsonObjectType {
+                    fields: OrderedMap::new(),
+                    open: false,
+                };
+                for field in fields {
+                    match field {
+                        JsonSchemaAstField::Field(name, required, ty) => {
+                            let ty = self.convert(ty, names)?;
+                            if result
+                                .fields
+                                .insert(name.to_string(), (ty, *required))
+                                .is_some()
+                            {
+                                return Err(JsonSchemaError::DuplicateField(name.to_string()));
+                            }
+                        }
+                        JsonSchemaAstField::Rest => result.open = true,
+                    }
+                }
+                JsonType::Object(result)
+            }
+            JsonSchemaAst::Enum(values) => {
+                JsonType::Enum(values.iter().map(|x| x.to_string()).collect())
+            }
+        })
+    }
+
+    fn add(&mut self, ty: JsonType) -> JsonTypeRef {
+        self.types.push(ty);
+        JsonTypeRef(self.types.len() - 1)
+    }
+
+    pub fn root(&self) -> JsonTypeRef {
+        self.root
+    }
+
+    /// The type of the values of keys allowed by `...`.
+    pub fn any(&self) -> JsonTypeRef {
+        JsonTypeRef(0)
+    }
+
+    pub fn get(&self, ty: JsonTypeRef) -> &JsonType {
+        &self.types[ty.0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &JsonType> {
+        self.types.iter()
+    }
+}
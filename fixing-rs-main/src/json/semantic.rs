@@ -0,0 +1,373 @@
+use super::{
+    schema::{JsonSchema, JsonType, JsonTypeRef},
+    syntactic::JsonProp,
+};
+use fixing_rs_base::{
+    props::{PropArray, PropEmpty, Unshared},
+    tokenizer::Token,
+    union_prop,
+    utils::{RefArena, StringPool, StringRef},
+};
+
+/// Whether a string is a key of an object or a value of the given type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Prop)]
+pub enum JsonStringSelector {
+    Key(JsonTypeRef),
+    Value(JsonTypeRef),
+}
+
+/// The keys of the members of an object, sorted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct JsonKeys<'a> {
+    keys: Vec<StringRef<'a>>,
+}
+
+impl<'a> JsonKeys<'a> {
+    fn new(key: StringRef<'a>) -> Self {
+        Self { keys: vec![key] }
+    }
+
+    fn insert(&self, key: StringRef<'a>) -> Option<Self> {
+        let index = self.keys.binary_search(&key).err()?;
+        let mut keys = self.keys.clone();
+        keys.insert(index, key);
+        Some(Self { keys })
+    }
+}
+
+pub struct JsonSProcessor<'a> {
+    schema: JsonSchema,
+    str_pool: StringPool<'a>,
+}
+
+impl<'a> JsonSProcessor<'a> {
+    pub fn new(schema: JsonSchema, arena: &'a RefArena<String>, tokens: &Vec<Token>) -> Self {
+        let mut str_pool = StringPool::new(arena);
+        str_pool.get_or_add("");
+        for token in tokens {
+            if let Some(s) = Self::decode(token.literal) {
+                str_pool.get_or_add(&s);
+            }
+        }
+        for ty in schema.iter() {
+            match ty {
+                JsonType::Enum(values) => values.iter().for_each(|x| {
+                    str_pool.get_or_add(x);
+                }),
+                JsonType::Object(object) => object.fields.keys().for_each(|x| {
+                    str_pool.get_or_add(x);
+                }),
+                _ => {}
+            }
+        }
+        Self { schema, str_pool }
+    }
+
+    /// The value of a string literal.
+    fn decode(literal: &str) -> Option<String> {
+        match literal.starts_with('"') {
+            true => serde_json::from_str(literal).ok(),
+            false => None,
+        }
+    }
+
+    fn is_object(&self, ty: JsonTypeRef) -> bool {
+        matches!(self.schema.get(ty), JsonType::Object(_) | JsonType::Any)
+    }
+
+    fn element(&self, ty: JsonTypeRef) -> Option<JsonTypeRef> {
+        match self.schema.get(ty) {
+            JsonType::Array(element) => Some(*element),
+            JsonType::Any => Some(self.schema.any()),
+            _ => None,
+        }
+    }
+
+    /// The type of the value of `key` in an object of type `ty`.
+    fn field(&self, ty: JsonTypeRef, key: &str) -> Option<JsonTypeRef> {
+        match self.schema.get(ty) {
+            JsonType::Object(object) => match object.fields.get(key) {
+                Some((ty, _)) => Some(*ty),
+                None => object.open.then(|| self.schema.any()),
+            },
+            JsonType::Any => Some(self.schema.any()),
+            _ => None,
+        }
+    }
+
+    fn has_required(&self, ty: JsonTypeRef, keys: &[StringRef<'a>]) -> bool {
+        match self.schema.get(ty) {
+            JsonType::Object(object) => object
+                .fields
+                .iter()
+                .filter(|(_, (_, required))| *required)
+                .all(|(name, _)| keys.iter().any(|x| x.as_str() == name)),
+            _ => true,
+        }
+    }
+
+    fn accepts(&self, ty: JsonTypeRef, filter: fn(&JsonType) -> bool) -> Option<PropEmpty> {
+        let ty = self.schema.get(ty);
+        (matches!(ty, JsonType::Any) || filter(ty)).then_some(PropEmpty)
+    }
+}
+
+union_prop!(
+    JsonSynProp<'a>,
+    Empty,
+    {
+        Empty(PropEmpty),
+        Str(StringRef<'a>),
+        Keys(JsonKeys<'a>)
+    }
+);
+
+union_prop!(
+    JsonInhProp,
+    Empty,
+    {
+        Empty(PropEmpty),
+        Type(JsonTypeRef),
+        StringSelector(JsonStringSelector)
+    },
+    Unshared
+);
+
+#[impl_semantic_processor(
+    g_prop = "JsonProp",
+    si_prop = "JsonInhProp",
+    ss_prop = "JsonSynProp<'a>",
+    grammar_file = "fixing-rs-main/src/json/json"
+)]
+#[allow(non_snake_case)]
+impl<'a> JsonSProcessor<'a> {
+    fn rooti(&self) -> JsonTypeRef {
+        self.schema.root()
+    }
+
+    // nti 0 value: 0 object
+    fn nti_value_0_0(&self, _g: &PropArray<JsonProp>, inh: &JsonTypeRef) -> Option<JsonTypeRef> {
+        self.is_object(*inh).then_some(*inh)
+    }
+
+    // nti 0 value: 1 array
+    fn nti_value_1_0(&self, _g: &PropArray<JsonProp>, inh: &JsonTypeRef) -> Option<JsonTypeRef> {
+        self.element(*inh).map(|_| *inh)
+    }
+
+    // nti 0 value: 2 string
+    fn nti_value_2_0(&self, _g: &PropArray<JsonProp>, inh: &JsonTypeRef) -> Option<JsonTypeRef> {
+        match self.schema.get(*inh) {
+            JsonType::Any | JsonType::String | JsonType::Enum(_) => Some(*inh),
+            _ => None,
+        }
+    }
+
+    // nts value: 3 NUMBER
+    fn nts_value_3(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.accepts(*inh, |x| matches!(x, JsonType::Number))
+    }
+
+    // nts value: 4 'true'
+    fn nts_value_4(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.accepts(*inh, |x| matches!(x, JsonType::Boolean))
+    }
+
+    // nts value: 5 'false'
+    fn nts_value_5(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.accepts(*inh, |x| matches!(x, JsonType::Boolean))
+    }
+
+    // nts value: 6 'null'
+    fn nts_value_6(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.accepts(*inh, |x| matches!(x, JsonType::Null))
+    }
+
+    // nts object: 0 '{' '}'
+    fn nts_object_0(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.has_required(*inh, &[]).then_some(PropEmpty)
+    }
+
+    // nts object: 1 '{' members '}'
+    fn nts_object_1(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+        keys: &JsonKeys<'a>,
+        _s3: &PropEmpty,
+    ) -> Option<PropEmpty> {
+        self.has_required(*inh, &keys.keys).then_some(PropEmpty)
+    }
+
+    // nts members: 0 member
+    fn nts_members_0(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonTypeRef,
+        key: &StringRef<'a>,
+    ) -> JsonKeys<'a> {
+        JsonKeys::new(*key)
+    }
+
+    // nts members: 1 member ',' members
+    fn nts_members_1(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonTypeRef,
+        key: &StringRef<'a>,
+        _s2: &PropEmpty,
+        keys: &JsonKeys<'a>,
+    ) -> Option<JsonKeys<'a>> {
+        keys.insert(*key)
+    }
+
+    // nts member: 0 key ':' value
+    fn nts_member_0(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonTypeRef,
+        key: &StringRef<'a>,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+    ) -> StringRef<'a> {
+        *key
+    }
+
+    // nti 2 member: 0 key ':' value
+    fn nti_member_0_2(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        key: &StringRef<'a>,
+        _s2: &PropEmpty,
+    ) -> Option<JsonTypeRef> {
+        self.field(*inh, key)
+    }
+
+    // nti 0 key: 0 STRING
+    fn nti_key_0_0(&self, _g: &PropArray<JsonProp>, inh: &JsonTypeRef) -> JsonStringSelector {
+        JsonStringSelector::Key(*inh)
+    }
+
+    // nts key: 0 STRING
+    fn nts_key_0(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonTypeRef,
+        key: &StringRef<'a>,
+    ) -> StringRef<'a> {
+        *key
+    }
+
+    // nti 0 string: 0 STRING
+    fn nti_string_0_0(&self, _g: &PropArray<JsonProp>, inh: &JsonTypeRef) -> JsonStringSelector {
+        JsonStringSelector::Value(*inh)
+    }
+
+    // nti 1 array: 1 '[' elements ']'
+    fn nti_array_1_1(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonTypeRef,
+        _s1: &PropEmpty,
+    ) -> Option<JsonTypeRef> {
+        self.element(*inh)
+    }
+
+    // sts STRING
+    fn sts_STRING(
+        &self,
+        _g: &PropArray<JsonProp>,
+        inh: &JsonStringSelector,
+        literal: Option<&str>,
+    ) -> Vec<StringRef<'a>> {
+        let value = match literal {
+            Some(literal) => match Self::decode(literal) {
+                Some(value) => Some(self.str_pool.get(&value).unwrap()),
+                None => return Vec::new(),
+            },
+            None => None,
+        };
+        match inh {
+            JsonStringSelector::Key(ty) => match (value, self.schema.get(*ty)) {
+                (Some(value), _) => match self.field(*ty, &value) {
+                    Some(_) => vec![value],
+                    None => Vec::new(),
+                },
+                (None, JsonType::Object(object)) => object
+                    .fields
+                    .keys()
+                    .map(|x| self.str_pool.get(x).unwrap())
+                    .collect(),
+                (None, _) => Vec::new(),
+            },
+            JsonStringSelector::Value(ty) => match (value, self.schema.get(*ty)) {
+                (Some(value), JsonType::Enum(values)) => match values.contains(&*value) {
+                    true => vec![value],
+                    false => Vec::new(),
+                },
+                (None, JsonType::Enum(values)) => values
+                    .iter()
+                    .map(|x| self.str_pool.get(x).unwrap())
+                    .collect(),
+                (Some(value), _) => vec![value],
+                (None, _) => vec![self.str_pool.get("").unwrap()],
+            },
+        }
+    }
+
+    // stg STRING
+    fn stg_STRING(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonStringSelector,
+        syn: &StringRef<'a>,
+        literal: Option<&str>,
+    ) -> String {
+        match literal {
+            Some(literal) => literal.to_string(),
+            None => serde_json::to_string(syn.as_str()).unwrap(),
+        }
+    }
+
+    // stg NUMBER
+    fn stg_NUMBER(
+        &self,
+        _g: &PropArray<JsonProp>,
+        _inh: &JsonTypeRef,
+        _syn: &PropEmpty,
+        literal: Option<&str>,
+    ) -> String {
+        match literal {
+            Some(literal) => literal.to_string(),
+            None => "0".to_string(),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use fixing_rs_base::{props::PropEmpty, union_prop};
+
+union_prop! {
+    JsonProp,
+    Empty,
+    {
+        Empty(PropEmpty)
+    }
+}
+
+pub struct JsonGProcessor;
+
+extern crate fixing_rs_macros;
+use fixing_rs_macros::impl_syntactic_processor;
+
+#[impl_syntactic_processor(g_prop = "JsonProp", grammar_file = "fixing-rs-main/src/json/json")]
+#[allow(unused, non_snake_case)]
+impl JsonGProcessor {}
@@ -0,0 +1,129 @@
+use fixing_rs_base::{
+    grammar::{GrammarSymbolsRef, OwnedToken, SymbolType},
+    tokenizer::{Token, Tokenizer},
+};
+use json_token_parser::FileParser;
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+};
+
+lalrpop_mod!(json_token_parser, "/grammars/json_tokenizer.rs");
+
+#[derive(Debug)]
+pub enum JsonToken<'input> {
+    LiteralTerminal(&'input str),
+    String(&'input str),
+    Number(&'input str),
+}
+
+pub struct JsonTokenizer;
+
+impl Tokenizer for JsonTokenizer {
+    type ErrType = JsonTokenizerError;
+    fn tokenize<'a, 's>(
+        &mut self,
+        input: &'s str,
+        grammar: GrammarSymbolsRef<'a>,
+    ) -> Result<Vec<Token<'a, 's>>, Self::ErrType> {
+        let symbolic = |name: &str| {
+            grammar
+                .symbolic_terminals
+                .get(name)
+                .copied()
+                .ok_or_else(|| {
+                    JsonTokenizerError::SymbolNotFound(
+                        SymbolType::SymbolicTerminal,
+                        name.to_string(),
+                    )
+                })
+        };
+        let string = symbolic("STRING")?;
+        let number = symbolic("NUMBER")?;
+        let mut result = Vec::new();
+        for token in FileParser::new().parse(input)? {
+            let (literal, symbol) = match token {
+                JsonToken::LiteralTerminal(literal) => {
+                    let symbol =
+                        grammar
+                            .literal_terminals
+                            .get(literal)
+                            .copied()
+                            .ok_or_else(|| {
+                                JsonTokenizerError::SymbolNotFound(
+                                    SymbolType::LiteralTerminal,
+                                    literal.to_string(),
+                                )
+                            })?;
+                    (literal, symbol)
+                }
+                JsonToken::String(literal) => (literal, string),
+                JsonToken::Number(literal) => (literal, number),
+            };
+            result.push(Token { literal, symbol });
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug)]
+pub enum JsonTokenizerError {
+    SyntaxError(lalrpop_util::ParseError<usize, OwnedToken, &'static str>),
+    SymbolNotFound(SymbolType, String),
+}
+
+impl Display for JsonTokenizerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl Error for JsonTokenizerError {}
+
+impl<'input> From<lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'input>, &'static str>>
+    for JsonTokenizerError
+{
+    fn from(
+        input: lalrpop_util::ParseError<usize, lalrpop_util::lexer::Token<'input>, &'static str>,
+    ) -> Self {
+        Self::SyntaxError(input.map_token(|x| x.into()))
+    }
+}
+
+/// The document made of `tokens`, indented by two spaces per level.
+pub fn detokenize(tokens: &[impl AsRef<str>]) -> String {
+    let mut result = String::new();
+    let mut level = 0usize;
+    let newline = |result: &mut String, level: usize| {
+        result.push('\n');
+        result.push_str(&"  ".repeat(level));
+    };
+    for (i, token) in tokens.iter().map(|x| x.as_ref()).enumerate() {
+        let next = tokens.get(i + 1).map(|x| x.as_ref());
+        let previous = i.checked_sub(1).map(|x| tokens[x].as_ref());
+        match token {
+            "{" | "[" => {
+                result.push_str(token);
+                level += 1;
+                if !matches!(next, Some("}") | Some("]")) {
+                    newline(&mut result, level);
+                }
+            }
+            "}" | "]" => {
+                level = level.saturating_sub(1);
+                if !matches!(previous, Some("{") | Some("[")) {
+                    newline(&mut result, level);
+                }
+                result.push_str(token);
+            }
+            "," => {
+                result.push(',');
+                newline(&mut result, level);
+            }
+            ":" => result.push_str(": "),
+            _ => result.push_str(token),
+        }
+    }
+    result.push('\n');
+    result
+}
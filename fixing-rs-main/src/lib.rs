@@ -0,0 +1,35 @@
+#![cfg_attr(nightly, feature(alloc_error_hook))]
+
+#[macro_use]
+extern crate fixing_rs_macros;
+#[macro_use]
+extern crate lalrpop_util;
+
+#[cfg(test)]
+pub mod tests;
+
+/// Counts the memory of the tasks run by the tests, as the binary does.
+#[cfg(all(test, not(feature = "trace_memory")))]
+#[global_allocator]
+static ALLOCATOR: fixing_rs_base::utils::CountingAllocator =
+    fixing_rs_base::utils::CountingAllocator;
+
+pub mod c;
+pub mod check;
+pub mod cmd;
+pub mod eval;
+pub mod fixing;
+pub mod gensrc;
+pub mod grammars;
+pub mod json;
+pub mod lsp;
+pub mod mem_limit;
+pub mod mj;
+pub mod mutate;
+pub mod py;
+pub mod serve;
+
+#[cfg(feature = "trace_memory")]
+pub mod trace_mem;
+
+pub use cmd::run;
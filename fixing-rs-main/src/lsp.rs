@@ -0,0 +1,367 @@
+use crate::{
+    cmd::{language, language_fixer},
+    fixing::{parse_option, FixCmd, Naming},
+};
+use clap::{error::ErrorKind, Parser};
+use fixing_rs_base::{
+    containers::Map,
+    edit_script::edit_script,
+    error_location::ErrorLocation,
+    fixing::FixTaskInfo,
+    grammar::{Grammar, GrammarArena},
+    language::{DocumentFormat, Fixer, LanguageOptions, LanguageRegistry},
+    naming::NamingStrategy,
+};
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, Write},
+    ops::Range,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Serves fixes for documents of a language over the Language Server
+/// Protocol on stdin/stdout. On `textDocument/codeAction`, the input of the
+/// document enclosing the requested range, such as a method body, is fixed
+/// against an environment built from the rest of the document, and each fix
+/// found is offered as a quick fix.
+#[derive(Parser, Clone)]
+pub struct LspCmd {
+    /// Language of the documents, which must have a document format.
+    #[arg(long, default_value = "mj")]
+    lang: String,
+    #[arg(long, default_value_t = 5)]
+    max_len: usize,
+    #[arg(long, default_value_t = 5)]
+    max_new_id: usize,
+    /// Number of code actions offered for a diagnostic.
+    #[arg(long, default_value_t = 3)]
+    max_fixes: usize,
+    /// Time budget of a code action request, in seconds.
+    #[arg(long, default_value_t = 10)]
+    time_limit: u64,
+    #[arg(long, value_enum, default_value_t = Naming::Type)]
+    naming: Naming,
+    #[arg(long)]
+    naming_vocabulary: Option<String>,
+    /// Option of the language, as `KEY=VALUE`; may be repeated.
+    #[arg(long, value_parser = parse_option)]
+    option: Vec<(String, String)>,
+}
+
+impl LspCmd {
+    pub fn run(self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let language = language(registry, &self.lang)?;
+        let format = language.document_format().ok_or_else(|| {
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!("documents of {} cannot be served", language.name()),
+            )
+        })?;
+        let mut options = LanguageOptions::default();
+        options.extend(self.option.iter().cloned());
+        let fixer = language_fixer(language, &options)?;
+        let grammar_arena = GrammarArena::new();
+        let grammar = Grammar::new(&grammar_arena, language.info().grammar).unwrap();
+        let mut server = LspServer::new(&grammar, fixer, format, self);
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        server
+            .serve(&mut stdin.lock(), &mut stdout.lock())
+            .expect("Unable to communicate with the client.");
+        Ok(())
+    }
+}
+
+pub struct LspServer<'a> {
+    grammar: &'a Grammar<'a>,
+    fixer: Box<dyn Fixer>,
+    format: &'a dyn DocumentFormat,
+    cmd: LspCmd,
+    naming: Arc<dyn NamingStrategy>,
+    documents: Map<String, String>,
+}
+
+impl<'a> LspServer<'a> {
+    pub fn new(
+        grammar: &'a Grammar<'a>,
+        fixer: Box<dyn Fixer>,
+        format: &'a dyn DocumentFormat,
+        cmd: LspCmd,
+    ) -> Self {
+        let naming = FixCmd::naming_strategy(cmd.naming, cmd.naming_vocabulary.as_deref());
+        Self {
+            grammar,
+            fixer,
+            format,
+            cmd,
+            naming,
+            documents: Map::default(),
+        }
+    }
+
+    /// Handles messages until the client sends `exit` or closes the stream.
+    /// Responses of the client, which have no method, are ignored.
+    pub fn serve(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<()> {
+        while let Some(message) = read_message(reader)? {
+            let method = match message["method"].as_str() {
+                Some(method) => method,
+                None => continue,
+            };
+            let params = &message["params"];
+            let result = match method {
+                "initialize" => Some(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "codeActionProvider": true,
+                    },
+                    "serverInfo": { "name": "ordinalfix" },
+                })),
+                "shutdown" => Some(Value::Null),
+                "exit" => break,
+                "textDocument/didOpen" => {
+                    let document = &params["textDocument"];
+                    if let (Some(uri), Some(text)) =
+                        (document["uri"].as_str(), document["text"].as_str())
+                    {
+                        self.documents.insert(uri.to_string(), text.to_string());
+                    }
+                    None
+                }
+                "textDocument/didChange" => {
+                    let uri = params["textDocument"]["uri"].as_str();
+                    let changes = params["contentChanges"].as_array();
+                    let text = changes
+                        .and_then(|x| x.last())
+                        .and_then(|x| x["text"].as_str());
+                    if let (Some(uri), Some(text)) = (uri, text) {
+                        self.documents.insert(uri.to_string(), text.to_string());
+                    }
+                    None
+                }
+                "textDocument/didClose" => {
+                    if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                        self.documents.remove(uri);
+                    }
+                    None
+                }
+                "textDocument/codeAction" => Some(self.code_actions(params)),
+                _ => None,
+            };
+            let id = match message.get("id") {
+                Some(id) => id,
+                None => continue,
+            };
+            let response = match result {
+                Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Unknown method {}", method) },
+                }),
+            };
+            write_message(writer, &response)?;
+        }
+        Ok(())
+    }
+
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return json!([]),
+        };
+        let position = |x: &Value| {
+            let line = x["line"].as_u64()? as usize;
+            let character = x["character"].as_u64()? as usize;
+            Some(offset_of(text, line, character))
+        };
+        let start = match position(&params["range"]["start"]) {
+            Some(start) => start,
+            None => return json!([]),
+        };
+        let bodies = self.format.inputs(text);
+        let index = match bodies
+            .iter()
+            .position(|x| x.start <= start && start < x.end)
+        {
+            Some(index) => index,
+            None => return json!([]),
+        };
+        let body = bodies[index].clone();
+        let block = &text[body.clone()];
+        let tokens = match self.format.tokens(self.grammar, block) {
+            Some(tokens) => tokens,
+            None => return json!([]),
+        };
+        let literals: Vec<_> = tokens.iter().map(|x| &block[x.clone()]).collect();
+        let span = |token: usize| body.start + tokens[token].start..body.start + tokens[token].end;
+
+        // Diagnostics point at the last token starting at or before them.
+        let diagnostics = params["context"]["diagnostics"].clone();
+        let mut error_locations = Vec::new();
+        for diagnostic in diagnostics.as_array().into_iter().flatten() {
+            if let Some(offset) = position(&diagnostic["range"]["start"]) {
+                if body.contains(&offset) {
+                    let index = (0..literals.len()).take_while(|x| span(*x).start <= offset);
+                    error_locations.push(ErrorLocation::Token(index.last().unwrap_or(0)));
+                }
+            }
+        }
+
+        let fixes = self.fix_body(uri, text, &bodies, index, error_locations);
+        let mut actions = Vec::new();
+        for (i, fix) in fixes.iter().enumerate() {
+            let edits = edit_script(&literals, fix);
+            if edits.is_empty() {
+                continue;
+            }
+            let mut text_edits = Vec::new();
+            for edit in edits.iter() {
+                let replacement = edit.replacement.join(" ");
+                let (range, new_text) = if edit.begin < edit.end {
+                    (span(edit.begin).start..span(edit.end - 1).end, replacement)
+                } else if edit.begin < literals.len() {
+                    let at = span(edit.begin).start;
+                    (at..at, replacement + " ")
+                } else {
+                    let at = span(literals.len() - 1).end;
+                    (at..at, " ".to_string() + &replacement)
+                };
+                let (start_line, start_character) = position_of(text, range.start);
+                let (end_line, end_character) = position_of(text, range.end);
+                text_edits.push(json!({
+                    "range": {
+                        "start": { "line": start_line, "character": start_character },
+                        "end": { "line": end_line, "character": end_character },
+                    },
+                    "newText": new_text,
+                }));
+            }
+            actions.push(json!({
+                "title": format!("OrdinalFix: apply fix {} ({} edits)", i + 1, edits.len()),
+                "kind": "quickfix",
+                "diagnostics": diagnostics,
+                "isPreferred": i == 0,
+                "edit": { "changes": { uri: text_edits } },
+            }));
+        }
+        Value::Array(actions)
+    }
+
+    fn fix_body(
+        &self,
+        uri: &str,
+        text: &str,
+        bodies: &[Range<usize>],
+        index: usize,
+        error_locations: Vec<ErrorLocation>,
+    ) -> Vec<Vec<String>> {
+        let env = self.format.env(text, bodies, index);
+        let info = FixTaskInfo {
+            max_new_id: self.cmd.max_new_id,
+            error_locations,
+            naming: self.naming.clone(),
+            max_fixes: self.cmd.max_fixes,
+            time_limit: Some(Duration::from_secs(self.cmd.time_limit)),
+            ..FixTaskInfo::new(uri.to_string(), uri.to_string(), self.cmd.max_len)
+        };
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            self.fixer.process_dyn(
+                self.grammar,
+                &text[bodies[index].clone()],
+                &env,
+                &info,
+                Instant::now(),
+            )
+        }));
+        match result {
+            Ok(Ok(result)) => {
+                info!("Fixed {} with length {:?}", uri, result.found_length);
+                result
+                    .outputs
+                    .into_iter()
+                    .chain(result.alternatives)
+                    .collect()
+            }
+            Ok(Err(e)) => {
+                warn!("Unable to fix {}: {:?}", uri, e);
+                Vec::new()
+            }
+            Err(_) => {
+                warn!("Fixing {} panicked", uri);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Reads a message framed by a `Content-Length` header. Returns `None` at the
+/// end of the stream.
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header.")
+    })?;
+    let mut content = vec![0; length];
+    reader.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+/// Byte offset of an LSP position, whose character counts UTF-16 code units.
+pub fn offset_of(text: &str, line: usize, character: usize) -> usize {
+    let mut offset = 0;
+    for _ in 0..line {
+        match text[offset..].find('\n') {
+            Some(x) => offset += x + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[offset..].char_indices() {
+        if units >= character || c == '\n' {
+            return offset + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+/// LSP position, as a 0-based line and UTF-16 character, of a byte offset.
+pub fn position_of(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_begin = before.rfind('\n').map_or(0, |x| x + 1);
+    let character = before[line_begin..].encode_utf16().count();
+    (line, character)
+}
@@ -1,26 +1,12 @@
-#![cfg_attr(nightly, feature(alloc_error_hook))]
+use fixing_rs_base::utils::CountingAllocator;
+use fixing_rs_main::grammars::registry;
 
-#[macro_use]
-extern crate fixing_rs_macros;
-#[macro_use]
-extern crate lalrpop_util;
-
-#[cfg(test)]
-pub mod tests;
-
-pub mod c;
-pub mod cmd;
-pub mod fixing;
-pub mod gensrc;
-pub mod grammars;
-pub mod mem_limit;
-pub mod mj;
-
-#[cfg(feature = "trace_memory")]
-pub mod trace_mem;
+#[cfg(not(feature = "trace_memory"))]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
-    cmd::run();
+    fixing_rs_main::run(registry());
 }
 
 #[ctor::ctor]
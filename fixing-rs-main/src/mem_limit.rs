@@ -66,3 +66,9 @@ fn oom_handler(layout: Layout) {
 fn set_oom_handler() {
     std::alloc::set_alloc_error_hook(oom_handler);
 }
+
+/// Whether an allocation has failed under the limit. The process should not
+/// be trusted with further work once this is set.
+pub fn oom_triggered() -> bool {
+    OOM_TRIGGERED.load(Ordering::SeqCst)
+}
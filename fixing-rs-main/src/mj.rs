@@ -1,4 +1,5 @@
 pub mod ast;
+pub mod document;
 pub mod fixing;
 pub mod mjenv;
 pub mod mjsymtab;
@@ -0,0 +1,80 @@
+use super::tokenizer::MJTokenizer;
+use fixing_rs_base::{grammar::Grammar, language::DocumentFormat, tokenizer::Tokenizer};
+use std::ops::Range;
+
+/// Middleweight Java documents, whose constructor and method bodies are
+/// fixed against the classes of the document.
+pub struct MJDocumentFormat;
+
+impl DocumentFormat for MJDocumentFormat {
+    fn inputs(&self, text: &str) -> Vec<Range<usize>> {
+        method_bodies(text)
+    }
+
+    fn env(&self, text: &str, inputs: &[Range<usize>], _index: usize) -> String {
+        env_from_document(text, inputs)
+    }
+
+    fn tokens<'a>(&self, grammar: &'a Grammar<'a>, input: &str) -> Option<Vec<Range<usize>>> {
+        let tokens = MJTokenizer.tokenize(input, grammar.get_symbol_ref()).ok()?;
+        let spans = tokens.iter().map(|x| {
+            let begin = x.literal.as_ptr() as usize - input.as_ptr() as usize;
+            begin..begin + x.literal.len()
+        });
+        Some(spans.collect())
+    }
+}
+
+/// Byte ranges of the constructor and method bodies, braces included, of a
+/// Middleweight Java document. Comments are skipped.
+pub fn method_bodies(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut result = Vec::new();
+    let mut depth = 0usize;
+    let mut begin = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"//") {
+            i += bytes[i..]
+                .iter()
+                .position(|x| *x == b'\n')
+                .unwrap_or(bytes.len() - i);
+            continue;
+        }
+        if bytes[i..].starts_with(b"/*") {
+            i += text[i + 2..].find("*/").map_or(bytes.len() - i, |x| x + 4);
+            continue;
+        }
+        match bytes[i] {
+            b'{' => {
+                depth += 1;
+                if depth == 2 {
+                    begin = i;
+                }
+            }
+            b'}' => {
+                if depth == 2 {
+                    result.push(begin..i + 1);
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    result
+}
+
+/// The document with every body emptied, which the environment parser
+/// accepts whatever state the bodies are in.
+pub fn env_from_document(text: &str, bodies: &[Range<usize>]) -> String {
+    let mut result = String::new();
+    let mut current = 0;
+    for body in bodies {
+        result.push_str(&text[current..body.start]);
+        result.push_str("{}");
+        current = body.end;
+    }
+    result.push_str(&text[current..]);
+    result
+}
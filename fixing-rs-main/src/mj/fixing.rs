@@ -10,6 +10,7 @@ use fixing_rs_base::{
     },
     fixing_info::FixingInfo,
     grammar::Grammar,
+    naming::{is_identifier, NamingScope},
     tokenizer::Tokenizer,
     utils::RefArena,
 };
@@ -43,14 +44,38 @@ impl FixingInputProcessor for MJFixingInputProcessor {
         let strs = RefArena::new();
         let gproc = MJGProcessor;
         let sproc = MJSProcessor::new(&env, &strs, &tokens, info.max_new_id);
+        let naming_scope = NamingScope::new(
+            env.iter_names()
+                .map(|x| x.to_string())
+                .chain(tokens.iter().map(|x| x.literal.to_string())),
+            mj_declared_type,
+        );
 
-        do_fix.do_fix(grammar, &tokens, &gproc, &sproc, info, time_before_load)
+        do_fix.do_fix(
+            grammar,
+            None,
+            &tokens,
+            &gproc,
+            &sproc,
+            naming_scope,
+            info,
+            time_before_load,
+        )
     }
 
     type TokenizerError = MJTokenizerError;
     type EnvLoadError = MJParseError;
 }
 
+/// New identifiers are only introduced by `className newIdentifier ';'`.
+fn mj_declared_type(tokens: &[String], position: usize) -> Option<String> {
+    position
+        .checked_sub(1)
+        .map(|x| &tokens[x])
+        .filter(|x| is_identifier(x))
+        .cloned()
+}
+
 pub const MJ_GRAMMAR: &str = include_str!("middle_weight_java");
 pub const MJ_GRAMMAR_FILE: &str = "src/mj/middle_weight_java";
 pub const MJ_PROP_G: &str = "MJProp";
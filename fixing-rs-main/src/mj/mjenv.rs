@@ -1,6 +1,6 @@
 use super::ast::{parse_ast, MJAstCls};
 use fixing_rs_base::{
-    containers::{Map, Set},
+    containers::{OrderedMap, Set},
     grammar::OwnedToken,
     utils::{Pointer, RefArena, StringRef},
 };
@@ -40,8 +40,8 @@ pub type MJMethodRef<'a> = Pointer<'a, MJMethod<'a>>;
 pub struct MJEnv<'a> {
     arena: &'a MJArena<'a>,
     #[get = "pub"]
-    names: Map<&'a str, StringRef<'a>>,
-    classes: Map<&'a str, MJClsRef<'a>>,
+    names: OrderedMap<&'a str, StringRef<'a>>,
+    classes: OrderedMap<&'a str, MJClsRef<'a>>,
     default_classes: MJDefaultClasses<'a>,
 }
 
@@ -85,12 +85,12 @@ pub struct MJDefaultClasses<'a> {
 
 impl<'a> MJEnv<'a> {
     pub fn new(arena: &'a MJArena<'a>, ast: Vec<MJAstCls<'_>>) -> Result<Self, MJParseError> {
-        let mut names = Map::new();
+        let mut names = OrderedMap::new();
         let default_classes = Self::allocate_default(arena, &mut names);
         let mut result = Self {
             arena,
             names,
-            classes: Map::new(),
+            classes: OrderedMap::new(),
             default_classes,
         };
         result.add_default_object();
@@ -104,7 +104,7 @@ impl<'a> MJEnv<'a> {
         arena: &'a MJArena<'a>,
         name: &str,
         has_con: bool,
-        names: &mut Map<&'a str, StringRef<'a>>,
+        names: &mut OrderedMap<&'a str, StringRef<'a>>,
     ) -> MJClsRef<'a> {
         let name = name.to_string();
         let name = arena.names.alloc(name);
@@ -112,10 +112,10 @@ impl<'a> MJEnv<'a> {
             name,
             content: RefCell::new(MJClsContent {
                 inh: None,
-                fields: Map::new(),
+                fields: OrderedMap::new(),
                 constructor: None,
-                methods: Map::new(),
-                supers: Set::new(),
+                methods: OrderedMap::new(),
+                supers: Set::default(),
             }),
         });
 
@@ -133,7 +133,7 @@ impl<'a> MJEnv<'a> {
 
     fn allocate_default(
         arena: &'a MJArena<'a>,
-        names: &mut Map<&'a str, StringRef<'a>>,
+        names: &mut OrderedMap<&'a str, StringRef<'a>>,
     ) -> MJDefaultClasses<'a> {
         let object = Self::allocate_cls(arena, OBJECT_CLASS_NAME, true, names);
         let void = Self::allocate_cls(arena, VOID_CLASS_NAME, false, names);
@@ -180,10 +180,10 @@ impl<'a> MJEnv<'a> {
     fn new_content() -> RefCell<MJClsContent<'a>> {
         RefCell::new(MJClsContent {
             inh: None,
-            fields: Map::new(),
+            fields: OrderedMap::new(),
             constructor: None,
-            methods: Map::new(),
-            supers: Set::new(),
+            methods: OrderedMap::new(),
+            supers: Set::default(),
         })
     }
 
@@ -403,11 +403,11 @@ pub struct MJClsContent<'a> {
     #[getset(get_copy = "pub")]
     inh: Option<MJClsRef<'a>>,
     #[getset(get = "pub")]
-    fields: Map<&'a str, MJFieldRef<'a>>,
+    fields: OrderedMap<&'a str, MJFieldRef<'a>>,
     #[getset(get_copy = "pub")]
     constructor: Option<MJConstructorRef<'a>>,
     #[getset(get = "pub")]
-    methods: Map<&'a str, MJMethodRef<'a>>,
+    methods: OrderedMap<&'a str, MJMethodRef<'a>>,
     #[getset(get = "pub")]
     supers: Set<MJClsRef<'a>>,
 }
@@ -1,15 +1,9 @@
 use super::mjenv::{MJClsRef, MJConstructorRef, MJMethodRef};
-use fixing_rs_base::{containers::Map, utils::StringRef};
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-    rc::Rc,
-};
+use fixing_rs_base::utils::{StringRef, SymTab};
 
-#[derive(Debug, Clone, Prop)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
 pub struct MJSymTab<'a> {
-    pub decled_vars: Rc<Map<StringRef<'a>, MJClsRef<'a>>>,
-    hash: u64,
+    decled_vars: SymTab<StringRef<'a>, MJClsRef<'a>>,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Prop)]
@@ -30,33 +24,15 @@ impl<'a> MJDecl<'a> {
 
 impl<'a> MJSymTab<'a> {
     pub fn new() -> Self {
-        Self::from_map(Map::new())
-    }
-
-    fn from_map(map: Map<StringRef<'a>, MJClsRef<'a>>) -> Self {
-        let hash = Self::compute_hash(&map);
         Self {
-            decled_vars: Rc::new(map),
-            hash,
-        }
-    }
-
-    fn compute_hash(m: &Map<StringRef<'a>, MJClsRef<'a>>) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        let mut keys = m.keys().collect::<Vec<_>>();
-        keys.sort();
-        for key in keys {
-            let val = m.get(key).unwrap();
-            key.hash(&mut hasher);
-            val.hash(&mut hasher);
+            decled_vars: SymTab::new(),
         }
-        hasher.finish()
     }
 
     pub fn extend(&self, name: StringRef<'a>, ty: MJClsRef<'a>) -> Self {
-        let mut new_map = self.decled_vars.as_ref().clone();
-        new_map.insert(name, ty);
-        Self::from_map(new_map)
+        Self {
+            decled_vars: self.decled_vars.extend(name, ty),
+        }
     }
 
     pub fn extend_decl(&self, decl: &MJDecl<'a>) -> Self {
@@ -68,7 +44,7 @@ impl<'a> MJSymTab<'a> {
     }
 
     pub fn get(&self, name: StringRef<'a>) -> Option<MJClsRef<'a>> {
-        self.decled_vars.get(&name).copied()
+        self.decled_vars.get(name)
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&StringRef<'a>, &MJClsRef<'a>)> {
@@ -82,26 +58,6 @@ impl Default for MJSymTab<'_> {
     }
 }
 
-impl PartialEq for MJSymTab<'_> {
-    fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.decled_vars, &other.decled_vars)
-            || (self.hash == other.hash
-                && self.decled_vars.len() == other.decled_vars.len()
-                && self
-                    .decled_vars
-                    .iter()
-                    .all(|(k, v)| other.decled_vars.get(k).map_or(false, |v2| v == v2)))
-    }
-}
-
-impl Eq for MJSymTab<'_> {}
-
-impl Hash for MJSymTab<'_> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.hash.hash(state);
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
 pub enum MJIdSelector<'a> {
     Identifier(MJSymTab<'a>),
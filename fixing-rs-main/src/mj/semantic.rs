@@ -1,11 +1,11 @@
-use std::collections::HashSet;
-
 use super::{
     mjenv::{MJClsRef, MJEnv},
     mjsymtab::{MJArgs, MJDecl, MJIdSelected, MJIdSelector, MJSymTab},
     syntactic::MJProp,
 };
 use fixing_rs_base::{
+    containers::OrderedSet,
+    naming::new_id_placeholder,
     props::{PropArray, PropEmpty},
     tokenizer::Token,
     union_prop,
@@ -27,7 +27,7 @@ impl<'a> MJSProcessor<'a> {
     ) -> Self {
         let mut str_pool = StringPool::new(arena);
 
-        let mut all_identifiers = HashSet::new();
+        let mut all_identifiers = OrderedSet::new();
         for token in tokens {
             if token.symbol.name() == "IDENTIFIER" {
                 all_identifiers.insert(token.literal.to_owned());
@@ -37,7 +37,7 @@ impl<'a> MJSProcessor<'a> {
             all_identifiers.insert(token.to_string());
         }
         for i in 0..max_new_id {
-            all_identifiers.insert(format!("__new_id_{}", i));
+            all_identifiers.insert(new_id_placeholder(i));
         }
 
         let mut identifiers = Vec::new();
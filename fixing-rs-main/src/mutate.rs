@@ -0,0 +1,377 @@
+use crate::cmd::language;
+use clap::{Parser, ValueEnum};
+use csv::Writer;
+use fixing_rs_base::{
+    containers::{Map, OrderedSet},
+    grammar::{Grammar, GrammarArena},
+    language::{LanguageRegistry, TokenFile},
+    naming::is_identifier,
+    utils::Rng,
+};
+use log::{info, warn};
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+/// Attempts at making a mutant before giving up on it, e.g. because no token
+/// of the chosen category is left to delete.
+const ATTEMPTS: usize = 5;
+
+/// Tokens a mutation may apply to.
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Category {
+    /// Literal terminals that are not words, such as `;` and `==`.
+    Punctuation,
+    /// Literal terminals that are words, such as `return`.
+    Keywords,
+    Identifiers,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operation {
+    Insert,
+    Delete,
+    /// Replace a token with another one of the same category.
+    Replace,
+    Duplicate,
+}
+
+/// A mutation applied to a mutant, written `operation-category`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Mutation {
+    pub operation: Operation,
+    pub category: Category,
+}
+
+impl Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.operation.to_possible_value().unwrap().get_name(),
+            self.category.to_possible_value().unwrap().get_name()
+        )
+    }
+}
+
+/// Makes benchmark tasks by applying random mutations to a correct program.
+#[derive(Parser, Clone)]
+pub struct MutateCmd {
+    #[arg(long)]
+    lang: String,
+    #[arg(long)]
+    input: String,
+    #[arg(long)]
+    env: String,
+    /// Directory the mutants are written to, each in a numbered directory
+    /// holding the mutated input, the env and the expected fix.
+    /// `manifest.csv` lists them for `eval` and `file_list.csv` for
+    /// `fix multiple`.
+    #[arg(long)]
+    out: String,
+    /// Mutations applied to make each mutant.
+    #[arg(long, default_value_t = 1)]
+    mutations: usize,
+    #[arg(long, default_value_t = 1)]
+    mutants: usize,
+    /// Category of the tokens mutated; may be repeated. All categories by
+    /// default.
+    #[arg(long, value_enum)]
+    category: Vec<Category>,
+    /// Operation mutating a token; may be repeated. All operations by
+    /// default.
+    #[arg(long, value_enum)]
+    operation: Vec<Operation>,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+/// A line of a token file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenLine {
+    pub text: String,
+    /// The literal of the token on the line, if there is one.
+    pub literal: Option<String>,
+    /// The category of the token, if mutations may apply to it.
+    pub category: Option<Category>,
+}
+
+/// Applies random mutations to the lines of a token file.
+pub struct Mutator {
+    format: TokenFile,
+    /// Literal terminals of the grammar by category.
+    terminals: Map<Category, Vec<String>>,
+    categories: Vec<Category>,
+    operations: Vec<Operation>,
+}
+
+impl Mutator {
+    /// A mutator of token files laid out as `format`, mutating tokens of
+    /// `categories` with `operations`, all of them if empty.
+    pub fn new<'s>(
+        format: TokenFile,
+        literal_terminals: impl IntoIterator<Item = &'s str>,
+        categories: &[Category],
+        operations: &[Operation],
+    ) -> Self {
+        let mut terminals: Map<Category, Vec<String>> = Map::default();
+        for terminal in literal_terminals {
+            terminals
+                .entry(Self::terminal_category(terminal))
+                .or_default()
+                .push(terminal.to_string());
+        }
+        let categories = match categories {
+            [] => Category::value_variants().to_vec(),
+            _ => categories.to_vec(),
+        };
+        let operations = match operations {
+            [] => Operation::value_variants().to_vec(),
+            _ => operations.to_vec(),
+        };
+        Self {
+            format,
+            terminals,
+            categories,
+            operations,
+        }
+    }
+
+    fn terminal_category(terminal: &str) -> Category {
+        if is_identifier(terminal) {
+            Category::Keywords
+        } else {
+            Category::Punctuation
+        }
+    }
+
+    fn is_terminal(&self, name: &str) -> bool {
+        self.terminals
+            .get(&Self::terminal_category(name))
+            .is_some_and(|x| x.iter().any(|x| x == name))
+    }
+
+    pub fn read(&self, text: &str) -> Vec<TokenLine> {
+        text.lines().map(|x| self.read_line(x)).collect()
+    }
+
+    fn read_line(&self, text: &str) -> TokenLine {
+        let mut line = TokenLine {
+            text: text.to_string(),
+            literal: None,
+            category: None,
+        };
+        let token = text.trim();
+        match self.format {
+            TokenFile::Lines => {
+                if token.is_empty() {
+                    return line;
+                }
+                line.literal = Some(token.to_string());
+                if self.is_terminal(token) {
+                    line.category = Some(Self::terminal_category(token));
+                } else if is_identifier(token) {
+                    line.category = Some(Category::Identifiers);
+                }
+            }
+            TokenFile::Tagged => {
+                if token.is_empty() || token.starts_with('#') {
+                    return line;
+                }
+                let fields: Vec<&str> = token.split('\t').collect();
+                if let [ty, name, literal] = fields[..] {
+                    line.literal = Some(literal.to_string());
+                    line.category = match ty {
+                        "LT" if self.is_terminal(name) => Some(Self::terminal_category(name)),
+                        "ST" if name == "IDENTIFIER" => Some(Category::Identifiers),
+                        _ => None,
+                    };
+                }
+            }
+        }
+        line
+    }
+
+    /// A line holding a new token `literal` of `category`.
+    fn new_line(&self, literal: &str, category: Category) -> TokenLine {
+        let text = match (self.format, category) {
+            (TokenFile::Lines, _) => literal.to_string(),
+            (TokenFile::Tagged, Category::Identifiers) => format!("ST\tIDENTIFIER\t{}", literal),
+            (TokenFile::Tagged, _) => format!("LT\t{}\t{}", literal, literal),
+        };
+        TokenLine {
+            text,
+            literal: Some(literal.to_string()),
+            category: Some(category),
+        }
+    }
+
+    /// `lines` with `count` mutations applied, differing from `lines`, with
+    /// the mutations in the order they were applied, or `None` if no such
+    /// mutant was found in [`ATTEMPTS`] attempts. Identifiers inserted are
+    /// taken from those of `lines`.
+    pub fn mutate(
+        &self,
+        lines: &[TokenLine],
+        count: usize,
+        rng: &mut Rng,
+    ) -> Option<(Vec<TokenLine>, Vec<Mutation>)> {
+        let identifiers: OrderedSet<&str> = lines
+            .iter()
+            .filter(|x| x.category == Some(Category::Identifiers))
+            .flat_map(|x| x.literal.as_deref())
+            .collect();
+        let identifiers: Vec<&str> = identifiers.into_iter().collect();
+        let original = literals(lines);
+        for _ in 0..ATTEMPTS {
+            let mut mutant = lines.to_vec();
+            let mutations: Option<Vec<Mutation>> = (0..count)
+                .map(|_| self.apply(&mut mutant, &identifiers, rng))
+                .collect();
+            match mutations {
+                Some(mutations) if literals(&mutant) != original => {
+                    return Some((mutant, mutations))
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Applies one mutation, returning it if one could be applied.
+    fn apply(
+        &self,
+        lines: &mut Vec<TokenLine>,
+        identifiers: &[&str],
+        rng: &mut Rng,
+    ) -> Option<Mutation> {
+        let category = *rng.choose(&self.categories).unwrap();
+        let operation = *rng.choose(&self.operations).unwrap();
+        let candidates: Vec<&str> = match category {
+            Category::Identifiers => identifiers.to_vec(),
+            _ => match self.terminals.get(&category) {
+                Some(x) => x.iter().map(|x| x.as_str()).collect(),
+                None => Vec::new(),
+            },
+        };
+        if operation == Operation::Insert {
+            let literal = rng.choose(&candidates)?;
+            let position = rng.below(lines.len() + 1);
+            lines.insert(position, self.new_line(literal, category));
+            return Some(Mutation {
+                operation,
+                category,
+            });
+        }
+        let locations: Vec<usize> = (0..lines.len())
+            .filter(|x| lines[*x].category == Some(category))
+            .collect();
+        let location = *rng.choose(&locations)?;
+        match operation {
+            Operation::Insert => unreachable!(),
+            Operation::Delete => {
+                lines.remove(location);
+            }
+            Operation::Duplicate => lines.insert(location, lines[location].clone()),
+            Operation::Replace => {
+                let old = lines[location].literal.as_deref();
+                let candidates: Vec<&str> =
+                    candidates.into_iter().filter(|x| Some(*x) != old).collect();
+                let literal = rng.choose(&candidates)?;
+                lines[location] = self.new_line(literal, category);
+            }
+        }
+        Some(Mutation {
+            operation,
+            category,
+        })
+    }
+}
+
+/// The literals of the tokens of `lines`, as in the output of `fix`.
+pub fn literals(lines: &[TokenLine]) -> Vec<String> {
+    lines.iter().flat_map(|x| x.literal.clone()).collect()
+}
+
+impl MutateCmd {
+    pub fn run(self, registry: &LanguageRegistry) -> Result<(), clap::Error> {
+        let language = language(registry, &self.lang)?;
+        let format = language
+            .token_file()
+            .unwrap_or_else(|| panic!("Token files of {} cannot be mutated.", language.name()));
+        let arena = GrammarArena::new();
+        let grammar = Grammar::new(&arena, language.info().grammar).unwrap();
+        let mutator = Mutator::new(
+            format,
+            grammar.get_symbol_ref().literal_terminals.keys().copied(),
+            &self.category,
+            &self.operation,
+        );
+
+        let input = fs::read_to_string(&self.input).expect("Unable to read input.");
+        let env = fs::read_to_string(&self.env).expect("Unable to read env.");
+        let lines = mutator.read(&input);
+        let expected = literals(&lines).join("\n");
+        let input_name = Path::new(&self.input)
+            .file_name()
+            .expect("Input is not a file.");
+        let env_name = Path::new(&self.env)
+            .file_name()
+            .expect("Env is not a file.");
+
+        let out = Path::new(&self.out);
+        fs::create_dir_all(out).expect("Unable to create output directory.");
+        let mut manifest =
+            Writer::from_path(out.join("manifest.csv")).expect("Unable to write manifest.");
+        let mut file_list =
+            Writer::from_path(out.join("file_list.csv")).expect("Unable to write file list.");
+        manifest
+            .write_record(["input", "env", "expected", "category"])
+            .unwrap();
+        file_list.write_record(["input", "env", "output"]).unwrap();
+
+        let mut rng = Rng::new(self.seed);
+        let mut written = 0;
+        for index in 0..self.mutants {
+            let (mutant, mutations) = match mutator.mutate(&lines, self.mutations, &mut rng) {
+                Some(x) => x,
+                None => {
+                    warn!("No mutant {} found.", index);
+                    continue;
+                }
+            };
+            let dir = out.join(index.to_string());
+            fs::create_dir_all(&dir).expect("Unable to create mutant directory.");
+            let mutant_input = dir.join(input_name);
+            let mutant_env = dir.join(env_name);
+            let mutant_expected = dir.join("expected");
+            let text: Vec<&str> = mutant.iter().map(|x| x.text.as_str()).collect();
+            fs::write(&mutant_input, text.join("\n")).expect("Unable to write mutant.");
+            fs::write(&mutant_env, &env).expect("Unable to write env.");
+            fs::write(&mutant_expected, &expected).expect("Unable to write expected fix.");
+            let [input, env, expected, output] = [
+                &mutant_input,
+                &mutant_env,
+                &mutant_expected,
+                &dir.join("output"),
+            ]
+            .map(|x| x.to_string_lossy().to_string());
+            let category = mutations
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join("+");
+            manifest
+                .write_record([&input, &env, &expected, &category])
+                .unwrap();
+            file_list.write_record([&input, &env, &output]).unwrap();
+            written += 1;
+        }
+        manifest.flush().unwrap();
+        file_list.flush().unwrap();
+        info!("Wrote {} mutants to {}.", written, self.out);
+        Ok(())
+    }
+}
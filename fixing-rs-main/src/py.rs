@@ -0,0 +1,6 @@
+pub mod fixing;
+pub mod pyenv;
+pub mod pysymtab;
+pub mod semantic;
+pub mod syntactic;
+pub mod tokenizer;
@@ -0,0 +1,94 @@
+use super::{
+    pyenv::{PyEnv, PyEnvError},
+    semantic::PySProcessor,
+    syntactic::PyGProcessor,
+    tokenizer::{detokenize, PyTokenizer, PyTokenizerError},
+};
+use fixing_rs_base::{
+    fixing::{
+        FixTaskError, FixTaskInfo, FixTaskResult, FixingInputProcessor, FixingInputProcessorBase,
+    },
+    fixing_info::FixingInfo,
+    grammar::Grammar,
+    naming::NamingScope,
+    tokenizer::Tokenizer,
+    utils::RefArena,
+};
+use std::time::Instant;
+
+pub struct PyFixingInputProcessor;
+
+impl FixingInputProcessorBase for PyFixingInputProcessor {
+    fn info(&self) -> &'static FixingInfo {
+        &PY_FIXING_INFO
+    }
+}
+
+impl FixingInputProcessor for PyFixingInputProcessor {
+    fn process<'a>(
+        &self,
+        grammar: &'a Grammar<'a>,
+        input_str: &str,
+        env_str: &str,
+        info: &FixTaskInfo,
+        time_before_load: Instant,
+        do_fix: impl fixing_rs_base::fixing::DoFix,
+    ) -> Result<FixTaskResult, FixTaskError<Self::TokenizerError, Self::EnvLoadError>> {
+        let symbol_ref = grammar.get_symbol_ref();
+        let env = PyEnv::build(env_str).map_err(|e| FixTaskError::EnvLoadError(e))?;
+        let tokens = PyTokenizer
+            .tokenize(input_str, symbol_ref)
+            .map_err(|e| FixTaskError::TokenizerError(e))?;
+        let strs = RefArena::new();
+        let gproc = PyGProcessor;
+        let sproc = PySProcessor::new(&env, &strs, &tokens, info.max_new_id);
+        let naming_scope = NamingScope::new(
+            env.iter()
+                .map(|(x, _)| x.to_string())
+                .chain(tokens.iter().map(|x| x.literal.to_string())),
+            |_, _| None,
+        );
+
+        let result = do_fix.do_fix(
+            grammar,
+            Some(input_str),
+            &tokens,
+            &gproc,
+            &sproc,
+            naming_scope,
+            info,
+            time_before_load,
+        )?;
+        // The output file holds the fixed source rather than its tokens, as
+        // the layout of a Python program is significant.
+        if let (Some(output), Some(outputs)) = (&info.output_name, &result.outputs) {
+            std::fs::write(output, detokenize(outputs)).expect("Unable to write output file.");
+        }
+        Ok(result)
+    }
+
+    type TokenizerError = PyTokenizerError;
+    type EnvLoadError = PyEnvError;
+}
+
+pub const PY_GRAMMAR: &str = include_str!("python");
+pub const PY_GRAMMAR_FILE: &str = "src/py/python";
+pub const PY_PROP_G: &str = "PyProp";
+pub const PY_PROP_SI: &str = "PyInhProp";
+pub const PY_PROP_SS: &str = "PySynProp";
+pub const PY_ENTITY_I: &str = "PyInhEntity<'s>";
+pub const PY_CONTAINER_I: &str = "PyInhEntityArena";
+pub const PY_ENTITY_S: &str = "PySynEntity<'s>";
+pub const PY_CONTAINER_S: &str = "PySynEntityArena";
+
+pub const PY_FIXING_INFO: FixingInfo = FixingInfo {
+    grammar: PY_GRAMMAR,
+    grammar_file: PY_GRAMMAR_FILE,
+    prop_g: PY_PROP_G,
+    prop_si: PY_PROP_SI,
+    prop_ss: PY_PROP_SS,
+    entity_i: PY_ENTITY_I,
+    entity_s: PY_ENTITY_S,
+    container_i: PY_CONTAINER_I,
+    container_s: PY_CONTAINER_S,
+};
@@ -0,0 +1,125 @@
+use fixing_rs_base::{containers::OrderedMap, grammar::OwnedToken};
+use lalrpop_util::lexer::Token;
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+lalrpop_mod!(py_env_parser, "/grammars/py_env.rs");
+
+pub enum PyEnvAstItem<'input> {
+    Func(&'input str, Vec<PyEnvAstParam>),
+    Var(&'input str),
+}
+
+pub enum PyEnvAstParam {
+    Required,
+    Optional,
+    Variadic,
+    Keywords,
+}
+
+/// The numbers of positional arguments a function accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PyArity {
+    pub min: usize,
+    /// `None` for functions taking `*args`.
+    pub max: Option<usize>,
+}
+
+impl PyArity {
+    pub fn exact(n: usize) -> Self {
+        Self {
+            min: n,
+            max: Some(n),
+        }
+    }
+
+    pub fn accepts(&self, n: usize) -> bool {
+        self.min <= n && self.max.is_none_or(|max| n <= max)
+    }
+
+    /// Whether more than `n` arguments may be passed.
+    pub fn accepts_more(&self, n: usize) -> bool {
+        self.max.is_none_or(|max| n < max)
+    }
+}
+
+/// What a name is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PyBinding {
+    Var,
+    Func(PyArity),
+}
+
+#[derive(Debug)]
+pub enum PyEnvError {
+    SyntaxError(lalrpop_util::ParseError<usize, OwnedToken, &'static str>),
+    DuplicateName(String),
+    InvalidParams(String),
+}
+
+impl Display for PyEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl Error for PyEnvError {}
+
+impl<'input> From<lalrpop_util::ParseError<usize, Token<'input>, &'static str>> for PyEnvError {
+    fn from(input: lalrpop_util::ParseError<usize, Token<'input>, &'static str>) -> Self {
+        Self::SyntaxError(input.map_token(|x| x.into()))
+    }
+}
+
+/// The builtins a program may use, given as stubs: `def len(obj): ...`
+/// declares a function, `x: int` or `x = ...` a variable. Only positional
+/// arities are checked, so parameters with defaults are optional and `*args`
+/// takes any number of arguments.
+#[derive(Debug, Default)]
+pub struct PyEnv {
+    names: OrderedMap<String, PyBinding>,
+}
+
+impl PyEnv {
+    pub fn build(env: &str) -> Result<Self, PyEnvError> {
+        let mut result = Self::default();
+        for item in py_env_parser::FileParser::new().parse(env)? {
+            let (name, binding) = match item {
+                PyEnvAstItem::Func(name, params) => (name, Self::arity(name, &params)?),
+                PyEnvAstItem::Var(name) => (name, PyBinding::Var),
+            };
+            if result.names.insert(name.to_string(), binding).is_some() {
+                return Err(PyEnvError::DuplicateName(name.to_string()));
+            }
+        }
+        Ok(result)
+    }
+
+    fn arity(name: &str, params: &[PyEnvAstParam]) -> Result<PyBinding, PyEnvError> {
+        let mut arity = PyArity::exact(0);
+        for param in params {
+            match (param, arity.max) {
+                (PyEnvAstParam::Required, Some(max)) if arity.min == max => {
+                    arity = PyArity::exact(max + 1)
+                }
+                (PyEnvAstParam::Optional, Some(max)) => arity.max = Some(max + 1),
+                (PyEnvAstParam::Variadic, Some(_)) => arity.max = None,
+                (PyEnvAstParam::Keywords, _) => {}
+                // Keyword-only parameters follow `*args`.
+                (PyEnvAstParam::Optional, None) => {}
+                _ => return Err(PyEnvError::InvalidParams(name.to_string())),
+            }
+        }
+        Ok(PyBinding::Func(arity))
+    }
+
+    pub fn get(&self, name: &str) -> Option<PyBinding> {
+        self.names.get(name).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PyBinding)> {
+        self.names.iter()
+    }
+}
@@ -0,0 +1,159 @@
+use super::pyenv::{PyArity, PyBinding};
+use fixing_rs_base::utils::{StringRef, SymTab};
+
+/// Names bound so far in the scope being fixed. Builtins of the env are not
+/// included; they are looked up by the processor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct PySymTab<'a> {
+    names: SymTab<StringRef<'a>, PyBinding>,
+    /// Whether the scope is the body of a function, where `return` may
+    /// appear and the functions of the module are visible.
+    pub in_function: bool,
+    /// Whether `break` and `continue` may appear.
+    pub in_loop: bool,
+}
+
+impl<'a> PySymTab<'a> {
+    pub fn new() -> Self {
+        Self {
+            names: SymTab::new(),
+            in_function: false,
+            in_loop: false,
+        }
+    }
+
+    pub fn extend(&self, binds: &PyBinds<'a>) -> Self {
+        let names = binds
+            .binds
+            .iter()
+            .fold(self.names.clone(), |names, (name, binding)| {
+                names.extend(*name, *binding)
+            });
+        Self {
+            names,
+            ..self.clone()
+        }
+    }
+
+    /// The scope of the body of a function defined in this scope.
+    pub fn function(&self, binds: &PyBinds<'a>) -> Self {
+        let result = Self {
+            in_function: true,
+            in_loop: false,
+            ..self.clone()
+        };
+        result.extend(binds)
+    }
+
+    /// The scope of the body of a loop.
+    pub fn in_loop(&self) -> Self {
+        Self {
+            in_loop: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn get(&self, name: StringRef<'a>) -> Option<PyBinding> {
+        self.names.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&StringRef<'a>, &PyBinding)> {
+        self.names.iter()
+    }
+}
+
+impl Default for PySymTab<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Names bound by a statement, in binding order.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Prop, Default)]
+pub struct PyBinds<'a> {
+    pub binds: Vec<(StringRef<'a>, PyBinding)>,
+}
+
+impl<'a> PyBinds<'a> {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn new(name: StringRef<'a>, binding: PyBinding) -> Self {
+        Self {
+            binds: vec![(name, binding)],
+        }
+    }
+
+    /// The names bound by `self` and then by `other`.
+    pub fn then(&self, other: &PyBinds<'a>) -> Self {
+        let mut binds = self.binds.clone();
+        for (name, binding) in other.binds.iter() {
+            binds.retain(|(x, _)| x != name);
+            binds.push((*name, *binding));
+        }
+        Self { binds }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub enum PyIdSelector<'a> {
+    /// Any bound name.
+    Name(PySymTab<'a>),
+    /// A bound function.
+    Func(PySymTab<'a>),
+    /// A bound variable.
+    Var(PySymTab<'a>),
+    /// A name being bound.
+    NewName,
+    /// An attribute, which is not checked.
+    Attr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub enum PyIdSelected<'a> {
+    Bound,
+    NewName(StringRef<'a>),
+    Func(PyArity),
+}
+
+impl<'a> PyIdSelected<'a> {
+    pub fn unwrap_new_name(&self) -> StringRef<'a> {
+        match self {
+            PyIdSelected::NewName(name) => *name,
+            _ => panic!("not a new name"),
+        }
+    }
+
+    pub fn unwrap_func(&self) -> PyArity {
+        match self {
+            PyIdSelected::Func(arity) => *arity,
+            _ => panic!("not a function"),
+        }
+    }
+}
+
+/// The arguments of a call from the `index`-th one on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Prop)]
+pub struct PyArgs<'a> {
+    pub arity: PyArity,
+    pub index: usize,
+    pub symtab: PySymTab<'a>,
+}
+
+impl<'a> PyArgs<'a> {
+    pub fn new(arity: PyArity, symtab: PySymTab<'a>) -> Self {
+        Self {
+            arity,
+            index: 0,
+            symtab,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        Self {
+            index: self.index + 1,
+            ..self.clone()
+        }
+    }
+}
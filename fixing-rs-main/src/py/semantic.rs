@@ -0,0 +1,805 @@
+use super::{
+    pyenv::{PyArity, PyBinding, PyEnv},
+    pysymtab::{PyArgs, PyBinds, PyIdSelected, PyIdSelector, PySymTab},
+    syntactic::PyProp,
+};
+use fixing_rs_base::{
+    containers::{OrderedMap, OrderedSet},
+    naming::new_id_placeholder,
+    props::{PropArray, PropEmpty},
+    tokenizer::Token,
+    union_prop,
+    utils::{RefArena, StringPool, StringRef},
+};
+
+pub struct PySProcessor<'a> {
+    str_pool: StringPool<'a>,
+    builtins: OrderedMap<StringRef<'a>, PyBinding>,
+    /// Functions defined at the top level of the input, visible in function
+    /// bodies whether they are defined before or after them.
+    module_funcs: OrderedMap<StringRef<'a>, PyBinding>,
+    /// Candidates for names being bound.
+    identifiers: Vec<StringRef<'a>>,
+    /// Candidates for attributes.
+    attrs: Vec<StringRef<'a>>,
+}
+
+impl<'a> PySProcessor<'a> {
+    pub fn new(
+        env: &PyEnv,
+        arena: &'a RefArena<String>,
+        tokens: &Vec<Token<'_, '_>>,
+        max_new_id: usize,
+    ) -> Self {
+        let mut str_pool = StringPool::new(arena);
+
+        let mut attrs = OrderedSet::new();
+        for token in tokens {
+            if token.symbol.name() == "IDENTIFIER" {
+                attrs.insert(str_pool.get_or_add(token.literal));
+            }
+        }
+        let mut builtins = OrderedMap::new();
+        for (name, binding) in env.iter() {
+            builtins.insert(str_pool.get_or_add(name), *binding);
+        }
+        let mut module_funcs = OrderedMap::new();
+        for (name, arity) in Self::module_functions(tokens) {
+            module_funcs.insert(str_pool.get_or_add(name), PyBinding::Func(arity));
+        }
+
+        let mut identifiers: OrderedSet<_> = attrs.iter().chain(builtins.keys()).copied().collect();
+        for i in 0..max_new_id {
+            identifiers.insert(str_pool.get_or_add(&new_id_placeholder(i)));
+        }
+
+        Self {
+            str_pool,
+            builtins,
+            module_funcs,
+            identifiers: identifiers.into_iter().collect(),
+            attrs: attrs.into_iter().collect(),
+        }
+    }
+
+    /// The functions defined by `def` at the top level, with the number of
+    /// their parameters.
+    fn module_functions<'s>(tokens: &[Token<'_, 's>]) -> Vec<(&'s str, PyArity)> {
+        let mut result = Vec::new();
+        let mut level = 0usize;
+        for (i, token) in tokens.iter().enumerate() {
+            match token.symbol.name() {
+                "INDENT" => level += 1,
+                "DEDENT" => level = level.saturating_sub(1),
+                "def" if level == 0 => {
+                    let (name, params) = match tokens.get(i + 1..i + 3) {
+                        Some([name, paren, ..])
+                            if name.symbol.name() == "IDENTIFIER" && paren.literal == "(" =>
+                        {
+                            (name.literal, &tokens[i + 3..])
+                        }
+                        _ => continue,
+                    };
+                    let arity = params
+                        .iter()
+                        .take_while(|x| x.literal != ")")
+                        .filter(|x| x.symbol.name() == "IDENTIFIER")
+                        .count();
+                    result.push((name, PyArity::exact(arity)));
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    fn lookup(&self, symtab: &PySymTab<'a>, name: StringRef<'a>) -> Option<PyBinding> {
+        symtab
+            .get(name)
+            .or_else(|| match symtab.in_function {
+                true => self.module_funcs.get(&name).copied(),
+                false => None,
+            })
+            .or_else(|| self.builtins.get(&name).copied())
+    }
+
+    /// The names visible in `symtab` whose binding satisfies `filter`.
+    fn visible(
+        &self,
+        symtab: &PySymTab<'a>,
+        filter: impl Fn(PyBinding) -> bool,
+    ) -> Vec<StringRef<'a>> {
+        let module_funcs = match symtab.in_function {
+            true => Some(self.module_funcs.keys()),
+            false => None,
+        };
+        let names: OrderedSet<_> = symtab
+            .iter()
+            .map(|(name, _)| name)
+            .chain(module_funcs.into_iter().flatten())
+            .chain(self.builtins.keys())
+            .copied()
+            .collect();
+        names
+            .into_iter()
+            .filter(|name| self.lookup(symtab, *name).is_some_and(&filter))
+            .collect()
+    }
+}
+
+union_prop!(
+    PySynProp<'a>,
+    Empty,
+    {
+        Empty(PropEmpty),
+        Binds(PyBinds<'a>),
+        Str(StringRef<'a>),
+        IdSelected(PyIdSelected<'a>)
+    }
+);
+
+union_prop!(
+    PyInhProp<'a>,
+    Empty,
+    {
+        Empty(PropEmpty),
+        SymTab(PySymTab<'a>),
+        IdSelector(PyIdSelector<'a>),
+        Args(PyArgs<'a>)
+    }
+);
+
+#[impl_semantic_processor(
+    g_prop = "PyProp",
+    si_prop = "PyInhProp<'a>",
+    ss_prop = "PySynProp<'a>",
+    grammar_file = "fixing-rs-main/src/py/python"
+)]
+#[allow(non_snake_case)]
+impl<'a> PySProcessor<'a> {
+    fn rooti(&self) -> PySymTab<'a> {
+        PySymTab::new()
+    }
+
+    // nts stmts: 0
+    fn nts_stmts_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts stmts: 1 stmt stmts
+    fn nts_stmts_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        stmt: &PyBinds<'a>,
+        stmts: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        stmt.then(stmts)
+    }
+
+    // nti 1 stmts: 1 stmt stmts
+    fn nti_stmts_1_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        stmt: &PyBinds<'a>,
+    ) -> PySymTab<'a> {
+        inh.extend(stmt)
+    }
+
+    // nts stmt: 0 simpleStmt 'NEWLINE'
+    fn nts_stmt_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        stmt: &PyBinds<'a>,
+        _s2: &PropEmpty,
+    ) -> PyBinds<'a> {
+        stmt.clone()
+    }
+
+    // nts stmt: 1 'def' newName '(' params ')' ':' suite
+    fn nts_stmt_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        name: &PyIdSelected<'a>,
+        _s3: &PropEmpty,
+        params: &PyBinds<'a>,
+        _s5: &PropEmpty,
+        _s6: &PropEmpty,
+        _s7: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        let arity = PyArity::exact(params.binds.len());
+        PyBinds::new(name.unwrap_new_name(), PyBinding::Func(arity))
+    }
+
+    // nti 1 stmt: 1 'def' newName '(' params ')' ':' suite
+    fn nti_stmt_1_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> PyIdSelector<'a> {
+        PyIdSelector::NewName
+    }
+
+    // nti 6 stmt: 1 'def' newName '(' params ')' ':' suite
+    fn nti_stmt_1_6(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        name: &PyIdSelected<'a>,
+        _s3: &PropEmpty,
+        params: &PyBinds<'a>,
+        _s5: &PropEmpty,
+        _s6: &PropEmpty,
+    ) -> PySymTab<'a> {
+        let arity = PyArity::exact(params.binds.len());
+        let func = PyBinds::new(name.unwrap_new_name(), PyBinding::Func(arity));
+        inh.function(&func.then(params))
+    }
+
+    // nts stmt: 2 'if' expr ':' suite elseClause
+    fn nts_stmt_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+        suite: &PyBinds<'a>,
+        other: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        suite.then(other)
+    }
+
+    // nts stmt: 3 'while' expr ':' suite
+    fn nts_stmt_3(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+        suite: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        suite.clone()
+    }
+
+    // nti 3 stmt: 3 'while' expr ':' suite
+    fn nti_stmt_3_3(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+    ) -> PySymTab<'a> {
+        inh.in_loop()
+    }
+
+    // nts stmt: 4 'for' newName 'in' expr ':' suite
+    fn nts_stmt_4(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        name: &PyIdSelected<'a>,
+        _s3: &PropEmpty,
+        _s4: &PropEmpty,
+        _s5: &PropEmpty,
+        suite: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        PyBinds::new(name.unwrap_new_name(), PyBinding::Var).then(suite)
+    }
+
+    // nti 1 stmt: 4 'for' newName 'in' expr ':' suite
+    fn nti_stmt_4_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> PyIdSelector<'a> {
+        PyIdSelector::NewName
+    }
+
+    // nti 5 stmt: 4 'for' newName 'in' expr ':' suite
+    fn nti_stmt_4_5(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        name: &PyIdSelected<'a>,
+        _s3: &PropEmpty,
+        _s4: &PropEmpty,
+        _s5: &PropEmpty,
+    ) -> PySymTab<'a> {
+        inh.extend(&PyBinds::new(name.unwrap_new_name(), PyBinding::Var))
+            .in_loop()
+    }
+
+    // nts elseClause: 0
+    fn nts_elseClause_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts elseClause: 1 'else' ':' suite
+    fn nts_elseClause_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        suite: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        suite.clone()
+    }
+
+    // nts elseClause: 2 'elif' expr ':' suite elseClause
+    fn nts_elseClause_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+        suite: &PyBinds<'a>,
+        other: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        suite.then(other)
+    }
+
+    // nts suite: 0 'NEWLINE' 'INDENT' stmt stmts 'DEDENT'
+    fn nts_suite_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        stmt: &PyBinds<'a>,
+        stmts: &PyBinds<'a>,
+        _s5: &PropEmpty,
+    ) -> PyBinds<'a> {
+        stmt.then(stmts)
+    }
+
+    // nti 3 suite: 0 'NEWLINE' 'INDENT' stmt stmts 'DEDENT'
+    fn nti_suite_0_3(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        stmt: &PyBinds<'a>,
+    ) -> PySymTab<'a> {
+        inh.extend(stmt)
+    }
+
+    // nts suite: 1 simpleStmt 'NEWLINE'
+    fn nts_suite_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        stmt: &PyBinds<'a>,
+        _s2: &PropEmpty,
+    ) -> PyBinds<'a> {
+        stmt.clone()
+    }
+
+    // nts simpleStmt: 0 newName '=' expr
+    fn nts_simpleStmt_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        name: &PyIdSelected<'a>,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::new(name.unwrap_new_name(), PyBinding::Var)
+    }
+
+    // nti 0 simpleStmt: 0 newName '=' expr
+    fn nti_simpleStmt_0_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::NewName
+    }
+
+    // nts simpleStmt: 1 expr
+    fn nts_simpleStmt_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts simpleStmt: 2 'return' expr
+    fn nts_simpleStmt_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nti 1 simpleStmt: 2 'return' expr
+    fn nti_simpleStmt_2_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> Option<PySymTab<'a>> {
+        inh.in_function.then(|| inh.clone())
+    }
+
+    // nts simpleStmt: 3 'return'
+    fn nts_simpleStmt_3(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> Option<PyBinds<'a>> {
+        inh.in_function.then(PyBinds::empty)
+    }
+
+    // nts simpleStmt: 4 'pass'
+    fn nts_simpleStmt_4(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts simpleStmt: 5 'break'
+    fn nts_simpleStmt_5(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> Option<PyBinds<'a>> {
+        inh.in_loop.then(PyBinds::empty)
+    }
+
+    // nts simpleStmt: 6 'continue'
+    fn nts_simpleStmt_6(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+    ) -> Option<PyBinds<'a>> {
+        inh.in_loop.then(PyBinds::empty)
+    }
+
+    // nts simpleStmt: 7 varName augOp expr
+    fn nts_simpleStmt_7(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PyIdSelected<'a>,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nti 0 simpleStmt: 7 varName augOp expr
+    fn nti_simpleStmt_7_0(&self, _g: &PropArray<PyProp>, inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::Var(inh.clone())
+    }
+
+    // nts simpleStmt: 8 atomExpr '[' expr ']' '=' expr
+    fn nts_simpleStmt_8(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PropEmpty,
+        _s4: &PropEmpty,
+        _s5: &PropEmpty,
+        _s6: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts simpleStmt: 9 atomExpr '.' attrName '=' expr
+    fn nts_simpleStmt_9(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PyIdSelected<'a>,
+        _s4: &PropEmpty,
+        _s5: &PropEmpty,
+    ) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nti 2 simpleStmt: 9 atomExpr '.' attrName '=' expr
+    fn nti_simpleStmt_9_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> PyIdSelector<'a> {
+        PyIdSelector::Attr
+    }
+
+    // nts params: 0
+    fn nts_params_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyBinds<'a> {
+        PyBinds::empty()
+    }
+
+    // nts params: 1 paramList
+    fn nts_params_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        params: &PyBinds<'a>,
+    ) -> PyBinds<'a> {
+        params.clone()
+    }
+
+    // nts paramList: 0 newName
+    fn nts_paramList_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        name: &PyIdSelected<'a>,
+    ) -> PyBinds<'a> {
+        PyBinds::new(name.unwrap_new_name(), PyBinding::Var)
+    }
+
+    // nti 0 paramList: 0 newName
+    fn nti_paramList_0_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::NewName
+    }
+
+    // nts paramList: 1 newName ',' paramList
+    fn nts_paramList_1(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        name: &PyIdSelected<'a>,
+        _s2: &PropEmpty,
+        params: &PyBinds<'a>,
+    ) -> Option<PyBinds<'a>> {
+        let name = name.unwrap_new_name();
+        if params.binds.iter().any(|(x, _)| *x == name) {
+            return None;
+        }
+        Some(PyBinds::new(name, PyBinding::Var).then(params))
+    }
+
+    // nti 0 paramList: 1 newName ',' paramList
+    fn nti_paramList_1_0(&self, _g: &PropArray<PyProp>, _inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::NewName
+    }
+
+    // nti 0 atomExpr: 1 funcName '(' args ')'
+    fn nti_atomExpr_1_0(&self, _g: &PropArray<PyProp>, inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::Func(inh.clone())
+    }
+
+    // nti 2 atomExpr: 1 funcName '(' args ')'
+    fn nti_atomExpr_1_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        func: &PyIdSelected<'a>,
+        _s2: &PropEmpty,
+    ) -> PyArgs<'a> {
+        PyArgs::new(func.unwrap_func(), inh.clone())
+    }
+
+    // nti 2 atomExpr: 3 atomExpr '.' attrName
+    fn nti_atomExpr_3_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> PyIdSelector<'a> {
+        PyIdSelector::Attr
+    }
+
+    // nti 2 atomExpr: 4 atomExpr '.' attrName '(' anyArgs ')'
+    fn nti_atomExpr_4_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> PyIdSelector<'a> {
+        PyIdSelector::Attr
+    }
+
+    // nti 4 atomExpr: 4 atomExpr '.' attrName '(' anyArgs ')'
+    fn nti_atomExpr_4_4(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PySymTab<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+        _s3: &PyIdSelected<'a>,
+        _s4: &PropEmpty,
+    ) -> PySymTab<'a> {
+        inh.clone()
+    }
+
+    // nti 0 atom: 0 name
+    fn nti_atom_0_0(&self, _g: &PropArray<PyProp>, inh: &PySymTab<'a>) -> PyIdSelector<'a> {
+        PyIdSelector::Name(inh.clone())
+    }
+
+    // nts args: 0
+    fn nts_args_0(&self, _g: &PropArray<PyProp>, inh: &PyArgs<'a>) -> Option<PropEmpty> {
+        inh.arity.accepts(inh.index).then_some(PropEmpty)
+    }
+
+    // nti 0 args: 1 argList
+    fn nti_args_1_0(&self, _g: &PropArray<PyProp>, inh: &PyArgs<'a>) -> Option<PyArgs<'a>> {
+        inh.arity.accepts_more(inh.index).then(|| inh.clone())
+    }
+
+    // nti 0 argList: 0 expr
+    fn nti_argList_0_0(&self, _g: &PropArray<PyProp>, inh: &PyArgs<'a>) -> Option<PySymTab<'a>> {
+        inh.arity.accepts(inh.index + 1).then(|| inh.symtab.clone())
+    }
+
+    // nti 0 argList: 1 expr ',' argList
+    fn nti_argList_1_0(&self, _g: &PropArray<PyProp>, inh: &PyArgs<'a>) -> Option<PySymTab<'a>> {
+        inh.arity
+            .accepts_more(inh.index + 1)
+            .then(|| inh.symtab.clone())
+    }
+
+    // nti 2 argList: 1 expr ',' argList
+    fn nti_argList_1_2(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PyArgs<'a>,
+        _s1: &PropEmpty,
+        _s2: &PropEmpty,
+    ) -> PyArgs<'a> {
+        inh.next()
+    }
+
+    // nts name: 0 IDENTIFIER
+    fn nts_name_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PyIdSelector<'a>,
+        _id: &StringRef<'a>,
+    ) -> PyIdSelected<'a> {
+        PyIdSelected::Bound
+    }
+
+    // nts funcName: 0 IDENTIFIER
+    fn nts_funcName_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PyIdSelector<'a>,
+        id: &StringRef<'a>,
+    ) -> PyIdSelected<'a> {
+        match inh {
+            PyIdSelector::Func(symtab) => match self.lookup(symtab, *id) {
+                Some(PyBinding::Func(arity)) => PyIdSelected::Func(arity),
+                _ => panic!("not a function"),
+            },
+            _ => panic!("not funcName"),
+        }
+    }
+
+    // nts varName: 0 IDENTIFIER
+    fn nts_varName_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PyIdSelector<'a>,
+        _id: &StringRef<'a>,
+    ) -> PyIdSelected<'a> {
+        PyIdSelected::Bound
+    }
+
+    // nts newName: 0 IDENTIFIER
+    fn nts_newName_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PyIdSelector<'a>,
+        id: &StringRef<'a>,
+    ) -> PyIdSelected<'a> {
+        PyIdSelected::NewName(*id)
+    }
+
+    // nts attrName: 0 IDENTIFIER
+    fn nts_attrName_0(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PyIdSelector<'a>,
+        _id: &StringRef<'a>,
+    ) -> PyIdSelected<'a> {
+        PyIdSelected::Bound
+    }
+
+    // sts IDENTIFIER
+    fn sts_IDENTIFIER(
+        &self,
+        _g: &PropArray<PyProp>,
+        inh: &PyIdSelector<'a>,
+        literal: Option<&str>,
+    ) -> Vec<StringRef<'a>> {
+        let literal = literal.map(|s| self.str_pool.get(s).unwrap());
+        let (symtab, filter): (_, fn(PyBinding) -> bool) = match inh {
+            PyIdSelector::Name(symtab) => (symtab, |_| true),
+            PyIdSelector::Func(symtab) => (symtab, |x| matches!(x, PyBinding::Func(_))),
+            PyIdSelector::Var(symtab) => (symtab, |x| x == PyBinding::Var),
+            PyIdSelector::NewName => {
+                return match literal {
+                    Some(literal) => vec![literal],
+                    None => self.identifiers.clone(),
+                }
+            }
+            PyIdSelector::Attr => {
+                return match literal {
+                    Some(literal) => vec![literal],
+                    None => self.attrs.clone(),
+                }
+            }
+        };
+        match literal {
+            Some(literal) => match self.lookup(symtab, literal) {
+                Some(binding) if filter(binding) => vec![literal],
+                _ => Vec::new(),
+            },
+            None => self.visible(symtab, filter),
+        }
+    }
+
+    // stg IDENTIFIER
+    fn stg_IDENTIFIER(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PyIdSelector<'a>,
+        syn: &StringRef<'a>,
+        _literal: Option<&str>,
+    ) -> String {
+        syn.to_string()
+    }
+
+    // stg NUMBER
+    fn stg_NUMBER(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _syn: &PropEmpty,
+        literal: Option<&str>,
+    ) -> String {
+        match literal {
+            Some(literal) => literal.to_string(),
+            None => "0".to_string(),
+        }
+    }
+
+    // stg STRING
+    fn stg_STRING(
+        &self,
+        _g: &PropArray<PyProp>,
+        _inh: &PySymTab<'a>,
+        _syn: &PropEmpty,
+        literal: Option<&str>,
+    ) -> String {
+        match literal {
+            Some(literal) => literal.to_string(),
+            None => "\"\"".to_string(),
+        }
+    }
+}
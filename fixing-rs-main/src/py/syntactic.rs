@@ -0,0 +1,19 @@
+use fixing_rs_base::{props::PropEmpty, union_prop};
+
+union_prop! {
+    PyProp,
+    Empty,
+    {
+        Empty(PropEmpty)
+    }
+}
+
+/// Precedence is encoded in the grammar, so no properties are needed.
+pub struct PyGProcessor;
+
+extern crate fixing_rs_macros;
+use fixing_rs_macros::impl_syntactic_processor;
+
+#[impl_syntactic_processor(g_prop = "PyProp", grammar_file = "fixing-rs-main/src/py/python")]
+#[allow(unused, non_snake_case)]
+impl PyGProcessor {}
@@ -0,0 +1,353 @@
+use fixing_rs_base::{
+    grammar::{GrammarSymbolsRef, SymbolRef, SymbolType},
+    tokenizer::{Token, Tokenizer},
+};
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+};
+
+pub const NEWLINE: &str = "NEWLINE";
+pub const INDENT: &str = "INDENT";
+pub const DEDENT: &str = "DEDENT";
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Operators, longest first.
+const OPERATORS: &[&str] = &[
+    "**=", "//=", ">>=", "<<=", "->", "**", "//", "==", "!=", "<=", ">=", "+=", "-=", "*=", "/=",
+    "%=", "&=", "|=", "^=", "@=", "<<", ">>", ":=", "+", "-", "*", "/", "%", "<", ">", "=", "(",
+    ")", "[", "]", "{", "}", ",", ":", ".", ";", "@", "&", "|", "^", "~",
+];
+
+/// Tokenizes Python source. Logical lines end with a `NEWLINE` token and
+/// changes of indentation are marked by `INDENT` and `DEDENT` tokens, all
+/// three being empty slices of the input where they occur. Blank lines,
+/// comments and line breaks within brackets are skipped.
+pub struct PyTokenizer;
+
+impl Tokenizer for PyTokenizer {
+    type ErrType = PyTokenizerError;
+    fn tokenize<'a, 's>(
+        &mut self,
+        input: &'s str,
+        grammar: GrammarSymbolsRef<'a>,
+    ) -> Result<Vec<Token<'a, 's>>, Self::ErrType> {
+        let literal = |name: &str| {
+            grammar.literal_terminals.get(name).copied().ok_or_else(|| {
+                PyTokenizerError::SymbolNotFound(SymbolType::LiteralTerminal, name.to_string())
+            })
+        };
+        let symbolic = |name: &str| -> Result<SymbolRef<'a>, PyTokenizerError> {
+            grammar
+                .symbolic_terminals
+                .get(name)
+                .copied()
+                .ok_or_else(|| {
+                    PyTokenizerError::SymbolNotFound(SymbolType::SymbolicTerminal, name.to_string())
+                })
+        };
+        let newline = literal(NEWLINE)?;
+        let indent = literal(INDENT)?;
+        let dedent = literal(DEDENT)?;
+        let identifier = symbolic("IDENTIFIER")?;
+        let number = symbolic("NUMBER")?;
+        let string = symbolic("STRING")?;
+
+        let bytes = input.as_bytes();
+        let empty = |pos: usize| &input[pos..pos];
+        let mut result = Vec::new();
+        let mut indents = vec![0];
+        let mut depth = 0usize;
+        let mut line_start = true;
+        let mut line = 1;
+        let mut pos = 0;
+        while pos < bytes.len() {
+            if line_start && depth == 0 {
+                let mut width = 0;
+                while pos < bytes.len() && (bytes[pos] == b' ' || bytes[pos] == b'\t') {
+                    width = if bytes[pos] == b'\t' {
+                        (width / 8 + 1) * 8
+                    } else {
+                        width + 1
+                    };
+                    pos += 1;
+                }
+                match bytes.get(pos) {
+                    None | Some(b'\n') | Some(b'\r') | Some(b'#') => {
+                        pos = Self::skip_line(bytes, pos);
+                        line += 1;
+                        continue;
+                    }
+                    _ => {}
+                }
+                if width > *indents.last().unwrap() {
+                    indents.push(width);
+                    result.push(Token {
+                        literal: empty(pos),
+                        symbol: indent,
+                    });
+                }
+                while width < *indents.last().unwrap() {
+                    indents.pop();
+                    if width > *indents.last().unwrap() {
+                        return Err(PyTokenizerError::InconsistentDedent(line));
+                    }
+                    result.push(Token {
+                        literal: empty(pos),
+                        symbol: dedent,
+                    });
+                }
+                line_start = false;
+            }
+            let c = bytes[pos];
+            match c {
+                b' ' | b'\t' | b'\x0c' => pos += 1,
+                b'#' => {
+                    while pos < bytes.len() && bytes[pos] != b'\n' && bytes[pos] != b'\r' {
+                        pos += 1;
+                    }
+                }
+                b'\\' if matches!(bytes.get(pos + 1), Some(b'\n') | Some(b'\r')) => {
+                    pos = Self::skip_line(bytes, pos + 1);
+                    line += 1;
+                }
+                b'\n' | b'\r' => {
+                    if depth == 0 {
+                        result.push(Token {
+                            literal: empty(pos),
+                            symbol: newline,
+                        });
+                        line_start = true;
+                    }
+                    pos = Self::skip_line(bytes, pos);
+                    line += 1;
+                }
+                _ if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 => {
+                    let end = Self::word_end(input, pos);
+                    if end == pos {
+                        return Err(PyTokenizerError::InvalidCharacter(line));
+                    }
+                    let word = &input[pos..end];
+                    if matches!(bytes.get(end), Some(b'"') | Some(b'\''))
+                        && word.len() <= 2
+                        && word.chars().all(|x| "rRbBuUfF".contains(x))
+                    {
+                        let end = Self::string_end(input, end, &mut line)?;
+                        result.push(Token {
+                            literal: &input[pos..end],
+                            symbol: string,
+                        });
+                        pos = end;
+                        continue;
+                    }
+                    let symbol = if KEYWORDS.contains(&word) {
+                        literal(word)?
+                    } else {
+                        identifier
+                    };
+                    result.push(Token {
+                        literal: word,
+                        symbol,
+                    });
+                    pos = end;
+                }
+                _ if c.is_ascii_digit()
+                    || (c == b'.' && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit)) =>
+                {
+                    let mut end = pos + 1;
+                    while end < bytes.len() {
+                        let x = bytes[end];
+                        let exponent_sign = (x == b'+' || x == b'-')
+                            && matches!(bytes[end - 1], b'e' | b'E')
+                            && !input[pos..end].starts_with("0x")
+                            && !input[pos..end].starts_with("0X");
+                        if x.is_ascii_alphanumeric() || x == b'_' || x == b'.' || exponent_sign {
+                            end += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    result.push(Token {
+                        literal: &input[pos..end],
+                        symbol: number,
+                    });
+                    pos = end;
+                }
+                b'"' | b'\'' => {
+                    let end = Self::string_end(input, pos, &mut line)?;
+                    result.push(Token {
+                        literal: &input[pos..end],
+                        symbol: string,
+                    });
+                    pos = end;
+                }
+                _ => {
+                    let op = OPERATORS
+                        .iter()
+                        .find(|x| input[pos..].starts_with(*x))
+                        .ok_or(PyTokenizerError::InvalidCharacter(line))?;
+                    match *op {
+                        "(" | "[" | "{" => depth += 1,
+                        ")" | "]" | "}" => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                    result.push(Token {
+                        literal: &input[pos..pos + op.len()],
+                        symbol: literal(op)?,
+                    });
+                    pos += op.len();
+                }
+            }
+        }
+        if !line_start {
+            result.push(Token {
+                literal: empty(pos),
+                symbol: newline,
+            });
+        }
+        for _ in 1..indents.len() {
+            result.push(Token {
+                literal: empty(pos),
+                symbol: dedent,
+            });
+        }
+        Ok(result)
+    }
+}
+
+impl PyTokenizer {
+    /// The position after the line break at or after `pos`.
+    fn skip_line(bytes: &[u8], mut pos: usize) -> usize {
+        while pos < bytes.len() && bytes[pos] != b'\n' && bytes[pos] != b'\r' {
+            pos += 1;
+        }
+        if bytes.get(pos) == Some(&b'\r') {
+            pos += 1;
+        }
+        if bytes.get(pos) == Some(&b'\n') {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn word_end(input: &str, pos: usize) -> usize {
+        input[pos..]
+            .char_indices()
+            .find(|(_, x)| !(x.is_alphanumeric() || *x == '_'))
+            .map_or(input.len(), |(i, _)| pos + i)
+    }
+
+    /// The end of the string literal whose quotes start at `pos`.
+    fn string_end(input: &str, pos: usize, line: &mut usize) -> Result<usize, PyTokenizerError> {
+        let bytes = input.as_bytes();
+        let quote = bytes[pos];
+        let triple = bytes[pos..].starts_with(&[quote; 3]);
+        let start_line = *line;
+        let mut end = pos + if triple { 3 } else { 1 };
+        loop {
+            match bytes.get(end) {
+                None => return Err(PyTokenizerError::UnterminatedString(start_line)),
+                Some(b'\\') => end += 2,
+                Some(b'\n') if !triple => {
+                    return Err(PyTokenizerError::UnterminatedString(start_line))
+                }
+                Some(b'\n') => {
+                    *line += 1;
+                    end += 1;
+                }
+                Some(x) if *x == quote => {
+                    if !triple {
+                        return Ok(end + 1);
+                    }
+                    if bytes[end..].starts_with(&[quote; 3]) {
+                        return Ok(end + 3);
+                    }
+                    end += 1;
+                }
+                Some(_) => end += 1,
+            }
+        }
+    }
+}
+
+/// Python source of tokens, indented by four spaces per level.
+pub fn detokenize(tokens: &[impl AsRef<str>]) -> String {
+    let mut result = String::new();
+    let mut level = 0usize;
+    let mut previous: Option<&str> = None;
+    let mut unary = false;
+    for token in tokens.iter().map(|x| x.as_ref()) {
+        match token {
+            NEWLINE => {
+                result.push('\n');
+                previous = None;
+                continue;
+            }
+            INDENT => {
+                level += 1;
+                continue;
+            }
+            DEDENT => {
+                level = level.saturating_sub(1);
+                continue;
+            }
+            _ => {}
+        }
+        match previous {
+            None => result.push_str(&"    ".repeat(level)),
+            Some(previous) if !unary && needs_space(previous, token) => result.push(' '),
+            Some(_) => {}
+        }
+        result.push_str(token);
+        unary = matches!(token, "-" | "+" | "~") && !previous.is_some_and(ends_operand);
+        previous = Some(token);
+    }
+    if previous.is_some() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Whether `token` ends an operand, so that a following `(` is a call and a
+/// following `-` is binary.
+fn ends_operand(token: &str) -> bool {
+    match token {
+        ")" | "]" | "}" | "True" | "False" | "None" => true,
+        _ if KEYWORDS.contains(&token) => false,
+        _ => token
+            .chars()
+            .next()
+            .is_some_and(|x| x.is_alphanumeric() || x == '_' || x == '"' || x == '\''),
+    }
+}
+
+fn needs_space(previous: &str, token: &str) -> bool {
+    match (previous, token) {
+        (_, ")" | "]" | "}" | "," | ":" | ".") => false,
+        ("(" | "[" | "{" | ".", _) => false,
+        (_, "(" | "[") => !ends_operand(previous),
+        _ => true,
+    }
+}
+
+#[derive(Debug)]
+pub enum PyTokenizerError {
+    SymbolNotFound(SymbolType, String),
+    InvalidCharacter(usize),
+    UnterminatedString(usize),
+    InconsistentDedent(usize),
+}
+
+impl Display for PyTokenizerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl Error for PyTokenizerError {}
@@ -0,0 +1,476 @@
+use crate::{
+    fixing::{FixCmd, Naming},
+    mem_limit::{limit_memory, oom_triggered},
+};
+use clap::{Parser, ValueEnum};
+use fixing_rs_base::{
+    containers::Map,
+    error_location::ErrorLocation,
+    fixing::{FixTaskError, FixTaskInfo},
+    grammar::{Grammar, GrammarArena},
+    language::{LanguageOptions, LanguageRegistry},
+    naming::{NamingStrategy, VocabularyNaming},
+    stats::EngineStats,
+};
+use log::{error, info, warn};
+use serde_json::{json, Value};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixListener,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const FIX_FAILED: i64 = 1;
+pub const TIME_LIMIT_EXCEEDED: i64 = 2;
+pub const WORKER_EXITED: i64 = 3;
+pub const MEMORY_BUDGET_EXCEEDED: i64 = 4;
+
+/// Extra time given to a worker past the time limit of a request before it
+/// is killed; the search checks its limit between batches of syntactic edges
+/// and before each new semantic edge.
+const TIME_LIMIT_GRACE: Duration = Duration::from_secs(1);
+
+/// Serves fix requests, one JSON-RPC message per line, over stdin/stdout or
+/// a Unix socket. Requests are run by a worker process that keeps the
+/// grammars loaded; a worker running out of memory or time is replaced
+/// without affecting the server.
+#[derive(Parser, Clone)]
+pub struct ServeCmd {
+    /// Listen on a Unix socket instead of stdin/stdout.
+    #[arg(long)]
+    socket: Option<PathBuf>,
+    /// Default memory limit of a request, in bytes; 0 for no limit.
+    #[arg(long, default_value_t = 0)]
+    memory_limit: usize,
+    /// Default time limit of a request, in seconds.
+    #[arg(long)]
+    time_limit: Option<f64>,
+    /// Run requests in this process.
+    #[arg(long, hide = true)]
+    worker: bool,
+}
+
+impl ServeCmd {
+    pub fn run(self, registry: LanguageRegistry) {
+        if self.worker {
+            run_worker(self.memory_limit, registry);
+            return;
+        }
+        match self.socket {
+            Some(ref path) => {
+                let listener = UnixListener::bind(path).expect("Unable to bind the socket.");
+                info!("Listening on {}", path.display());
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            warn!("Unable to accept a connection: {}", e);
+                            continue;
+                        }
+                    };
+                    let cmd = self.clone();
+                    thread::spawn(move || {
+                        let reader = stream.try_clone().map(BufReader::new);
+                        let result = reader.and_then(|mut reader| {
+                            let mut writer = stream;
+                            Session::new(cmd).serve(&mut reader, &mut writer)
+                        });
+                        if let Err(e) = result {
+                            warn!("Connection closed: {}", e);
+                        }
+                    });
+                }
+            }
+            None => {
+                let stdin = io::stdin();
+                let stdout = io::stdout();
+                Session::new(self.clone())
+                    .serve(&mut stdin.lock(), &mut stdout.lock())
+                    .expect("Unable to communicate with the client.");
+            }
+        }
+    }
+}
+
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    memory_limit: usize,
+}
+
+impl Worker {
+    fn spawn(memory_limit: usize) -> io::Result<Self> {
+        let mut child = Command::new(std::env::current_exe()?)
+            .args(["serve", "--worker", "--memory-limit"])
+            .arg(memory_limit.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+            memory_limit,
+        })
+    }
+
+    fn kill(mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// Forwards the requests of one client to a worker process, restarting the
+/// worker whenever it fails or a request asks for another memory limit.
+struct Session {
+    cmd: ServeCmd,
+    worker: Option<Worker>,
+}
+
+impl Session {
+    fn new(cmd: ServeCmd) -> Self {
+        Self { cmd, worker: None }
+    }
+
+    fn serve(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.handle(&line);
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+        if let Some(worker) = self.worker.take() {
+            worker.kill();
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, line: &str) -> Value {
+        let mut request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return error_response(Value::Null, PARSE_ERROR, e.to_string()),
+        };
+        let id = request["id"].clone();
+        let params = &request["params"];
+        let memory_limit = match params.get("memory_limit") {
+            Some(x) => match x.as_u64() {
+                Some(x) => x as usize,
+                None => return error_response(id, INVALID_PARAMS, "Invalid memory_limit."),
+            },
+            None => self.cmd.memory_limit,
+        };
+        let time_limit = match params.get("time_limit") {
+            Some(x) => match x.as_f64().filter(|x| x.is_finite() && *x >= 0.0) {
+                Some(x) => Some(x),
+                None => return error_response(id, INVALID_PARAMS, "Invalid time_limit."),
+            },
+            None => self.cmd.time_limit,
+        };
+        if let (Some(time_limit), Some(params)) = (time_limit, request["params"].as_object_mut()) {
+            params.insert("time_limit".to_string(), json!(time_limit));
+        }
+
+        if self.worker.as_ref().map(|x| x.memory_limit) != Some(memory_limit) {
+            if let Some(worker) = self.worker.take() {
+                worker.kill();
+            }
+            match Worker::spawn(memory_limit) {
+                Ok(worker) => self.worker = Some(worker),
+                Err(e) => return error_response(id, WORKER_EXITED, e.to_string()),
+            }
+        }
+        let worker = self.worker.as_mut().unwrap();
+        if let Err(e) = writeln!(worker.stdin, "{}", request).and_then(|_| worker.stdin.flush()) {
+            self.worker.take().unwrap().kill();
+            return error_response(id, WORKER_EXITED, e.to_string());
+        }
+        let response = match time_limit {
+            Some(time_limit) => worker
+                .lines
+                .recv_timeout(Duration::from_secs_f64(time_limit) + TIME_LIMIT_GRACE),
+            None => worker
+                .lines
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match response {
+            Ok(response) => serde_json::from_str(&response).unwrap_or_else(|e| {
+                error_response(id, WORKER_EXITED, format!("Invalid worker output: {}", e))
+            }),
+            Err(RecvTimeoutError::Timeout) => {
+                self.worker.take().unwrap().kill();
+                error_response(id, TIME_LIMIT_EXCEEDED, "Time limit exceeded.")
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                self.worker.take().unwrap().kill();
+                error_response(id, WORKER_EXITED, "Worker exited, possibly out of memory.")
+            }
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+/// The counters of `stats`, leaving out those only counted when profiling if
+/// the task was not profiled.
+fn stats_json(stats: &EngineStats) -> Value {
+    let mut result = json!({
+        "syntactic_edges": stats.syntactic_edges,
+        "semantic_keys": stats.semantic_keys,
+        "peak_arena_bytes": stats.peak_arena_bytes,
+        "peak_heap_bytes": stats.peak_heap_bytes,
+    });
+    if stats.profiled {
+        result["rules_applied"] = counts_json(&stats.rules_applied);
+        result["quick_ref_lookups"] = json!(stats.quick_ref_lookups);
+        result["cache_hits"] = json!(stats.cache_hits);
+        result["cache_misses"] = json!(stats.cache_misses);
+        result["props_produced"] = counts_json(&stats.props_produced);
+    }
+    result
+}
+
+fn counts_json(counts: &[(impl ToString, usize)]) -> Value {
+    Value::Object(
+        counts
+            .iter()
+            .map(|(name, count)| (name.to_string(), json!(count)))
+            .collect(),
+    )
+}
+
+fn run_worker(memory_limit: usize, registry: LanguageRegistry) {
+    if memory_limit != 0 {
+        limit_memory(memory_limit).unwrap();
+    }
+    let arena = GrammarArena::new();
+    let grammars = ResidentGrammars::new(&arena, registry);
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let response = grammars.handle(&line);
+        if writeln!(stdout, "{}", response)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+        if oom_triggered() {
+            error!("Worker ran out of memory, exiting.");
+            break;
+        }
+    }
+}
+
+/// The grammars of all registered languages, loaded once.
+pub struct ResidentGrammars<'a> {
+    registry: LanguageRegistry,
+    grammars: Map<&'static str, Grammar<'a>>,
+}
+
+impl<'a> ResidentGrammars<'a> {
+    pub fn new(arena: &'a GrammarArena<'a>, registry: LanguageRegistry) -> Self {
+        let grammars = registry
+            .names()
+            .map(|name| {
+                let info = registry.get(name).unwrap().info();
+                (name, Grammar::new(arena, info.grammar).unwrap())
+            })
+            .collect();
+        Self { registry, grammars }
+    }
+
+    /// Runs one request in this process and returns its response.
+    pub fn handle(&'a self, line: &str) -> Value {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return error_response(Value::Null, PARSE_ERROR, e.to_string()),
+        };
+        let id = request["id"].clone();
+        if request["method"] != "fix" {
+            return error_response(id, METHOD_NOT_FOUND, "Only fix is supported.");
+        }
+        let params = &request["params"];
+        let language = match params["lang"].as_str().and_then(|x| self.registry.get(x)) {
+            Some(language) => language,
+            None => return error_response(id, INVALID_PARAMS, "Invalid lang."),
+        };
+        let (input, env) = match (params["input"].as_str(), params["env"].as_str()) {
+            (Some(input), Some(env)) => (input, env),
+            _ => return error_response(id, INVALID_PARAMS, "Missing input or env."),
+        };
+        let info = match Self::task_info(&id, params) {
+            Ok(info) => info,
+            Err(message) => return error_response(id, INVALID_PARAMS, message),
+        };
+        let fixer = match Self::options(params).and_then(|x| language.fixer(&x)) {
+            Ok(fixer) => fixer,
+            Err(message) => return error_response(id, INVALID_PARAMS, message),
+        };
+        let grammar = &self.grammars[language.name()];
+        let time_before_load = Instant::now();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            fixer
+                .process_dyn(grammar, input, env, &info, time_before_load)
+                .map_err(|e| {
+                    let code = match e {
+                        FixTaskError::TimeLimitExceeded => TIME_LIMIT_EXCEEDED,
+                        FixTaskError::MemoryLimit => MEMORY_BUDGET_EXCEEDED,
+                        FixTaskError::SourceLocation(_) => INVALID_PARAMS,
+                        _ => FIX_FAILED,
+                    };
+                    (code, format!("{:?}", e))
+                })
+        }));
+        match result {
+            Ok(Ok(r)) => {
+                let time_load = r.time_after_load - r.time_before_load;
+                let time_build = r.time_after_reachability_built - r.time_after_load;
+                let time_find = match r.time_after_find.last() {
+                    Some(x) => *x - r.time_after_reachability_built,
+                    None => Duration::new(0, 0),
+                };
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "found_length": r.found_length,
+                        "source": r.outputs.as_ref().map(|x| language.detokenize(x)),
+                        "outputs": r.outputs,
+                        "alternatives": r.alternatives,
+                        "time_load": time_load.as_secs_f64(),
+                        "time_build": time_build.as_secs_f64(),
+                        "time_find": time_find.as_secs_f64(),
+                        "stats": stats_json(&r.stats),
+                    },
+                })
+            }
+            Ok(Err((code, message))) => error_response(id, code, message),
+            Err(_) => error_response(id, FIX_FAILED, "Fixing panicked."),
+        }
+    }
+
+    /// The language options of a request: the string, number or boolean
+    /// values of its `options` object.
+    fn options(params: &Value) -> Result<LanguageOptions, String> {
+        let mut options = LanguageOptions::default();
+        let object = match params.get("options") {
+            Some(x) => x.as_object().ok_or("Invalid options.")?,
+            None => return Ok(options),
+        };
+        for (key, value) in object {
+            let value = match value {
+                Value::String(x) => x.clone(),
+                Value::Number(x) => x.to_string(),
+                Value::Bool(x) => x.to_string(),
+                _ => return Err(format!("Invalid option {}.", key)),
+            };
+            options.insert(key.clone(), value);
+        }
+        Ok(options)
+    }
+
+    fn task_info(id: &Value, params: &Value) -> Result<FixTaskInfo, String> {
+        let usize_param = |name: &str, default: Option<usize>| match params.get(name) {
+            Some(x) => x
+                .as_u64()
+                .map(|x| x as usize)
+                .ok_or(format!("Invalid {}.", name)),
+            None => default.ok_or(format!("Missing {}.", name)),
+        };
+        let max_len = usize_param("max_len", None)?;
+        let max_new_id = usize_param("max_new_id", Some(max_len))?;
+        let max_fixes = usize_param("max_fixes", Some(1))?;
+        let time_limit = match params.get("time_limit") {
+            Some(x) => Some(
+                x.as_f64()
+                    .filter(|x| *x >= 0.0)
+                    .map(Duration::from_secs_f64)
+                    .ok_or("Invalid time_limit.")?,
+            ),
+            None => None,
+        };
+        let prune = match params.get("prune") {
+            Some(x) => x.as_bool().ok_or("Invalid prune.")?,
+            None => false,
+        };
+        let threads = usize_param("threads", Some(1))?;
+        let memory_budget = match params.get("memory_budget") {
+            Some(_) => Some(usize_param("memory_budget", None)?),
+            None => None,
+        };
+        let mut error_locations = Vec::new();
+        for location in params["error_locations"].as_array().into_iter().flatten() {
+            let location = match location {
+                Value::String(x) => x.parse::<ErrorLocation>(),
+                x => x.to_string().parse::<ErrorLocation>(),
+            };
+            error_locations.push(location.map_err(|e| e.to_string())?);
+        }
+        let naming: Arc<dyn NamingStrategy> = match params["naming"].as_str() {
+            Some("vocabulary") => {
+                let names = params["naming_vocabulary"]
+                    .as_array()
+                    .ok_or("Missing naming_vocabulary.")?;
+                let names = names.iter().flat_map(|x| x.as_str()).map(|x| x.to_string());
+                Arc::new(VocabularyNaming::new(names))
+            }
+            Some(naming) => FixCmd::naming_strategy(Naming::from_str(naming, true)?, None),
+            None => FixCmd::naming_strategy(Naming::Placeholder, None),
+        };
+        Ok(FixTaskInfo {
+            max_new_id,
+            error_locations,
+            naming,
+            max_fixes,
+            time_limit,
+            memory_budget,
+            prune,
+            threads,
+            ..FixTaskInfo::new(
+                format!("request {}", id),
+                format!("request {}", id),
+                max_len,
+            )
+        })
+    }
+}
@@ -1,5 +1,18 @@
 mod test_c;
+mod test_check;
+mod test_cli;
+mod test_error_location;
+mod test_eval;
+mod test_generator;
 mod test_grammar;
+mod test_json;
+mod test_lsp;
+mod test_mutate;
+mod test_naming;
+mod test_props;
+mod test_py;
+mod test_serve;
+mod test_symtab;
 mod test_syntactic;
 mod test_tokenizer;
 mod test_value_enum;
@@ -1,16 +1,41 @@
-use crate::c::fixing::CFixingInputProcessor;
-use fixing_rs_base::fixing::{fix, FixTaskInfo, FixTaskResult};
+use crate::c::{
+    cenv::{CEnv, CEnvArena, CEnvBuildError},
+    fixing::{CFixingInputProcessor, C_GRAMMAR},
+    flow::CFlow,
+    macros::{CMacroLiteral, CMacros},
+    stdlib::CStdlib,
+    strictness::{CStrictness, CStrictnessLevel},
+    syntactic::CGProcessor,
+    types::{CTypeArena, CTypePool},
+};
+use fixing_rs_base::{
+    fixing::{fix, FixTaskError, FixTaskInfo, FixTaskResult},
+    grammar::{Grammar, GrammarArena},
+    reachability::GProcessor,
+    utils::CountingAllocator,
+};
+use std::{fs, process};
 
 fn test_c_folder(folder: &str, dist: usize) {
-    let info = FixTaskInfo {
-        input_name: format!("src/tests/test_c/{}/c.tokens", folder),
-        env_name: format!("src/tests/test_c/{}/env", folder),
-        output_name: None,
-        max_len: dist,
-        max_new_id: dist,
+    test_c_input(
+        &format!("src/tests/test_c/{}/c.tokens", folder),
+        &format!("src/tests/test_c/{}/env", folder),
+        CFixingInputProcessor::default(),
+        dist,
+    )
+}
+
+fn task_info(input: &str, env: &str, dist: usize, memory_budget: Option<usize>) -> FixTaskInfo {
+    FixTaskInfo {
         verbose_gen: true,
-    };
-    let result = fix(std::iter::once(info), &CFixingInputProcessor);
+        memory_budget,
+        ..FixTaskInfo::new(input, env, dist)
+    }
+}
+
+fn test_c_input(input: &str, env: &str, processor: CFixingInputProcessor, dist: usize) {
+    let info = task_info(input, env, dist, None);
+    let result = fix(std::iter::once(info), &processor);
     let result: [Result<FixTaskResult, _>; 1] = result.try_into().unwrap();
     let [result] = result;
     let result = result.unwrap();
@@ -56,4 +81,349 @@ fn test_c_array() {
 #[test]
 fn test_c_printf() {
     test_c_folder("printf", 2)
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_c_types() {
+    test_c_folder("types", 1)
+}
+
+#[test]
+fn test_c_env_types() {
+    let arena = CEnvArena::new();
+    let types_arena = CTypeArena::new();
+    let types = CTypePool::new(&types_arena);
+    let build = |env: &str| CEnv::build(&arena, env, &types, &Vec::new(), 0, None, false);
+
+    let env = build(
+        "=VAR c:unsigned long long.const;\n\
+         =VAR f:int.(:char.*, ...).*;\n\
+         =VAR a:double.[8].[];\n\
+         =VAR x:float.const;\n\
+         =FN main:int-;",
+    )
+    .unwrap();
+    let global = |name: &str| {
+        let (_, ty) = env
+            .globals()
+            .iter()
+            .find(|(x, _)| x.as_str() == name)
+            .unwrap();
+        format!("{:?}", ty)
+    };
+    let int = format!("{:?}", types.type_u64());
+    let float = format!("{:?}", types.type_f64());
+    assert_eq!(global("c"), format!("{} const", int));
+    assert!(global("f").ends_with('*'));
+    assert_eq!(global("a"), format!("{}[N][N]", float));
+    assert_eq!(global("x"), format!("{:?} const", types.type_f32()));
+
+    assert!(build("=VAR a:int.[8];\n=FN main:int-;").is_ok());
+    assert!(matches!(
+        build("=VAR a:int.[0];\n=FN main:int-;"),
+        Err(CEnvBuildError::SyntaxError(_))
+    ));
+
+    assert!(matches!(
+        build("=VAR x:unsigned float;\n=FN main:int-;"),
+        Err(CEnvBuildError::FailedToComposeType(_))
+    ));
+}
+
+#[test]
+fn test_c_strictness() {
+    let test = |input: &str, level: CStrictnessLevel, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/strictness/{}.tokens", input),
+            "src/tests/test_c/strictness/env",
+            CFixingInputProcessor::new(CStrictness::new(level), CStdlib::None, false),
+            dist,
+        )
+    };
+    // `f(n)` passes an int as a pointer; `f(0)` passes a null pointer.
+    test("int", CStrictnessLevel::C89Permissive, 0);
+    test("int", CStrictnessLevel::C99, 0);
+    test("int", CStrictnessLevel::GccWerror, 1);
+    // `f(s)` discards the const of `s`.
+    test("const", CStrictnessLevel::C99, 0);
+    test("const", CStrictnessLevel::GccWerror, 1);
+    // `g()` calls an undeclared function.
+    test("implicit", CStrictnessLevel::C89Permissive, 0);
+    test("implicit", CStrictnessLevel::C99, 1);
+}
+
+#[test]
+fn test_c_flow() {
+    let test = |input: &str, level: CStrictnessLevel, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/flow/{}.tokens", input),
+            "src/tests/test_c/flow/env",
+            CFixingInputProcessor::new(CStrictness::new(level), CStdlib::None, false),
+            dist,
+        )
+    };
+    // Locals initialized on both branches, through `&`, or in loops are
+    // read only after initialization, and all of them are used.
+    test("ok", CStrictnessLevel::GccWallWerror, 0);
+    // `x` is only initialized if `n` is nonzero.
+    test("uninit", CStrictnessLevel::GccWerror, 0);
+    test("uninit", CStrictnessLevel::GccWallWerror, 1);
+    // `x` is set but never used.
+    test("unused", CStrictnessLevel::GccWerror, 0);
+    test("unused", CStrictnessLevel::GccWallWerror, 2);
+}
+
+#[test]
+fn test_c_flow_empty() {
+    // Without tracked variables flows stay unboxed, one pointer wide.
+    assert_eq!(std::mem::size_of::<CFlow>(), std::mem::size_of::<usize>());
+    let none = CFlow::default();
+    assert_eq!(none.then(&none).either(&none).value().update(), none);
+    assert_eq!(CFlow::read(0), none);
+    assert_ne!(CFlow::read(1).union(&none), none);
+}
+
+#[test]
+fn test_c_returns() {
+    let test = |input: &str, level: CStrictnessLevel, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/returns/{}.tokens", input),
+            "src/tests/test_c/returns/env",
+            CFixingInputProcessor::new(CStrictness::new(level), CStdlib::None, false),
+            dist,
+        )
+    };
+    test("ok", CStrictnessLevel::GccWallWerror, 0);
+    test("switch", CStrictnessLevel::GccWallWerror, 0);
+    // Control falls off the end if `n >= 0`.
+    test("missing", CStrictnessLevel::GccWerror, 0);
+    test("missing", CStrictnessLevel::GccWallWerror, 2);
+    // `case 1` breaks out of the switch.
+    test("break", CStrictnessLevel::GccWerror, 0);
+    test("break", CStrictnessLevel::GccWallWerror, 1);
+    // Loops only left by returning, as gcc accepts them.
+    test("while", CStrictnessLevel::GccWallWerror, 0);
+    test("for", CStrictnessLevel::GccWallWerror, 0);
+    // The loop may be left by its `break`.
+    test("loop_break", CStrictnessLevel::GccWerror, 0);
+    test("loop_break", CStrictnessLevel::GccWallWerror, 1);
+    // `continue` goes on to the condition, which may end the loop.
+    test("continue", CStrictnessLevel::GccWerror, 0);
+    test("continue", CStrictnessLevel::GccWallWerror, 1);
+}
+
+#[test]
+fn test_c_stdlib() {
+    let test = |input: &str, stdlib: CStdlib, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/stdlib/{}.tokens", input),
+            "src/tests/test_c/stdlib/env",
+            CFixingInputProcessor::new(CStrictness::new(CStrictnessLevel::C99), stdlib, false),
+            dist,
+        )
+    };
+    // The env only declares `main`.
+    test("c", CStdlib::V1, 0);
+    test("puts", CStdlib::None, 1);
+    test("puts", CStdlib::V1, 0);
+}
+
+#[test]
+fn test_c_translation_unit() {
+    let test = |input: &str, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/unit/{}.tokens", input),
+            "src/tests/test_c/unit/env",
+            CFixingInputProcessor::new(
+                CStrictness::new(CStrictnessLevel::C99),
+                CStdlib::None,
+                true,
+            ),
+            dist,
+        )
+    };
+    // The env is empty: every function is declared by the input itself.
+    test("ok", 0);
+    // `fact` lacks its closing brace.
+    test("brace", 1);
+    // The body of `sq` uses `x`, its parameter is named `y`.
+    test("param", 1);
+}
+
+#[test]
+fn test_c_start_rules() {
+    let arena = GrammarArena::new();
+    let grammar = Grammar::new(&arena, C_GRAMMAR).unwrap();
+    let non_terminals = grammar.get_symbol_ref().non_terminals;
+    let derives = |translation_unit: bool, name: &str| {
+        CGProcessor::new(&grammar, translation_unit).derives(non_terminals[name])
+    };
+    // Function bodies take no edges of top-level declarations, and
+    // translation units none of a body outside a function.
+    for name in ["translationUnit", "stmtList", "decl"] {
+        assert!(derives(false, name));
+        assert!(derives(true, name));
+    }
+    for name in ["externalDeclList", "funcHead", "param"] {
+        assert!(!derives(false, name));
+        assert!(derives(true, name));
+    }
+    assert!(derives(false, "functionBody"));
+    assert!(!derives(true, "functionBody"));
+}
+
+#[test]
+fn test_c_macros() {
+    let test = |input: &str, dist: usize| {
+        test_c_input(
+            &format!("src/tests/test_c/macros/{}.tokens", input),
+            "src/tests/test_c/macros/env",
+            CFixingInputProcessor::new(
+                CStrictness::new(CStrictnessLevel::C99),
+                CStdlib::None,
+                false,
+            ),
+            dist,
+        )
+    };
+    // The input defines `N`, `PI`, `MSG` and `SQ`; the env types `MAX`.
+    test("ok", 0);
+    test("semi", 1);
+    // `MAX` takes two arguments; dropping the call is cheaper than adding
+    // one.
+    test("arity", 1);
+}
+
+#[test]
+fn test_c_macro_expansions() {
+    let literal = CMacroLiteral::of_expansion;
+    assert_eq!(literal(" 10"), Some(CMacroLiteral::Int));
+    assert_eq!(literal(" (N * 2)"), Some(CMacroLiteral::Int));
+    assert_eq!(literal(" 'a'"), Some(CMacroLiteral::Int));
+    assert_eq!(literal(" 1e3"), Some(CMacroLiteral::Float));
+    assert_eq!(literal(" \"{int}\""), Some(CMacroLiteral::String));
+    assert_eq!(literal(""), None);
+    assert_eq!(literal(" {"), None);
+    assert_eq!(literal(" int"), None);
+    assert_eq!(literal(" unsigned long"), None);
+    assert_eq!(literal(" x = 0;"), None);
+
+    let input = "#define N 10\n#define BEGIN {\n#define T int\n#define F(x) x\n";
+    let macros = CMacros::new(input, "").unwrap();
+    assert_eq!(macros.env(), "=MACRO N:int;\n=MACRO F:int-...;\n");
+}
+
+#[test]
+fn test_c_macros_output() {
+    let dir = std::env::temp_dir().join(format!("fixing-rs-macros-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output");
+    let input = "src/tests/test_c/macros/directives.tokens";
+    let info = FixTaskInfo {
+        output_name: Some(output.to_string_lossy().to_string()),
+        ..task_info(input, "src/tests/test_c/macros/env", 1, None)
+    };
+    let processor = CFixingInputProcessor::new(
+        CStrictness::new(CStrictnessLevel::C99),
+        CStdlib::None,
+        false,
+    );
+    let result = fix(std::iter::once(info), &processor);
+    assert_eq!(result[0].as_ref().unwrap().found_length, Some(1));
+    let written = fs::read_to_string(&output).unwrap();
+    fs::remove_dir_all(&dir).ok();
+
+    // The fix replaces `puts` by the missing `;`, keeping `(MSG);` as a
+    // statement; every directive stays where it was.
+    let input = fs::read_to_string(input).unwrap();
+    let expected: Vec<_> = input
+        .lines()
+        .map(|line| match line.rsplit('\t').next().unwrap() {
+            "puts" => ";",
+            literal => literal,
+        })
+        .collect();
+    assert_eq!(written.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_c_memory_budget() {
+    let input = "src/tests/test_c/decls2/c.tokens";
+    let env = "src/tests/test_c/decls2/env";
+    let processor = CFixingInputProcessor::default();
+    // Counts the memory of the task without a budget too.
+    CountingAllocator::enable();
+    let unbounded = fix(std::iter::once(task_info(input, env, 2, None)), &processor);
+    let unbounded = unbounded.into_iter().next().unwrap().unwrap();
+    let peak = unbounded.stats.peak_heap_bytes;
+    assert!(peak > unbounded.stats.peak_arena_bytes);
+
+    // A task over its budget, whether while building syntactic reachability
+    // or while searching, does not keep the next ones from running, and a
+    // budget a little over the peak bytes of the task is enough; the bytes
+    // of later tasks may differ a little, e.g. as hash tables grow.
+    let results = fix(
+        [Some(1024), Some(peak - peak / 8), Some(peak + peak / 8)]
+            .into_iter()
+            .map(|budget| task_info(input, env, 2, budget)),
+        &processor,
+    );
+    let [tiny, search, enough]: [_; 3] = results.try_into().unwrap();
+    assert!(matches!(tiny, Err(FixTaskError::MemoryLimit)));
+    assert!(matches!(search, Err(FixTaskError::MemoryLimit)));
+    let enough = enough.unwrap();
+    assert_eq!(enough.outputs, unbounded.outputs);
+}
+
+/// Budget of the task of [`test_c_memory_budget_rss`].
+const RSS_BUDGET: usize = 64 << 20;
+
+/// Fixes a task needing several times [`RSS_BUDGET`] within it, printing
+/// how much the peak resident memory of the process grew. Run by
+/// [`test_c_memory_budget_rss`] in a process of its own.
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore]
+fn c_memory_budget_rss_child() {
+    fn status(field: &str) -> usize {
+        let status = fs::read_to_string("/proc/self/status").unwrap();
+        let line = status.lines().find(|x| x.starts_with(field)).unwrap();
+        let kb = line.split_whitespace().nth(1).unwrap();
+        kb.parse::<usize>().unwrap() << 10
+    }
+    let before = status("VmRSS:");
+    let info = task_info(
+        "src/tests/test_c/basic/c.tokens",
+        "src/tests/test_c/basic/env",
+        4,
+        Some(RSS_BUDGET),
+    );
+    let result = fix(std::iter::once(info), &CFixingInputProcessor::default());
+    assert!(matches!(result[0], Err(FixTaskError::MemoryLimit)));
+    println!("rss_growth:{}", status("VmHWM:") - before);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_c_memory_budget_rss() {
+    let output = process::Command::new(std::env::current_exe().unwrap())
+        .args([
+            "tests::test_c::c_memory_budget_rss_child",
+            "--exact",
+            "--ignored",
+            "--nocapture",
+        ])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    let growth: usize = stdout
+        .lines()
+        .find_map(|x| x.strip_prefix("rss_growth:"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    // The grammar and the tokens, loaded before the task starts counting,
+    // take the rest.
+    assert!(growth < RSS_BUDGET + RSS_BUDGET / 2, "{}", growth);
+}
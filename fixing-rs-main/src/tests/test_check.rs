@@ -0,0 +1,65 @@
+use crate::{check::CheckCmd, grammars::registry};
+use clap::Parser;
+use fixing_rs_base::check::{CheckResult, Rejection};
+use std::{fs, process};
+
+fn check(input: &str) -> CheckResult {
+    let results = CheckCmd::parse_from([
+        "check",
+        "--lang",
+        "mj",
+        "single",
+        "--input",
+        input,
+        "--env",
+        "src/tests/test_mutate/env",
+    ])
+    .check(&registry())
+    .unwrap();
+    results.into_iter().next().unwrap().1.unwrap()
+}
+
+#[test]
+fn test_check() {
+    assert_eq!(check("src/tests/test_mutate/block"), CheckResult::Valid);
+    assert_eq!(
+        check("src/tests/test_check/syntax"),
+        CheckResult::Syntactic {
+            location: 3,
+            token: Some("CLASS_999".to_string()),
+        }
+    );
+    // An undeclared variable is rejected where it is used, and an
+    // assignment of the wrong type as a whole.
+    assert_eq!(
+        check("src/tests/test_check/semantic"),
+        CheckResult::Semantic(Rejection {
+            begin: 16,
+            end: 17,
+            reason: "`VAR_123` is not a valid IDENTIFIER".to_string(),
+        })
+    );
+    match check("src/tests/test_check/type") {
+        CheckResult::Semantic(rejection) => assert_eq!((rejection.begin, rejection.end), (28, 32)),
+        result => panic!("Unexpected result: {}", result),
+    }
+}
+
+#[test]
+fn test_check_incomplete() {
+    let dir = std::env::temp_dir().join(format!("fixing-rs-check-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("block");
+    let block = fs::read_to_string("src/tests/test_mutate/block").unwrap();
+    let tokens: Vec<&str> = block.lines().collect();
+    fs::write(&input, tokens[..tokens.len() - 1].join("\n")).unwrap();
+    let result = check(&input.to_string_lossy());
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        result,
+        CheckResult::Syntactic {
+            location: tokens.len() - 1,
+            token: None,
+        }
+    );
+}
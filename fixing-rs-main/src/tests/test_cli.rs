@@ -0,0 +1,122 @@
+use crate::{
+    check::CheckCmd,
+    cmd::{run_cmd, MainCmd},
+    mj::fixing::{MJFixingInputProcessor, MJ_FIXING_INFO},
+};
+use clap::{error::ErrorKind, Parser};
+use fixing_rs_base::{
+    check::CheckResult,
+    fixing_info::FixingInfo,
+    language::{check_option_keys, Fixer, Language, LanguageOptions, LanguageRegistry},
+};
+use std::{fs, process};
+
+/// A language registered outside of `registry()`, under a name of its own.
+struct MiniLanguage;
+
+impl Language for MiniLanguage {
+    fn name(&self) -> &'static str {
+        "mini"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        &MJ_FIXING_INFO
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        check_option_keys(options, &["mini_strict"])?;
+        match options.get("mini_strict").map(|x| x.as_str()) {
+            None | Some("true") => Ok(Box::new(MJFixingInputProcessor)),
+            Some(x) => Err(format!("Invalid mini_strict: {}", x)),
+        }
+    }
+}
+
+fn mini_registry() -> LanguageRegistry {
+    let mut registry = LanguageRegistry::new();
+    registry.register(MiniLanguage);
+    registry
+}
+
+fn run(args: &[&str]) -> Result<(), clap::Error> {
+    let registry = mini_registry();
+    let args = ["fixing-rs-main"].iter().chain(args);
+    let cmd = MainCmd::try_parse_for(args, &registry)?;
+    run_cmd(cmd, registry)
+}
+
+#[test]
+fn test_cli_registry() {
+    let dir = std::env::temp_dir().join(format!("fixing-rs-cli-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output");
+    let output = output.to_string_lossy();
+    let fix = |lang: &str, option: &str| {
+        run(&[
+            "fix",
+            "--lang",
+            lang,
+            "--max-len",
+            "1",
+            "--max-new-id",
+            "0",
+            "--option",
+            option,
+            "single",
+            "--input",
+            "src/tests/test_check/syntax",
+            "--env",
+            "src/tests/test_mutate/env",
+            "--output",
+            &output,
+        ])
+    };
+
+    assert_eq!(
+        fix("mj", "mini_strict=true").unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+    assert_eq!(
+        fix("mini", "mini_strict=maybe").unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+    // A misspelt option is an error rather than ignored.
+    assert_eq!(
+        fix("mini", "mini_strct=true").unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+    fix("mini", "mini_strict=true").unwrap();
+    let result = CheckCmd::parse_from([
+        "check",
+        "--lang",
+        "mini",
+        "single",
+        "--input",
+        &output,
+        "--env",
+        "src/tests/test_mutate/env",
+    ])
+    .check(&mini_registry())
+    .unwrap();
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(result[0].1, Some(CheckResult::Valid));
+    // Commands built without the registry check report unknown languages.
+    let unknown = CheckCmd::parse_from([
+        "check",
+        "--lang",
+        "py",
+        "single",
+        "--input",
+        &output,
+        "--env",
+        "src/tests/test_mutate/env",
+    ])
+    .check(&mini_registry());
+    assert_eq!(unknown.unwrap_err().kind(), ErrorKind::InvalidValue);
+
+    run(&["gen-src", "mini", "g"]).unwrap();
+    assert_eq!(
+        run(&["gen-src", "py", "g"]).unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+}
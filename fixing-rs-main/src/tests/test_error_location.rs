@@ -0,0 +1,191 @@
+use crate::{
+    c::{
+        fixing::{CFixingInputProcessor, C_GRAMMAR},
+        tokenizer::CTokenizer,
+    },
+    json::{fixing::JSON_GRAMMAR, tokenizer::JsonTokenizer},
+};
+use fixing_rs_base::{
+    edit_script::{edit_script, TokenEdit},
+    error_location::{parse_diagnostics, resolve_error_locations, ErrorLocation},
+    fixing::{fix, rank_fixes, FixTaskError, FixTaskInfo, FixTaskResult},
+    grammar::{Grammar, GrammarArena},
+    reachability::ScoreMemo,
+    tokenizer::{Token, Tokenizer},
+};
+use std::cmp::min;
+
+#[test]
+fn test_parse_error_location() {
+    assert_eq!(
+        "12".parse::<ErrorLocation>().unwrap(),
+        ErrorLocation::Token(12)
+    );
+    assert_eq!(
+        "3:7".parse::<ErrorLocation>().unwrap(),
+        ErrorLocation::Source { line: 3, column: 7 }
+    );
+    assert!("a:b".parse::<ErrorLocation>().is_err());
+
+    let diagnostics = "\
+prog.c: In function 'main':
+prog.c:4:5: error: 'x' undeclared (first use in this function)
+    4 |     x = 1;
+      |     ^
+prog.c:4:5: note: each undeclared identifier is reported only once
+prog.c:6:1: warning: control reaches end of non-void function
+prog.c:7:12: error: expected ';' before '}' token
+";
+    assert_eq!(
+        parse_diagnostics(diagnostics),
+        vec![
+            ErrorLocation::Source { line: 4, column: 5 },
+            ErrorLocation::Source {
+                line: 7,
+                column: 12
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_resolve_error_location() {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, JSON_GRAMMAR).unwrap();
+    let input = "{\n  \"a\": 1,\n  \"b\": [true]\n}\n";
+    let tokens = JsonTokenizer
+        .tokenize(input, grammar.get_symbol_ref())
+        .unwrap();
+    let locations = [
+        ErrorLocation::Source { line: 2, column: 3 },
+        ErrorLocation::Source {
+            line: 3,
+            column: 10,
+        },
+        ErrorLocation::Token(100),
+    ];
+    assert_eq!(
+        resolve_error_locations(Some(input), &tokens, &locations),
+        Ok(vec![1, 8, 11])
+    );
+
+    // Tokens not sliced from the input have no source position.
+    let mut tokens = tokens;
+    tokens.insert(
+        1,
+        Token {
+            literal: "\"made up\"",
+            symbol: tokens[1].symbol,
+        },
+    );
+    assert_eq!(
+        resolve_error_locations(Some(input), &tokens, &locations),
+        Ok(vec![2, 9, 12])
+    );
+}
+
+#[test]
+fn test_resolve_error_location_token_file() {
+    // Lines of a token file are tokens, not lines of the program.
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, C_GRAMMAR).unwrap();
+    let input = "LT\tint\tint\nST\tIDENTIFIER\tx\nLT\t;\t;\n";
+    let tokens = CTokenizer
+        .tokenize(input, grammar.get_symbol_ref())
+        .unwrap();
+    let source = ErrorLocation::Source { line: 2, column: 1 };
+    assert_eq!(
+        resolve_error_locations(None, &tokens, &[ErrorLocation::Token(1), source]),
+        Err(source)
+    );
+    assert_eq!(
+        resolve_error_locations(None, &tokens, &[ErrorLocation::Token(1)]),
+        Ok(vec![1])
+    );
+
+    let folder = "src/tests/test_c/decls2";
+    let info = FixTaskInfo {
+        error_locations: vec![source],
+        ..FixTaskInfo::new(format!("{}/c.tokens", folder), format!("{}/env", folder), 2)
+    };
+    let result = fix(std::iter::once(info), &CFixingInputProcessor::default());
+    assert!(matches!(
+        result[0],
+        Err(FixTaskError::SourceLocation(x)) if x == source
+    ));
+}
+
+/// The edits of the fix of `decls2`, which redeclares `b` then `a`.
+fn decls2_edits(error_locations: Vec<ErrorLocation>) -> Vec<TokenEdit> {
+    let folder = "src/tests/test_c/decls2";
+    let info = FixTaskInfo {
+        error_locations,
+        ..FixTaskInfo::new(format!("{}/c.tokens", folder), format!("{}/env", folder), 2)
+    };
+    let result = fix(std::iter::once(info), &CFixingInputProcessor::default());
+    let result: [Result<FixTaskResult, _>; 1] = result.try_into().unwrap();
+    let [result] = result;
+    let result = result.unwrap();
+    assert_eq!(result.found_length.unwrap(), 2);
+
+    let input = std::fs::read_to_string(format!("{}/c.tokens", folder)).unwrap();
+    let literals: Vec<_> = input
+        .lines()
+        .filter_map(|line| line.split('\t').nth(2))
+        .collect();
+    edit_script(&literals, &result.outputs.unwrap())
+}
+
+#[test]
+fn test_fix_near_error_location() {
+    let renamed = |edits: Vec<TokenEdit>| -> Vec<usize> {
+        edits
+            .iter()
+            .map(|x| {
+                assert_eq!((x.end - x.begin, x.replacement.len()), (1, 1));
+                x.begin
+            })
+            .collect()
+    };
+    // Either declaration of `a` can be renamed; the error points at the
+    // second one, the first one being renamed otherwise.
+    assert_eq!(
+        renamed(decls2_edits(vec![ErrorLocation::Token(27)])),
+        vec![18, 24]
+    );
+    assert_eq!(renamed(decls2_edits(Vec::new())), vec![5, 18]);
+}
+
+#[test]
+fn test_score_memo_cycle() {
+    // `a` and `b` derive each other through unit rules; `a` also costs 5
+    // and `b` 9 on their own.
+    fn score(memo: &ScoreMemo<char>, edge: char) -> usize {
+        memo.get_or_compute(edge, || match edge {
+            'a' => min(score(memo, 'b'), 5),
+            _ => min(score(memo, 'a'), 9),
+        })
+    }
+    let memo = ScoreMemo::default();
+    assert_eq!(score(&memo, 'a'), 5);
+    // Scored while `a` was open, `b` must not keep the 9 it got then.
+    assert_eq!(score(&memo, 'b'), 5);
+}
+
+#[test]
+fn test_rank_fixes() {
+    let fix = |tokens: &str| tokens.split(' ').map(|x| x.to_string()).collect::<Vec<_>>();
+    let fixes = vec![
+        (1, fix("a b")),
+        (0, fix("b")),
+        (1, fix("a a")),
+        (0, fix("a c")),
+        (1, fix("a a")),
+    ];
+    // Nearest to the errors first, then by tokens, without duplicates.
+    assert_eq!(
+        rank_fixes(fixes.clone(), 10),
+        vec![fix("a c"), fix("b"), fix("a a"), fix("a b")]
+    );
+    assert_eq!(rank_fixes(fixes, 2), vec![fix("a c"), fix("b")]);
+}
@@ -0,0 +1,73 @@
+use crate::{
+    eval::{Accuracy, EvalCmd},
+    grammars::registry,
+};
+use clap::Parser;
+use fixing_rs_base::edit_script::edit_distance;
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(edit_distance(&["a", "b", "c"], &["a", "b", "c"]), 0);
+    assert_eq!(edit_distance(&["a", "x", "x", "c"], &["a", "c"]), 2);
+    assert_eq!(edit_distance(&["a", "x", "c"], &["a", "y", "c", "d"]), 2);
+}
+
+#[test]
+fn test_eval() {
+    let cmd = EvalCmd::parse_from([
+        "eval",
+        "--lang",
+        "c",
+        "--max-len",
+        "2",
+        "--max-new-id",
+        "2",
+        "--manifest",
+        "src/tests/test_eval/manifest.csv",
+        "--oracle",
+        "! grep -q __new_id \"$FIX\"",
+    ]);
+    let evaluation = cmd.evaluate(&registry()).unwrap();
+
+    // `ids` is fixed as expected, `decls2` with a fix naming a new variable
+    // differently, `args` has no expected fix and the last input is missing.
+    let a = Accuracy {
+        tasks: 2,
+        fixed: 2,
+        expected: 2,
+        exact: 1,
+        same_length: 2,
+        checked: 2,
+        compiles: 1,
+    };
+    let b = Accuracy {
+        tasks: 2,
+        fixed: 1,
+        expected: 0,
+        exact: 0,
+        same_length: 0,
+        checked: 1,
+        compiles: 1,
+    };
+    assert_eq!(evaluation.by_category["a"], a);
+    assert_eq!(evaluation.by_category["b"], b);
+    assert_eq!(evaluation.overall.tasks, 4);
+    assert_eq!(evaluation.overall.compiles, 2);
+    assert!(evaluation.tasks[3].error.is_some());
+
+    let lengths: Vec<_> = evaluation.by_length.keys().copied().collect();
+    assert_eq!(lengths, vec![None, Some(1), Some(2)]);
+    assert_eq!(evaluation.by_length[&Some(2)].tasks, 2);
+
+    let times: Vec<_> = evaluation.time_percentiles.iter().map(|x| x.1).collect();
+    assert_eq!(times.len(), 4);
+    assert!(times.windows(2).all(|x| x[0] <= x[1]));
+
+    let json = evaluation.to_json();
+    assert_eq!(json["by_category"]["a"]["exact_rate"], 0.5);
+    assert_eq!(json["by_length"]["-"]["fixed"], 0);
+    assert_eq!(json["tasks"][1]["same_length"], true);
+    let table = evaluation.to_string();
+    assert!(table.contains("category b"));
+    assert!(table.contains("length 2"));
+}
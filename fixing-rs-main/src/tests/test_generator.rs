@@ -0,0 +1,83 @@
+use crate::{
+    grammars::registry,
+    mj::{
+        fixing::{MJFixingInputProcessor, MJ_GRAMMAR},
+        mjenv::{MJArena, MJEnv},
+        semantic::MJSProcessor,
+        syntactic::MJGProcessor,
+    },
+    mutate::{literals, Mutator},
+};
+use fixing_rs_base::{
+    fixing::{fix, FixTaskInfo, FixTaskResult},
+    generator::Generator,
+    grammar::{Grammar, GrammarArena},
+    utils::{RefArena, Rng},
+};
+use std::{fs, path::Path, process};
+
+const ENV: &str = "src/tests/test_mutate/env";
+const PROGRAMS: usize = 6;
+
+fn fix_file(input: &Path, max_len: usize) -> FixTaskResult {
+    let info = FixTaskInfo {
+        max_new_id: 1,
+        ..FixTaskInfo::new(
+            input.to_string_lossy().to_string(),
+            ENV.to_string(),
+            max_len,
+        )
+    };
+    let result = fix(std::iter::once(info), &MJFixingInputProcessor);
+    result.into_iter().next().unwrap().unwrap()
+}
+
+#[test]
+fn test_generator_mj() {
+    let arena = GrammarArena::new();
+    let grammar = Grammar::new(&arena, MJ_GRAMMAR).unwrap();
+    let mjarena = MJArena::new();
+    let env = MJEnv::build_from_env(&mjarena, &fs::read_to_string(ENV).unwrap()).unwrap();
+    let strs = RefArena::new();
+    let sproc = MJSProcessor::new(&env, &strs, &Vec::new(), 1);
+    let generator = Generator::new(&grammar, &MJGProcessor, &sproc, 24)
+        .min_tokens(8)
+        .max_tokens(40);
+
+    let language = registry();
+    let language = language.get("mj").unwrap();
+    let mutator = Mutator::new(
+        language.token_file().unwrap(),
+        grammar.get_symbol_ref().literal_terminals.keys().copied(),
+        &[],
+        &[],
+    );
+
+    let dir = std::env::temp_dir().join(format!("fixing-rs-generator-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut rng = Rng::new(0);
+    for i in 0..PROGRAMS {
+        let program = generator.generate(&mut rng).unwrap();
+        let input = dir.join(format!("{}", i));
+        fs::write(&input, program.join("\n")).unwrap();
+        assert_eq!(fix_file(&input, 0).found_length, Some(0));
+
+        // A mutant is fixed with at most as many edits as mutations, and
+        // its fix is valid as it is.
+        let mutations = 1 + i % 2;
+        let (mutant, _) = mutator
+            .mutate(&mutator.read(&program.join("\n")), mutations, &mut rng)
+            .unwrap();
+        fs::write(&input, literals(&mutant).join("\n")).unwrap();
+        let result = fix_file(&input, mutations);
+        assert!(result.found_length.unwrap() <= mutations);
+        fs::write(&input, result.outputs.unwrap().join("\n")).unwrap();
+        assert_eq!(fix_file(&input, 0).found_length, Some(0));
+    }
+    fs::remove_dir_all(&dir).ok();
+
+    let programs: Vec<_> = (0..2)
+        .map(|_| generator.generate(&mut Rng::new(1)).unwrap())
+        .collect();
+    assert_eq!(programs[0], programs[1]);
+}
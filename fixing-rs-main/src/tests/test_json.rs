@@ -0,0 +1,71 @@
+use crate::json::{
+    fixing::JsonFixingInputProcessor,
+    schema::{JsonSchema, JsonSchemaError},
+    tokenizer::detokenize,
+};
+use fixing_rs_base::fixing::{fix, FixTaskInfo, FixTaskResult};
+
+fn test_json_folder(folder: &str, dist: usize) -> FixTaskResult {
+    let info = FixTaskInfo {
+        max_new_id: 0,
+        verbose_gen: true,
+        ..FixTaskInfo::new(
+            format!("src/tests/test_json/{}/input.json", folder),
+            format!("src/tests/test_json/{}/env", folder),
+            dist,
+        )
+    };
+    let result = fix(std::iter::once(info), &JsonFixingInputProcessor);
+    let result: [Result<FixTaskResult, _>; 1] = result.try_into().unwrap();
+    let [result] = result;
+    let result = result.unwrap();
+    assert_eq!(result.found_length.unwrap(), dist);
+    println!("{:?}", result);
+    result
+}
+
+#[test]
+fn test_json_ok() {
+    test_json_folder("ok", 0);
+}
+
+#[test]
+fn test_json_comma() {
+    let result = test_json_folder("comma", 1);
+    let source = detokenize(&result.outputs.unwrap());
+    assert_eq!(
+        source,
+        "{\n  \"name\": \"app\",\n  \"mode\": \"debug\",\n  \"deps\": []\n}\n"
+    );
+}
+
+#[test]
+fn test_json_type() {
+    test_json_folder("type", 1);
+}
+
+#[test]
+fn test_json_missing() {
+    let result = test_json_folder("missing", 4);
+    assert!(result.outputs.unwrap().contains(&"\"mode\"".to_string()));
+}
+
+#[test]
+fn test_json_schema_errors() {
+    assert!(matches!(
+        JsonSchema::build("type A = B; type B = A;"),
+        Err(JsonSchemaError::CyclicType(_))
+    ));
+    assert!(matches!(
+        JsonSchema::build("type A = { x: Missing };"),
+        Err(JsonSchemaError::UndefinedType(_))
+    ));
+    assert!(matches!(
+        JsonSchema::build("type A = { x: number, x: string };"),
+        Err(JsonSchemaError::DuplicateField(_))
+    ));
+    assert!(matches!(
+        JsonSchema::build(""),
+        Err(JsonSchemaError::EmptySchema)
+    ));
+}
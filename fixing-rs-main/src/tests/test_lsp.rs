@@ -0,0 +1,191 @@
+use crate::{
+    grammars::registry,
+    lsp::{offset_of, position_of, read_message, write_message, LspCmd, LspServer},
+    mj::document::{env_from_document, method_bodies},
+};
+use clap::{error::ErrorKind, Parser};
+use fixing_rs_base::{
+    edit_script::{edit_script, TokenEdit},
+    grammar::{Grammar, GrammarArena},
+    language::LanguageOptions,
+};
+use serde_json::{json, Value};
+use std::{collections::HashSet, io::Cursor};
+
+const URI: &str = "file:///test.mj";
+
+fn document(body: &str) -> String {
+    let env = std::fs::read_to_string("src/tests/test_mj/decl/env").unwrap();
+    env.replace("void METHODFIX() {}", &format!("void METHODFIX() {}", body))
+}
+
+/// Plays `requests` against a server and returns its responses.
+fn run_session(requests: &[Value]) -> Vec<Value> {
+    let mut input = Vec::new();
+    for request in requests {
+        write_message(&mut input, request).unwrap();
+    }
+    let registry = registry();
+    let language = registry.get("mj").unwrap();
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, language.info().grammar).unwrap();
+    let fixer = language.fixer(&LanguageOptions::default()).unwrap();
+    let format = language.document_format().unwrap();
+    let cmd = LspCmd::parse_from(["lsp", "--max-len", "2", "--max-new-id", "2"]);
+    let mut server = LspServer::new(&grammar, fixer, format, cmd);
+    let mut output = Vec::new();
+    server.serve(&mut Cursor::new(input), &mut output).unwrap();
+
+    let mut output = Cursor::new(output);
+    let mut responses = Vec::new();
+    while let Some(response) = read_message(&mut output).unwrap() {
+        responses.push(response);
+    }
+    responses
+}
+
+fn code_actions(text: &str, line: usize, character: usize) -> Vec<Value> {
+    let position = json!({ "line": line, "character": character });
+    let range = json!({ "start": position, "end": position });
+    let responses = run_session(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        // A response of the client, which is not answered.
+        json!({ "jsonrpc": "2.0", "id": 1, "result": null }),
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": URI, "languageId": "mj", "version": 1, "text": text } },
+        }),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": URI },
+                "range": range,
+                "context": { "diagnostics": [{ "range": range, "message": "error" }] },
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ]);
+    assert_eq!(responses.len(), 3);
+    assert_eq!(
+        responses[0]["result"]["capabilities"]["codeActionProvider"],
+        true
+    );
+    assert_eq!(responses[1]["id"], 2);
+    assert_eq!(responses[2]["result"], Value::Null);
+    responses[1]["result"].as_array().unwrap().clone()
+}
+
+fn apply(text: &str, action: &Value) -> String {
+    let offset = |x: &Value| {
+        offset_of(
+            text,
+            x["line"].as_u64().unwrap() as usize,
+            x["character"].as_u64().unwrap() as usize,
+        )
+    };
+    let mut edits: Vec<_> = action["edit"]["changes"][URI]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| {
+            let begin = offset(&x["range"]["start"]);
+            let end = offset(&x["range"]["end"]);
+            (begin, end, x["newText"].as_str().unwrap().to_string())
+        })
+        .collect();
+    edits.sort_by_key(|(begin, _, _)| *begin);
+    let mut result = text.to_string();
+    for (begin, end, new_text) in edits.into_iter().rev() {
+        result.replace_range(begin..end, &new_text);
+    }
+    result
+}
+
+#[test]
+fn test_lsp_code_action() {
+    let text = document("{ CLASS_729 CLASS_729 VAR_999 ; CLASS_999 VAR_703 ; }");
+    let (line, character) = position_of(&text, text.find("CLASS_729 VAR_999").unwrap());
+    let actions = code_actions(&text, line, character);
+    assert!(!actions.is_empty());
+    assert!(actions.len() <= 3);
+    assert_eq!(actions[0]["kind"], "quickfix");
+    let fixed: Vec<_> = actions.iter().map(|x| apply(&text, x)).collect();
+    let distinct: HashSet<_> = fixed.iter().collect();
+    assert_eq!(distinct.len(), fixed.len());
+
+    // The preferred fix deletes the repeated type.
+    let edits = actions[0]["edit"]["changes"][URI].as_array().unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0]["newText"], "");
+    let begin = text.find("CLASS_729 VAR_999").unwrap();
+    let (line, character) = position_of(&text, begin);
+    assert_eq!(
+        edits[0]["range"]["start"],
+        json!({ "line": line, "character": character })
+    );
+    let (line, character) = position_of(&text, begin + "CLASS_729".len());
+    assert_eq!(
+        edits[0]["range"]["end"],
+        json!({ "line": line, "character": character })
+    );
+    let fixed = &fixed[0];
+    assert!(fixed.contains("{ CLASS_729  VAR_999 ; CLASS_999 VAR_703 ; }"));
+
+    // A fixed document needs no further fix.
+    assert_ne!(fixed, &text);
+    let (line, character) = position_of(fixed, fixed.find("METHODFIX").unwrap() + 12);
+    assert!(code_actions(fixed, line, character).is_empty());
+}
+
+#[test]
+fn test_lsp_language() {
+    let cmd = LspCmd::parse_from(["lsp", "--lang", "c"]);
+    assert_eq!(
+        cmd.run(&registry()).unwrap_err().kind(),
+        ErrorKind::InvalidValue
+    );
+}
+
+#[test]
+fn test_lsp_document() {
+    let text = "class A extends Object {\n  A() {super();}\n  void m() { if ( { } }\n}\n";
+    let bodies = method_bodies(text);
+    assert_eq!(bodies.len(), 2);
+    assert_eq!(&text[bodies[1].clone()], "{ if ( { } }");
+    assert_eq!(
+        env_from_document(text, &bodies),
+        "class A extends Object {\n  A() {}\n  void m() {}\n}\n"
+    );
+    let text = "a\u{e9}\u{1F600}b\nc";
+    assert_eq!(position_of(text, text.find('b').unwrap()), (0, 4));
+    assert_eq!(offset_of(text, 0, 4), text.find('b').unwrap());
+    assert_eq!(offset_of(text, 1, 0), text.find('c').unwrap());
+    assert_eq!(offset_of(text, 0, 100), text.find('\n').unwrap());
+}
+
+#[test]
+fn test_edit_script() {
+    let edit = |begin, end, replacement: &[&str]| TokenEdit {
+        begin,
+        end,
+        replacement: replacement.iter().map(|x| x.to_string()).collect(),
+    };
+    assert_eq!(edit_script(&["a", "b", "c"], &["a", "b", "c"]), vec![]);
+    assert_eq!(
+        edit_script(&["a", "x", "x", "c"], &["a", "c"]),
+        vec![edit(1, 3, &[])]
+    );
+    assert_eq!(
+        edit_script(&["a", "c"], &["a", "b", "c", "d"]),
+        vec![edit(1, 1, &["b"]), edit(2, 2, &["d"])]
+    );
+    assert_eq!(
+        edit_script(&["a", "x", "c"], &["a", "y", "c"]),
+        vec![edit(1, 2, &["y"])]
+    );
+}
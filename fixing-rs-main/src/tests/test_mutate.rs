@@ -0,0 +1,137 @@
+use crate::{
+    eval::EvalCmd,
+    grammars::registry,
+    mutate::{literals, Category, MutateCmd, Mutation, Mutator, Operation},
+};
+use clap::{Parser, ValueEnum};
+use fixing_rs_base::{
+    edit_script::edit_distance,
+    grammar::{Grammar, GrammarArena},
+    utils::Rng,
+};
+use std::{fs, process};
+
+fn mutate(out: &str, seed: &str) {
+    MutateCmd::parse_from([
+        "mutate",
+        "--lang",
+        "mj",
+        "--input",
+        "src/tests/test_mutate/block",
+        "--env",
+        "src/tests/test_mutate/env",
+        "--out",
+        out,
+        "--mutations",
+        "2",
+        "--mutants",
+        "4",
+        "--seed",
+        seed,
+    ])
+    .run(&registry())
+    .unwrap();
+}
+
+fn read_dir(dir: &str) -> Vec<String> {
+    (0..4)
+        .map(|i| fs::read_to_string(format!("{}/{}/block", dir, i)).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_mutate() {
+    let dir = std::env::temp_dir().join(format!("fixing-rs-mutate-{}", process::id()));
+    let out = |name: &str| dir.join(name).to_string_lossy().to_string();
+    mutate(&out("a"), "1");
+    mutate(&out("b"), "1");
+    mutate(&out("c"), "2");
+    let mutants = read_dir(&out("a"));
+    assert_eq!(mutants, read_dir(&out("b")));
+    assert_ne!(mutants, read_dir(&out("c")));
+
+    let expected = fs::read_to_string(out("a") + "/0/expected").unwrap();
+    let expected: Vec<&str> = expected.lines().collect();
+    for mutant in mutants.iter() {
+        let mutant: Vec<&str> = mutant.lines().collect();
+        assert!((1..=2).contains(&edit_distance(&mutant, &expected)));
+    }
+
+    // Each mutant is listed with the two mutations applied to it.
+    let manifest = fs::read_to_string(out("a") + "/manifest.csv").unwrap();
+    let names: Vec<String> = Operation::value_variants()
+        .iter()
+        .flat_map(|&operation| {
+            Category::value_variants().iter().map(move |&category| {
+                Mutation {
+                    operation,
+                    category,
+                }
+                .to_string()
+            })
+        })
+        .collect();
+    for line in manifest.lines().skip(1) {
+        let category = line.rsplit(',').next().unwrap();
+        let mutations: Vec<&str> = category.split('+').collect();
+        assert_eq!(mutations.len(), 2);
+        assert!(mutations.iter().all(|x| names.contains(&x.to_string())));
+    }
+
+    // The original program being correct, every mutant has a fix no longer
+    // than the mutations applied.
+    let evaluation = EvalCmd::parse_from([
+        "eval",
+        "--lang",
+        "mj",
+        "--max-len",
+        "2",
+        "--max-new-id",
+        "1",
+        "--manifest",
+        &(out("a") + "/manifest.csv"),
+    ])
+    .evaluate(&registry())
+    .unwrap();
+    assert_eq!(evaluation.overall.tasks, 4);
+    assert_eq!(evaluation.overall.fixed, 4);
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_mutate_tagged() {
+    let registry = registry();
+    let language = registry.get("c").unwrap();
+    let arena = GrammarArena::new();
+    let grammar = Grammar::new(&arena, language.info().grammar).unwrap();
+    let mutator = Mutator::new(
+        language.token_file().unwrap(),
+        grammar.get_symbol_ref().literal_terminals.keys().copied(),
+        &[Category::Identifiers],
+        &[],
+    );
+    let original = mutator.read(&fs::read_to_string("src/tests/test_c/ids/c.tokens").unwrap());
+    let mut rng = Rng::new(0);
+    for _ in 0..8 {
+        let (mutant, mutations) = mutator.mutate(&original, 1, &mut rng).unwrap();
+        let operation = match mutations[..] {
+            [Mutation {
+                operation,
+                category: Category::Identifiers,
+            }] => operation,
+            _ => panic!("Unexpected mutations: {:?}", mutations),
+        };
+        let added = mutant.len() as isize - original.len() as isize;
+        match operation {
+            Operation::Insert | Operation::Duplicate => assert_eq!(added, 1),
+            Operation::Delete => assert_eq!(added, -1),
+            Operation::Replace => assert_eq!(added, 0),
+        }
+        let changed: Vec<_> = mutant.iter().filter(|x| !original.contains(x)).collect();
+        assert!(changed
+            .iter()
+            .all(|x| x.text.starts_with("ST\tIDENTIFIER\t")));
+        let length = edit_distance(&literals(&mutant), &literals(&original));
+        assert_eq!(length, 1);
+    }
+}
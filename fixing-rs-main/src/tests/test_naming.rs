@@ -0,0 +1,78 @@
+use crate::c::fixing::CFixingInputProcessor;
+use fixing_rs_base::{
+    fixing::{fix, FixTaskInfo},
+    naming::{
+        rename_new_ids, NamingScope, NamingStrategy, NearbyNaming, PlaceholderNaming, TypeNaming,
+        VocabularyNaming,
+    },
+};
+use std::sync::Arc;
+
+fn tokens(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|x| x.to_string()).collect()
+}
+
+fn previous_token(tokens: &[String], position: usize) -> Option<String> {
+    position.checked_sub(1).map(|x| tokens[x].clone())
+}
+
+fn rename(input: &str, strategy: &dyn NamingStrategy, reserved: &[&str]) -> String {
+    let mut scope = NamingScope::new(reserved.iter().map(|x| x.to_string()), previous_token);
+    scope.reserve_keywords(["int".to_string()]);
+    rename_new_ids(&tokens(input), strategy, &scope).join(" ")
+}
+
+#[test]
+fn test_naming_strategies() {
+    let input = "Foo __new_id_0 ; Foo __new_id_1 ; x = __new_id_0 ;";
+    assert_eq!(rename(input, &PlaceholderNaming, &[]), input);
+    assert_eq!(
+        rename(input, &TypeNaming, &[]),
+        "Foo foo ; Foo foo1 ; x = foo ;"
+    );
+    assert_eq!(
+        rename(input, &TypeNaming, &["foo", "foo1"]),
+        "Foo foo2 ; Foo foo3 ; x = foo2 ;"
+    );
+    assert_eq!(
+        rename("CLASS_1 __new_id_0 ; int __new_id_1 ;", &TypeNaming, &[]),
+        "CLASS_1 class_1 ; int i ;"
+    );
+    assert_eq!(
+        rename("int __new_id_0 ; int i ;", &TypeNaming, &["i1"]),
+        "int i2 ; int i ;"
+    );
+    assert_eq!(
+        rename("int count ; int __new_id_0 ;", &NearbyNaming, &[]),
+        "int count ; int count1 ;"
+    );
+    assert_eq!(
+        rename("int v1 ; int __new_id_0 ;", &NearbyNaming, &[]),
+        "int v1 ; int v1_1 ;"
+    );
+    let vocabulary = VocabularyNaming::new(["tmp", "result", "not valid"].map(String::from));
+    assert_eq!(
+        rename(
+            "int __new_id_0 , __new_id_1 , __new_id_2 ;",
+            &vocabulary,
+            &["result"]
+        ),
+        "int tmp , tmp1 , tmp2 ;"
+    );
+}
+
+#[test]
+fn test_fix_with_type_naming() {
+    let folder = "src/tests/test_c/decls2";
+    let info = FixTaskInfo {
+        naming: Arc::new(TypeNaming),
+        ..FixTaskInfo::new(format!("{}/c.tokens", folder), format!("{}/env", folder), 2)
+    };
+    let result = fix(std::iter::once(info), &CFixingInputProcessor::default());
+    let result = result.into_iter().next().unwrap().unwrap();
+    assert_eq!(result.found_length, Some(2));
+    let outputs = result.outputs.unwrap();
+    assert!(outputs.iter().all(|x| !x.starts_with("__new_id_")));
+    assert_eq!(outputs.iter().filter(|x| *x == "i1").count(), 1);
+    assert_eq!(outputs.iter().filter(|x| *x == "i2").count(), 1);
+}
@@ -0,0 +1,57 @@
+use crate::mj::{semantic::MJSynProp, syntactic::MJProp};
+use fixing_rs_base::props::{PropArray, PropEmpty, PropInterner, PropStore};
+
+#[test]
+fn test_prop_interner() {
+    let interner = PropInterner::new();
+    let single = interner.intern(PropArray::new_single(PropEmpty));
+    let multiple = interner.intern(PropArray::new_multiple(vec![PropEmpty]));
+    let zero = interner.intern(PropArray::new_zero());
+    assert_eq!(interner.len(), 3);
+    assert_eq!(single, interner.intern(PropArray::new_single(PropEmpty)));
+    assert_eq!(
+        multiple,
+        interner.intern(PropArray::new_zero().append(PropEmpty))
+    );
+    assert_eq!(zero, interner.intern(PropArray::new_multiple(vec![])));
+    assert_ne!(single, multiple);
+    assert_eq!(interner.len(), 3);
+}
+
+#[test]
+fn test_prop_store() {
+    // Empty props are unshared, the props of MiniJava interned.
+    let unshared: PropStore<PropEmpty, _> = PropStore::new();
+    let first = unshared.store(PropArray::new_single(PropEmpty));
+    let second = unshared.store(PropArray::new_single(PropEmpty));
+    assert_eq!(unshared.len(), 2);
+    assert_eq!(first, second);
+    assert!(!std::ptr::eq(first.get(), second.get()));
+    assert_ne!(
+        unshared.store(PropArray::new_zero()),
+        unshared.store(PropArray::new_zero().append(PropEmpty))
+    );
+
+    let interned: PropStore<MJSynProp, _> = PropStore::new();
+    let first = interned.store(PropArray::new_single(MJSynProp::default()));
+    let second = interned.store(PropArray::new_single(MJSynProp::default()));
+    assert_eq!(interned.len(), 1);
+    assert_eq!(first, second);
+    assert!(std::ptr::eq(first.get(), second.get()));
+
+    let syntactic: PropStore<MJProp, _> = PropStore::new();
+    let first = syntactic.store(PropArray::new_single(MJProp::default()));
+    let second = syntactic.store(PropArray::new_single(MJProp::default()));
+    assert_eq!(syntactic.len(), 1);
+    assert!(std::ptr::eq(first.get(), second.get()));
+    assert_ne!(
+        interned.store(PropArray::new_zero()),
+        interned.store(PropArray::new_zero().append(MJSynProp::default()))
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_prop_array_kinds() {
+    let _ = PropArray::new_single(PropEmpty) == PropArray::new_multiple(vec![PropEmpty]);
+}
@@ -0,0 +1,68 @@
+use crate::py::{
+    fixing::{PyFixingInputProcessor, PY_GRAMMAR},
+    tokenizer::{detokenize, PyTokenizer},
+};
+use fixing_rs_base::{
+    fixing::{fix, FixTaskInfo, FixTaskResult},
+    grammar::{Grammar, GrammarArena},
+    tokenizer::Tokenizer,
+};
+
+fn test_py_folder(folder: &str, dist: usize) -> FixTaskResult {
+    let info = FixTaskInfo {
+        verbose_gen: true,
+        ..FixTaskInfo::new(
+            format!("src/tests/test_py/{}/input.py", folder),
+            format!("src/tests/test_py/{}/env", folder),
+            dist,
+        )
+    };
+    let result = fix(std::iter::once(info), &PyFixingInputProcessor);
+    let result: [Result<FixTaskResult, _>; 1] = result.try_into().unwrap();
+    let [result] = result;
+    let result = result.unwrap();
+    assert_eq!(result.found_length.unwrap(), dist);
+    println!("{:?}", result);
+    result
+}
+
+#[test]
+fn test_py_ok() {
+    test_py_folder("ok", 0);
+}
+
+#[test]
+fn test_py_colon() {
+    let result = test_py_folder("colon", 1);
+    let source = detokenize(&result.outputs.unwrap());
+    assert!(source.contains("    for x in xs:\n        s += x\n"));
+}
+
+#[test]
+fn test_py_arity() {
+    test_py_folder("arity", 1);
+}
+
+#[test]
+fn test_py_names() {
+    test_py_folder("names", 1);
+}
+
+#[test]
+fn test_py_detokenize() {
+    let input = std::fs::read_to_string("src/tests/test_py/ok/input.py").unwrap();
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, PY_GRAMMAR).unwrap();
+    let tokens = PyTokenizer
+        .tokenize(&input, grammar.get_symbol_ref())
+        .unwrap();
+    let names = tokens
+        .iter()
+        .map(|x| match x.literal {
+            "" => x.symbol.name(),
+            literal => literal,
+        })
+        .collect::<Vec<_>>();
+    // Blank lines are not kept.
+    assert_eq!(detokenize(&names), input.replace("\n\n", "\n"));
+}
@@ -0,0 +1,4 @@
+count = 0
+while count < 10:
+    count += 1
+print(cout)
@@ -0,0 +1,11 @@
+def total(xs):
+    s = 0
+    for x in xs:
+        s += x
+    return s
+
+xs = [1, 2, 3]
+if len(xs) > 2:
+    print(total(xs))
+else:
+    print("short")
@@ -0,0 +1,188 @@
+use crate::{
+    grammars::{registry, CLanguage},
+    serve::{
+        ResidentGrammars, INVALID_PARAMS, MEMORY_BUDGET_EXCEEDED, METHOD_NOT_FOUND, PARSE_ERROR,
+    },
+};
+use fixing_rs_base::{
+    fixing_info::FixingInfo,
+    grammar::GrammarArena,
+    language::{Fixer, Language, LanguageOptions},
+};
+use serde_json::{json, Value};
+
+fn read(path: &str) -> String {
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn fix_request(id: usize, lang: &str, dir: &str, input: &str, params: Value) -> String {
+    let mut request = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "fix",
+        "params": {
+            "lang": lang,
+            "input": read(&format!("{}/{}", dir, input)),
+            "env": read(&format!("{}/env", dir)),
+        },
+    });
+    for (key, value) in params.as_object().unwrap() {
+        request["params"][key] = value.clone();
+    }
+    request.to_string()
+}
+
+#[test]
+fn test_serve() {
+    let arena = GrammarArena::new();
+    let grammars = ResidentGrammars::new(&arena, registry());
+
+    // Grammars are reused across requests.
+    for id in 0..2 {
+        let request = fix_request(
+            id,
+            "c",
+            "src/tests/test_c/decls2",
+            "c.tokens",
+            json!({ "max_len": 2, "naming": "type" }),
+        );
+        let response = grammars.handle(&request);
+        assert_eq!(response["id"], id);
+        assert_eq!(response["result"]["found_length"], 2);
+        let outputs = response["result"]["outputs"].as_array().unwrap();
+        assert!(outputs.contains(&json!("i1")));
+        let source = response["result"]["source"].as_str().unwrap();
+        assert!(source.contains(" i1 "));
+    }
+
+    let request = fix_request(
+        2,
+        "mj",
+        "src/tests/test_mj/decl",
+        "block",
+        json!({ "max_len": 1 }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["result"]["found_length"], 1);
+    let stats = &response["result"]["stats"];
+    assert!(stats["semantic_keys"].as_u64().unwrap() > 0);
+    assert_eq!(stats["props_produced"], Value::Null);
+    let edges = stats["syntactic_edges"].as_array().unwrap();
+    assert!(edges.iter().any(|count| count.as_u64().unwrap() > 0));
+
+    // Requests cannot make the server write files.
+    let profile = std::env::temp_dir().join(format!("fixing-rs-serve-{}", std::process::id()));
+    let request = fix_request(
+        2,
+        "mj",
+        "src/tests/test_mj/decl",
+        "block",
+        json!({ "max_len": 1, "profile": profile.to_string_lossy() }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["result"]["found_length"], 1);
+    assert_eq!(response["result"]["stats"]["props_produced"], Value::Null);
+    assert!(!profile.exists());
+
+    let response = grammars.handle("{ not json");
+    assert_eq!(response["id"], Value::Null);
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+    let response = grammars.handle(r#"{"jsonrpc":"2.0","id":3,"method":"check"}"#);
+    assert_eq!(response["id"], 3);
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+    let request = fix_request(
+        4,
+        "c",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "time_limit": -1 }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+    let request = fix_request(
+        5,
+        "c",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "options": { "c_strictness": "c11" } }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+    let request = fix_request(
+        6,
+        "c",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "memory_budget": 1024 }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["error"]["code"], MEMORY_BUDGET_EXCEEDED);
+
+    // Only the options object is passed to the language.
+    let request = fix_request(
+        7,
+        "c",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "options": { "c_strictness": "c99" } }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["result"]["found_length"], 2);
+
+    let request = fix_request(
+        8,
+        "c",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "options": "c99" }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+}
+
+/// C fixed under C99 rules whatever the request asks for.
+struct StrictC;
+
+impl Language for StrictC {
+    fn name(&self) -> &'static str {
+        "strict-c"
+    }
+
+    fn info(&self) -> &'static FixingInfo {
+        CLanguage.info()
+    }
+
+    fn fixer(&self, options: &LanguageOptions) -> Result<Box<dyn Fixer>, String> {
+        let mut options = options.clone();
+        options.insert("c_strictness".to_string(), "c99".to_string());
+        CLanguage.fixer(&options)
+    }
+
+    fn detokenize(&self, tokens: &[String]) -> String {
+        tokens.concat()
+    }
+}
+
+#[test]
+fn test_serve_plugin() {
+    let mut registry = registry();
+    registry.register(StrictC);
+    let arena = GrammarArena::new();
+    let grammars = ResidentGrammars::new(&arena, registry);
+
+    let request = fix_request(
+        0,
+        "Strict-C",
+        "src/tests/test_c/decls2",
+        "c.tokens",
+        json!({ "max_len": 2, "options": { "c_strictness": "c89-permissive" } }),
+    );
+    let response = grammars.handle(&request);
+    assert_eq!(response["result"]["found_length"], 2);
+    let source = response["result"]["source"].as_str().unwrap();
+    assert!(!source.contains(' '));
+}
@@ -0,0 +1,121 @@
+use fixing_rs_base::utils::{PersistentMap, SymTab};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+fn hash_of<T: Hash>(x: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    x.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_persistent_map() {
+    let keys = [5, 3, 8, 1, 9, 2, 7, 4, 6, 0];
+    let versions: Vec<_> = keys
+        .iter()
+        .scan(PersistentMap::new(), |map, k| {
+            *map = map.insert(*k, k * 10);
+            Some(map.clone())
+        })
+        .collect();
+    for (i, map) in versions.iter().enumerate() {
+        assert_eq!(map.len(), i + 1);
+        let iterated: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        let expected: Vec<_> = keys[..=i].iter().map(|k| (*k, k * 10)).collect();
+        assert_eq!(iterated, expected);
+        assert_eq!(map.get(&keys[i]), Some(&(keys[i] * 10)));
+        assert_eq!(map.get(&11), None);
+    }
+
+    let last = versions.last().unwrap();
+    let updated = last.insert(1, 100);
+    assert_eq!(updated.len(), last.len());
+    assert_eq!(updated.get(&1), Some(&100));
+    assert_eq!(last.get(&1), Some(&10));
+    assert_eq!(updated.iter().position(|(k, _)| *k == 1), Some(3));
+    assert!(last.ptr_eq(&last.clone()));
+    assert!(!last.ptr_eq(&updated));
+}
+
+/// A key whose hash only depends on its value modulo 4.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Colliding(usize);
+
+impl Hash for Colliding {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.0 % 4).hash(state);
+    }
+}
+
+#[test]
+fn test_persistent_map_index() {
+    // Past a few keys, keys are found through an index on their hashes,
+    // where keys with the same hash are still told apart.
+    for n in [40, 100] {
+        let keys = (0..n).map(|i| (i * 7) % n);
+        let plain = keys
+            .clone()
+            .fold(PersistentMap::new(), |map, k| map.insert(k, k * 10));
+        let colliding = keys.fold(PersistentMap::new(), |map, k| {
+            map.insert(Colliding(k), k * 10)
+        });
+        assert_eq!(plain.len(), n);
+        assert_eq!(colliding.len(), n);
+        for k in 0..n {
+            assert_eq!(plain.get(&k), Some(&(k * 10)));
+            assert_eq!(colliding.get(&Colliding(k)), Some(&(k * 10)));
+        }
+        assert_eq!(plain.get(&n), None);
+        assert_eq!(colliding.get(&Colliding(n)), None);
+
+        let updated = colliding.insert(Colliding(5), 0).insert(Colliding(n), 0);
+        assert_eq!(updated.len(), n + 1);
+        assert_eq!(updated.get(&Colliding(5)), Some(&0));
+        assert_eq!(colliding.get(&Colliding(5)), Some(&50));
+        let order: Vec<_> = updated.iter().map(|(k, _)| k.0).collect();
+        let expected: Vec<_> = (0..n).map(|i| (i * 7) % n).chain([n]).collect();
+        assert_eq!(order, expected);
+    }
+}
+
+#[test]
+fn test_symtab_scopes() {
+    let outer: SymTab<usize, usize> = [(1, 10), (2, 20)].into_iter().collect();
+    assert!(!outer.is_empty());
+    assert!(SymTab::<usize, usize>::new().is_empty());
+    let inner = outer.new_scope().extend(3, 30);
+    assert!(outer.is_current(1));
+    assert!(!inner.is_current(1));
+    assert!(inner.is_current(3));
+    assert_eq!(inner.get(1), Some(10));
+    assert_eq!(
+        inner.iter_current().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![3]
+    );
+
+    let shadowed = inner.extend(1, 11);
+    assert!(shadowed.is_current(1));
+    assert_eq!(shadowed.get(1), Some(11));
+    assert_eq!(
+        shadowed.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    let updated = inner.update(1, 12);
+    assert!(!updated.is_current(1));
+    assert_eq!(updated.get(1), Some(12));
+    assert_eq!(outer.get(1), Some(10));
+
+    // Tables built differently are equal, with equal hashes, when they
+    // declare the same names in the same scopes.
+    let rebuilt = SymTab::from_iter([(1, 10)])
+        .extend_outer(2, 20)
+        .new_scope()
+        .extend(3, 30);
+    assert_eq!(inner, rebuilt);
+    assert_eq!(hash_of(&inner), hash_of(&rebuilt));
+    assert_ne!(inner, outer.extend(3, 30));
+    assert_ne!(inner, rebuilt.update(2, 21));
+    assert_eq!(inner, rebuilt.update(2, 21).update(2, 20));
+}
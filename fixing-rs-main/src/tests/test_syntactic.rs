@@ -1,10 +1,11 @@
 use crate::mj::{fixing::MJ_GRAMMAR, syntactic::MJGProcessor, tokenizer::MJTokenizer};
 use fixing_rs_base::{
     grammar::{Grammar, GrammarArena},
-    reachability::{GReachability, GReachabilityArena},
+    props::UnionProp,
+    reachability::{GKeyRef, GProcessor, GReachability, GReachabilityArena, GReachabilityOptions},
     tokenizer::Tokenizer,
 };
-use std::{fs::File, io::Write};
+use std::{collections::HashSet, fs::File, io::Write, time::Instant};
 
 const TEST_SYNTACTIC: &'static [(&'static str, usize)] = &[
     (
@@ -26,8 +27,14 @@ fn test_syntactic() {
     for (t_str, t_len) in TEST_SYNTACTIC {
         let syntactic_arena = GReachabilityArena::new();
         let tokens = MJTokenizer.tokenize(t_str, symbol_ref).unwrap();
-        let mut syntactic_reachability =
-            GReachability::new(&grammar, &syntactic_arena, &tokens, &MJGProcessor, *t_len);
+        let mut syntactic_reachability = GReachability::new(
+            &grammar,
+            &syntactic_arena,
+            &tokens,
+            &MJGProcessor,
+            *t_len,
+            GReachabilityOptions::default(),
+        );
         syntactic_reachability.update_until(*t_len);
         let mut output_file = File::create("../target/mjgrammar-syntactic-reachability").unwrap();
         write!(&mut output_file, "{}", syntactic_reachability).unwrap();
@@ -39,3 +46,185 @@ fn test_syntactic() {
             .unwrap();
     }
 }
+
+#[test]
+fn test_syntactic_prune() {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, MJ_GRAMMAR).unwrap();
+    let symbol_ref = grammar.get_symbol_ref();
+    for (t_str, t_len) in TEST_SYNTACTIC {
+        let tokens = MJTokenizer.tokenize(t_str, symbol_ref).unwrap();
+        let arenas = [GReachabilityArena::new(), GReachabilityArena::new()];
+        let [full, pruned] = [false, true].map(|prune| {
+            let mut reachability = GReachability::new(
+                &grammar,
+                &arenas[prune as usize],
+                &tokens,
+                &MJGProcessor,
+                *t_len,
+                GReachabilityOptions {
+                    prune,
+                    ..Default::default()
+                },
+            );
+            reachability.update_until(*t_len);
+            reachability
+        });
+        let start_lengths = |r: &GReachability<_, _>| {
+            r.get_start_edges()
+                .iter()
+                .map(|x| x.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(start_lengths(&pruned), start_lengths(&full));
+        assert!(pruned.edge_count() < full.edge_count());
+    }
+}
+
+#[test]
+fn test_syntactic_deadline() {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, MJ_GRAMMAR).unwrap();
+    let symbol_ref = grammar.get_symbol_ref();
+    let (t_str, t_len) = TEST_SYNTACTIC[2];
+    let tokens = MJTokenizer.tokenize(t_str, symbol_ref).unwrap();
+    let arenas = [GReachabilityArena::new(), GReachabilityArena::new()];
+    let [full, stopped] = [None, Some(Instant::now())].map(|deadline| {
+        let arena = &arenas[deadline.is_some() as usize];
+        let mut reachability = GReachability::new(
+            &grammar,
+            arena,
+            &tokens,
+            &MJGProcessor,
+            t_len,
+            GReachabilityOptions::default(),
+        );
+        reachability.set_deadline(deadline);
+        reachability.update_until(t_len);
+        reachability
+    });
+    // Updates stop after the first batch of edges once the deadline has
+    // passed, before any edge of the length asked for is built.
+    assert!(!full.deadline_exceeded());
+    assert!(stopped.deadline_exceeded());
+    assert!(stopped.edge_count() < full.edge_count());
+    assert!(stopped
+        .get_start_edges()
+        .get(t_len)
+        .is_none_or(|x| x.is_empty()));
+}
+
+/// The edges derivable from the start edges, each followed by its
+/// derivations, in the order they are stored.
+fn derivations<'a, 'b, PG, GProc>(
+    reachability: &GReachability<'a, 'b, '_, PG, GProc>,
+) -> Vec<String>
+where
+    PG: UnionProp,
+    GProc: GProcessor<PG = PG>,
+{
+    fn visit<'a, 'b, PG, GProc>(
+        reachability: &GReachability<'a, 'b, '_, PG, GProc>,
+        edge: GKeyRef<'a, 'b, PG>,
+        seen: &mut HashSet<GKeyRef<'a, 'b, PG>>,
+        result: &mut Vec<String>,
+    ) where
+        PG: UnionProp,
+        GProc: GProcessor<PG = PG>,
+    {
+        if !seen.insert(edge) {
+            return;
+        }
+        result.push(edge.ptr().to_string());
+        for rule in reachability.get_sub_edges(edge).iter() {
+            result.push(format!(" {} {}", rule.rule().ptr(), rule.ptr()));
+            for sub in [rule.sub1(), rule.sub2()].into_iter().flatten() {
+                visit(reachability, sub, seen, result);
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for (length, edges) in reachability.get_start_edges().iter().enumerate() {
+        result.push(format!("Length {}", length));
+        for edge in edges {
+            visit(reachability, *edge, &mut seen, &mut result);
+        }
+    }
+    result
+}
+
+#[test]
+fn test_syntactic_prune_derivations() {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, MJ_GRAMMAR).unwrap();
+    let symbol_ref = grammar.get_symbol_ref();
+    let block = std::fs::read_to_string("src/tests/test_mj/decl/block").unwrap();
+    let inputs = TEST_SYNTACTIC
+        .iter()
+        .copied()
+        .chain(std::iter::once((block.as_str(), 2)));
+    for (t_str, t_len) in inputs {
+        let tokens = MJTokenizer.tokenize(t_str, symbol_ref).unwrap();
+        let arenas = [GReachabilityArena::new(), GReachabilityArena::new()];
+        let [full, pruned] = [false, true].map(|prune| {
+            let mut reachability = GReachability::new(
+                &grammar,
+                &arenas[prune as usize],
+                &tokens,
+                &MJGProcessor,
+                t_len,
+                GReachabilityOptions {
+                    prune,
+                    ..Default::default()
+                },
+            );
+            reachability.update_until(t_len);
+            derivations(&reachability)
+        });
+        // Pruning only drops edges no start edge derives, so the start edges
+        // keep all their derivations.
+        assert_eq!(pruned, full);
+    }
+}
+
+#[test]
+fn test_syntactic_parallel() {
+    let grammar_arena = GrammarArena::new();
+    let grammar = Grammar::new(&grammar_arena, MJ_GRAMMAR).unwrap();
+    let symbol_ref = grammar.get_symbol_ref();
+    let block = std::fs::read_to_string("src/tests/test_mj/decl/block").unwrap();
+    let inputs = TEST_SYNTACTIC
+        .iter()
+        .copied()
+        .chain(std::iter::once((block.as_str(), 2)));
+    for (t_str, t_len) in inputs {
+        let tokens = MJTokenizer.tokenize(t_str, symbol_ref).unwrap();
+        let arenas = [(); 4].map(|_| GReachabilityArena::new());
+        let build = |arena, threads, stepwise: bool| {
+            let mut reachability = GReachability::new(
+                &grammar,
+                arena,
+                &tokens,
+                &MJGProcessor,
+                t_len,
+                GReachabilityOptions::default(),
+            );
+            let lengths = if stepwise { 0..=t_len } else { t_len..=t_len };
+            for length in lengths {
+                match threads {
+                    1 => reachability.update_until(length),
+                    _ => reachability.update_until_parallel(length, threads),
+                }
+            }
+            reachability
+        };
+        for stepwise in [false, true] {
+            let arenas = &arenas[2 * stepwise as usize..];
+            let sequential = build(&arenas[0], 1, stepwise);
+            let parallel = build(&arenas[1], 4, stepwise);
+            assert_eq!(parallel.edge_count(), sequential.edge_count());
+            assert_eq!(derivations(&parallel), derivations(&sequential));
+        }
+    }
+}
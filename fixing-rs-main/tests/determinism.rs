@@ -0,0 +1,65 @@
+use std::{env, fs, path::Path, process::Command};
+
+const RUNS: usize = 3;
+
+/// Fixes the input in a new process of the binary, whose arenas, and thus
+/// the addresses the engine containers hash, differ from run to run.
+fn fix_in_process(lang: &str, input: &str, env: &str, dist: usize, output: &Path) -> Vec<u8> {
+    let status = Command::new(env!("CARGO_BIN_EXE_fixing-rs-main"))
+        .args(["fix", "--lang", lang, "--max-len"])
+        .arg(dist.to_string())
+        .args([
+            "--max-new-id",
+            "10",
+            "single",
+            "--input",
+            input,
+            "--env",
+            env,
+        ])
+        .arg("--output")
+        .arg(output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::read(output).unwrap()
+}
+
+fn assert_deterministic(lang: &str, input: &str, env: &str, dist: usize) {
+    let dir = env::temp_dir().join(format!(
+        "fixing-rs-determinism-{}-{}",
+        lang,
+        std::process::id()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output");
+    let first = fix_in_process(lang, input, env, dist, &output);
+    assert!(!first.is_empty());
+    for _ in 1..RUNS {
+        let next = fix_in_process(lang, input, env, dist, &output);
+        assert_eq!(next, first);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_deterministic_c() {
+    for (folder, dist) in [("decls2", 2), ("args", 2), ("printf", 2)] {
+        assert_deterministic(
+            "c",
+            &format!("src/tests/test_c/{}/c.tokens", folder),
+            &format!("src/tests/test_c/{}/env", folder),
+            dist,
+        );
+    }
+}
+
+#[test]
+fn test_deterministic_mj() {
+    assert_deterministic(
+        "mj",
+        "src/tests/test_mj/decl/block",
+        "src/tests/test_mj/decl/env",
+        1,
+    );
+}